@@ -0,0 +1,59 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tic_tac_toe::engine::{minimax_move, EngineConfig};
+use tic_tac_toe::game_model::GameBoard;
+use tic_tac_toe::random_source::SeededRandom;
+
+fn board_after(game_board: GameBoard, moves: &[usize]) -> GameBoard {
+    moves.iter().fold(game_board, |mut game_board, &position| {
+        _ = game_board.play_next_up_at_position(position);
+        game_board
+    })
+}
+
+fn win_detection(c: &mut Criterion) {
+    // No line yet, so every line of the board is checked
+    let classic = board_after(GameBoard::new_human_vs_human(), &[1, 5, 9, 3, 7, 4]);
+    let cube = board_after(
+        GameBoard::new_human_vs_human().with_cube(true),
+        &[1, 14, 27, 3, 25, 7],
+    );
+
+    c.bench_function("win detection 3x3", |b| {
+        b.iter(|| black_box(&classic).determine_winning_player().is_some());
+    });
+    c.bench_function("win detection 3x3x3 cube", |b| {
+        b.iter(|| black_box(&cube).determine_winning_player().is_some());
+    });
+}
+
+fn move_generation(c: &mut Criterion) {
+    let classic = board_after(GameBoard::new_human_vs_human(), &[5, 1]);
+    let gravity = board_after(GameBoard::new_human_vs_human().with_gravity(true), &[8, 5]);
+
+    c.bench_function("legal moves 3x3", |b| {
+        b.iter(|| black_box(&classic).legal_moves());
+    });
+    c.bench_function("legal moves with gravity", |b| {
+        b.iter(|| black_box(&gravity).legal_moves());
+    });
+}
+
+fn full_game_search(c: &mut Criterion) {
+    c.bench_function("minimax self-play 3x3", |b| {
+        b.iter(|| {
+            let mut random = SeededRandom::new(1);
+            let mut game_board = GameBoard::new_computer_vs_computer();
+
+            while let Some(position) =
+                minimax_move(&game_board, EngineConfig::FULL_DEPTH, &mut random)
+            {
+                _ = game_board.play_next_up_at_position(position);
+            }
+
+            game_board
+        });
+    });
+}
+
+criterion_group!(benches, win_detection, move_generation, full_game_search);
+criterion_main!(benches);
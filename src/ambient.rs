@@ -0,0 +1,90 @@
+use crate::registry::{StrategyEntry, STRATEGIES};
+use std::thread;
+use std::time::Duration;
+use tic_tac_toe::game_model::{execute_computer_turn, GameBoard, GameState, Piece};
+use tic_tac_toe::random_source::{RandomSource, ThreadRandom};
+
+// Slow enough to follow every move without it dragging
+const MOVE_DELAY: Duration = Duration::from_millis(1500);
+const BETWEEN_GAMES_DELAY: Duration = Duration::from_secs(4);
+
+#[derive(Default)]
+struct Tally {
+    games: usize,
+    x_wins: usize,
+    o_wins: usize,
+    draws: usize,
+}
+
+// Plays computer against computer until interrupted, with a new pairing of strategies each game
+pub fn ambient_main() -> ! {
+    let mut tally = Tally::default();
+    let mut last_result = String::from("No games finished yet");
+
+    loop {
+        let x_strategy = random_strategy();
+        let o_strategy = random_strategy();
+
+        let mut game_board = GameBoard::new_computer_vs_computer();
+
+        let heading = format!("{} (X) vs {} (O)", x_strategy.name, o_strategy.name);
+        render(&game_board, &heading, &tally, &last_result);
+
+        while !game_board.is_game_over() {
+            thread::sleep(MOVE_DELAY);
+
+            let strategy = match game_board.player_for_id(game_board.next_up).piece() {
+                Piece::X => x_strategy,
+                Piece::O => o_strategy,
+            };
+
+            if execute_computer_turn(&mut game_board, strategy).is_err() {
+                break;
+            }
+
+            render(&game_board, &heading, &tally, &last_result);
+        }
+
+        tally.games += 1;
+        last_result = match game_board.game_state() {
+            GameState::Winner(player, _) => {
+                let (winner, loser) = match player.piece() {
+                    Piece::X => {
+                        tally.x_wins += 1;
+                        (x_strategy.name, o_strategy.name)
+                    }
+                    Piece::O => {
+                        tally.o_wins += 1;
+                        (o_strategy.name, x_strategy.name)
+                    }
+                };
+                format!("Last game: {winner} beat {loser}")
+            }
+            GameState::Draw | GameState::InProgress => {
+                tally.draws += 1;
+                format!("Last game: {heading} was a draw")
+            }
+        };
+
+        render(&game_board, &heading, &tally, &last_result);
+        thread::sleep(BETWEEN_GAMES_DELAY);
+    }
+}
+
+fn random_strategy() -> &'static StrategyEntry {
+    &STRATEGIES[ThreadRandom.next_index(STRATEGIES.len())]
+}
+
+fn render(game_board: &GameBoard, heading: &str, tally: &Tally, last_result: &str) {
+    // Clear the screen and move the cursor home so each frame replaces the last
+    print!("\x1b[2J\x1b[H");
+
+    println!("Rusty 🦀 Tic Tac Toe, ambient mode (Ctrl+C to stop)\n");
+    println!("{heading}\n");
+    println!("{game_board}\n");
+    println!(
+        "Games: {}  X wins: {}  O wins: {}  Draws: {}",
+        tally.games, tally.x_wins, tally.o_wins, tally.draws
+    );
+    println!("{last_result}");
+}
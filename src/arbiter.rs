@@ -0,0 +1,159 @@
+use crate::exit_code::ExitReason;
+use crate::info::version_string;
+use crate::registry::{self, StrategyEntry};
+use std::io::{self, BufRead, Write};
+use tic_tac_toe::game_model::{GameBoard, Piece, Player};
+use tic_tac_toe::strategy::MoveStrategy;
+
+#[derive(clap::Args)]
+pub struct EngineArgs {
+    #[arg(
+        long,
+        value_name = "name",
+        value_parser = registry::strategy,
+        default_value = registry::default_strategy().name,
+        help = "Strategy to play with"
+    )]
+    strategy: &'static StrategyEntry,
+}
+
+// Declared in the handshake line, an opponent's handshake must declare the same
+const SETTINGS: &str = "variant=classic size=3";
+
+pub fn arbiter_main(args: &EngineArgs) -> ExitReason {
+    run(io::stdin().lock(), io::stdout(), args.strategy)
+}
+
+// Moves are read from `input` and written to `output` one position per line. The first player
+// plays X: send "go" to have the engine move first, or send the opening move instead.
+fn run(input: impl BufRead, mut output: impl Write, strategy: &StrategyEntry) -> ExitReason {
+    if writeln!(output, "{} {SETTINGS}", version_string()).is_err() {
+        return ExitReason::EndedEarly;
+    }
+
+    let mut game_board: Option<GameBoard> = None;
+
+    for line in input.lines() {
+        let Ok(line) = line else {
+            return ExitReason::EndedEarly;
+        };
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        // Lets two engines be wired straight to each other
+        if line.starts_with(env!("CARGO_PKG_NAME")) {
+            if line.ends_with(SETTINGS) {
+                continue;
+            }
+
+            eprintln!("Incompatible handshake '{line}', expected '{SETTINGS}'");
+            return ExitReason::InvalidInput;
+        }
+
+        if line.eq_ignore_ascii_case("quit") {
+            return ExitReason::EndedEarly;
+        }
+
+        let engine_moves_first = line.eq_ignore_ascii_case("go");
+
+        let game_board = match (&mut game_board, engine_moves_first) {
+            (None, true) => {
+                game_board.insert(GameBoard::new_with_first_up(Player::Computer(Piece::X)))
+            }
+            (None, false) => {
+                game_board.insert(GameBoard::new_with_first_up(Player::Human(Piece::X)))
+            }
+            (Some(_), true) => {
+                eprintln!("'go' is only valid before the first move");
+                return ExitReason::InvalidInput;
+            }
+            (Some(game_board), false) => game_board,
+        };
+
+        if !engine_moves_first {
+            let result = line
+                .parse::<usize>()
+                .map_err(|_| format!("'{line}' is not a position"))
+                .and_then(|position| {
+                    game_board
+                        .play_next_up_at_position(position)
+                        .map_err(String::from)
+                });
+
+            if let Err(error) = result {
+                eprintln!("{error}");
+                return ExitReason::InvalidInput;
+            }
+        }
+
+        if game_board.is_game_over() {
+            return ExitReason::for_game(game_board);
+        }
+
+        let Some(position) = strategy.choose_move(game_board) else {
+            return ExitReason::for_game(game_board);
+        };
+
+        if game_board.play_next_up_at_position(position).is_err()
+            || writeln!(output, "{position}").is_err()
+            || output.flush().is_err()
+        {
+            return ExitReason::EndedEarly;
+        }
+
+        if game_board.is_game_over() {
+            return ExitReason::for_game(game_board);
+        }
+    }
+
+    ExitReason::EndedEarly
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_and_one_reply_per_move() {
+        let (reason, output) = run_with_input("1\n");
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(reason, ExitReason::EndedEarly);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(SETTINGS));
+        assert!(lines[1]
+            .parse::<usize>()
+            .is_ok_and(|position| position != 1));
+    }
+
+    #[test]
+    fn test_engine_moves_first_on_go() {
+        let (_, output) = run_with_input("go\n");
+
+        assert_eq!(output.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_engine_accepts_its_own_handshake() {
+        let input = format!("{} {SETTINGS}\ngo\n", version_string());
+        let (_, output) = run_with_input(&input);
+
+        assert_eq!(output.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_illegal_move_is_invalid_input() {
+        assert_eq!(run_with_input("10\n").0, ExitReason::InvalidInput);
+        assert_eq!(run_with_input("go\ngo\n").0, ExitReason::InvalidInput);
+    }
+
+    fn run_with_input(input: &str) -> (ExitReason, String) {
+        let mut output = Vec::new();
+        let reason = run(input.as_bytes(), &mut output, registry::default_strategy());
+
+        (reason, String::from_utf8(output).unwrap_or_default())
+    }
+}
@@ -0,0 +1,72 @@
+use crate::exit_code::ExitReason;
+use std::time::Instant;
+use tic_tac_toe::engine::{search_nodes, EngineConfig};
+use tic_tac_toe::game_model::GameBoard;
+use tic_tac_toe::table::Table;
+
+#[derive(clap::Args)]
+pub struct BenchArgs {
+    #[arg(
+        long,
+        value_name = "n",
+        default_value_t = 20,
+        help = "How many times to search each position"
+    )]
+    searches: usize,
+}
+
+// Times the minimax search on a few positions of each kind of board, to compare changes to the
+// engine. Run it from a release build.
+pub fn bench_main(args: &BenchArgs) -> ExitReason {
+    let searches = args.searches.max(1);
+    let mut table = Table::new(&["Position", "Nodes", "Time per search", "Nodes/s"]);
+
+    for (name, game_board) in positions() {
+        let config = EngineConfig {
+            max_depth: game_board.search_depth(),
+        };
+
+        let started = Instant::now();
+        let nodes: usize = (0..searches)
+            .map(|_| search_nodes(&game_board, config))
+            .sum();
+        let seconds = started.elapsed().as_secs_f64().max(f64::EPSILON);
+
+        table.add_row(vec![
+            name.to_string(),
+            (nodes / searches).to_string(),
+            format!("{:.3}ms", seconds * 1000.0 / count_to_f64(searches)),
+            format!("{:.0}", count_to_f64(nodes) / seconds),
+        ]);
+    }
+
+    print!("{searches} searches of each position:\n{table}");
+    ExitReason::Completed
+}
+
+fn positions() -> Vec<(&'static str, GameBoard)> {
+    let after = |game_board: GameBoard, moves: &[usize]| {
+        moves.iter().fold(game_board, |mut game_board, &position| {
+            _ = game_board.play_next_up_at_position(position);
+            game_board
+        })
+    };
+
+    vec![
+        ("Empty 3x3", GameBoard::new_human_vs_human()),
+        (
+            "3x3 after a corner",
+            after(GameBoard::new_human_vs_human(), &[1]),
+        ),
+        ("Empty 4x4", GameBoard::new_human_vs_human().with_size(4)),
+        (
+            "Empty 3x3x3 cube",
+            GameBoard::new_human_vs_human().with_cube(true),
+        ),
+    ]
+}
+
+#[allow(clippy::cast_precision_loss)]
+const fn count_to_f64(count: usize) -> f64 {
+    count as f64
+}
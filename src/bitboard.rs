@@ -0,0 +1,202 @@
+use crate::game_model::{CellState, Piece};
+
+// A bit for each cell, set in the mask of whatever is there. Cell 0 is position 1, and cells count
+// on row by row and then layer by layer, so a cube of the largest size still fits.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<Vec<CellState>>", into = "Vec<Vec<CellState>>")
+)]
+pub struct Bitboard {
+    size: usize,
+    layers: usize,
+    x: u128,
+    o: u128,
+    blocked: u128,
+}
+
+impl Bitboard {
+    pub const MAX_CELLS: usize = u128::BITS as usize;
+
+    pub fn empty(size: usize, layers: usize) -> Self {
+        assert!(
+            size * size * layers <= Self::MAX_CELLS,
+            "A board holds at most {} cells",
+            Self::MAX_CELLS
+        );
+
+        Self {
+            size,
+            layers,
+            x: 0,
+            o: 0,
+            blocked: 0,
+        }
+    }
+
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    pub const fn layers(&self) -> usize {
+        self.layers
+    }
+
+    // Static, so that the board can still lend out its cells by reference
+    pub const fn cell(&self, index: usize) -> &'static CellState {
+        let bit = 1 << index;
+
+        if self.x & bit != 0 {
+            &CellState::Occupied(Piece::X)
+        } else if self.o & bit != 0 {
+            &CellState::Occupied(Piece::O)
+        } else if self.blocked & bit != 0 {
+            &CellState::Blocked
+        } else {
+            &CellState::Empty
+        }
+    }
+
+    pub const fn set(&mut self, index: usize, cell: CellState) {
+        let bit = 1 << index;
+        self.x &= !bit;
+        self.o &= !bit;
+        self.blocked &= !bit;
+
+        match cell {
+            CellState::Empty => (),
+            CellState::Occupied(Piece::X) => self.x |= bit,
+            CellState::Occupied(Piece::O) => self.o |= bit,
+            CellState::Blocked => self.blocked |= bit,
+        }
+    }
+
+    pub const fn pieces(&self, piece: Piece) -> u128 {
+        match piece {
+            Piece::X => self.x,
+            Piece::O => self.o,
+        }
+    }
+
+    // The same cells moved within each layer, the cell at each row and column going to wherever
+    // the move puts it
+    pub fn moved(&self, to: impl Fn(usize, usize) -> (usize, usize)) -> Self {
+        let mut moved = Self::empty(self.size, self.layers);
+        let layer_cells = self.size * self.size;
+
+        for index in 0..layer_cells * self.layers {
+            let cell = *self.cell(index);
+            if cell != CellState::Empty {
+                let within_layer = index % layer_cells;
+                let (row, col) = to(within_layer / self.size, within_layer % self.size);
+                moved.set(index - within_layer + row * self.size + col, cell);
+            }
+        }
+
+        moved
+    }
+
+    // The piece filling every cell of the mask, if one does
+    pub const fn owner(&self, mask: u128) -> Option<Piece> {
+        if self.x & mask == mask {
+            Some(Piece::X)
+        } else if self.o & mask == mask {
+            Some(Piece::O)
+        } else {
+            None
+        }
+    }
+}
+
+impl TryFrom<Vec<Vec<CellState>>> for Bitboard {
+    type Error = String;
+
+    // Square layers of rows, one layer after another
+    fn try_from(rows: Vec<Vec<CellState>>) -> Result<Self, Self::Error> {
+        let size = rows.first().map_or(0, Vec::len);
+        if size == 0
+            || !rows.len().is_multiple_of(size)
+            || rows.len() * size > Self::MAX_CELLS
+            || rows.iter().any(|row| row.len() != size)
+        {
+            return Err(format!(
+                "Expected square layers of at most {} cells",
+                Self::MAX_CELLS
+            ));
+        }
+
+        let mut bitboard = Self::empty(size, rows.len() / size);
+        for (index, &cell) in rows.iter().flatten().enumerate() {
+            bitboard.set(index, cell);
+        }
+
+        Ok(bitboard)
+    }
+}
+
+impl From<Bitboard> for Vec<Vec<CellState>> {
+    fn from(bitboard: Bitboard) -> Self {
+        (0..bitboard.layers * bitboard.size)
+            .map(|row| {
+                (0..bitboard.size)
+                    .map(|col| *bitboard.cell(row * bitboard.size + col))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cells_hold_whatever_was_set_last() {
+        let mut bitboard = Bitboard::empty(3, 1);
+        bitboard.set(0, CellState::Occupied(Piece::X));
+        bitboard.set(4, CellState::Blocked);
+        bitboard.set(4, CellState::Occupied(Piece::O));
+
+        assert_eq!(*bitboard.cell(0), CellState::Occupied(Piece::X));
+        assert_eq!(*bitboard.cell(4), CellState::Occupied(Piece::O));
+        assert_eq!(*bitboard.cell(8), CellState::Empty);
+        assert_eq!(bitboard.pieces(Piece::O), 1 << 4);
+    }
+
+    #[test]
+    fn test_a_mask_is_owned_only_when_one_piece_fills_it() {
+        let mut bitboard = Bitboard::empty(3, 1);
+        for index in [0, 1] {
+            bitboard.set(index, CellState::Occupied(Piece::X));
+        }
+
+        assert_eq!(bitboard.owner(0b011), Some(Piece::X));
+        assert_eq!(bitboard.owner(0b111), None);
+    }
+
+    #[test]
+    fn test_moved_cells_stay_in_their_layer() {
+        let mut bitboard = Bitboard::empty(3, 2);
+        bitboard.set(0, CellState::Occupied(Piece::X));
+        bitboard.set(9, CellState::Blocked);
+
+        let moved = bitboard.moved(|row, col| (col, 2 - row));
+
+        assert_eq!(*moved.cell(2), CellState::Occupied(Piece::X));
+        assert_eq!(*moved.cell(11), CellState::Blocked);
+        assert_eq!(*moved.cell(0), CellState::Empty);
+    }
+
+    #[test]
+    fn test_rows_convert_both_ways() {
+        let mut bitboard = Bitboard::empty(3, 2);
+        bitboard.set(17, CellState::Occupied(Piece::O));
+        let rows: Vec<Vec<CellState>> = bitboard.clone().into();
+
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[5][2], CellState::Occupied(Piece::O));
+        assert_eq!(Bitboard::try_from(rows), Ok(bitboard));
+        assert!(Bitboard::try_from(vec![vec![CellState::Empty; 3]; 2]).is_err());
+    }
+}
@@ -1,103 +1,1148 @@
-use crate::game_model::{execute_computer_turn, GameBoard, GameState, Player};
-use std::io::Write;
-use std::time::Duration;
-use std::{io, thread};
+use crate::event_log::EventLog;
+use crate::exit_code::ExitReason;
+use crate::input_session::{read_session, InputSession, SessionHeader};
+use crate::local_data::{self, Interface, Settings};
+use crate::palette::Palette;
+use crate::registry::{self, OpponentEntry, StrategyEntry, VARIANT_HEADING};
+use crate::speedrun::Speedrun;
+use crate::stats::{self, LifetimeStats};
+use clap::Parser;
+use owo_colors::{OwoColorize, Style};
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::io::{IsTerminal, Write};
+use std::rc::Rc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use std::{env, io, thread};
+use tic_tac_toe::frontend::{run_game, Frontend};
+use tic_tac_toe::game::Game;
+use tic_tac_toe::game_model::{
+    threats, CellState, Difficulty, GameBoard, GameBoardBuilder, GameError, Move, MoveRecord,
+    Piece, Player, Scoreboard,
+};
+use tic_tac_toe::numbering::Numbering;
+use tic_tac_toe::patterns::detect_patterns;
+use tic_tac_toe::random_source::seed_thread_random;
 
-pub fn cli_main() {
-    let mut game_board = GameBoard::new();
-    println!("\nWelcome to Rusty 🦀 Tic Tac Toe:\n{game_board}");
+// The cli mode's options as clap reads them. Options that depend on each other are checked in
+// CliOptions::from_args.
+#[derive(clap::Args)]
+pub struct CliArgs {
+    #[arg(
+        long,
+        value_name = "name",
+        value_parser = registry::strategy,
+        conflicts_with_all = ["opponent", "difficulty"],
+        help = "Computer strategy to play against"
+    )]
+    strategy: Option<&'static StrategyEntry>,
+    #[arg(
+        long,
+        value_name = "name",
+        value_parser = registry::opponent,
+        conflicts_with = "difficulty",
+        help = "Play a named opponent: Randy, Blocky, Forky or Minnie Max"
+    )]
+    opponent: Option<&'static OpponentEntry>,
+    #[arg(
+        long,
+        value_name = "level",
+        value_parser = Difficulty::from_name,
+        help = "Pick the strategy by difficulty: easy, medium or hard"
+    )]
+    difficulty: Option<Difficulty>,
+    #[arg(long, help = "Mark cells that complete a line next move")]
+    threats: bool,
+    #[arg(long, help = "Explain classic traps and mistakes after each move")]
+    explain: bool,
+    #[arg(
+        long,
+        value_name = "scheme",
+        value_parser = Numbering::from_name,
+        help = "Position numbering: standard, numpad (7-8-9 on top) or zero (0-8)"
+    )]
+    numbering: Option<Numbering>,
+    #[arg(
+        long,
+        help = "Leave empty cells blank instead of showing their position numbers"
+    )]
+    no_cell_numbers: bool,
+    #[arg(long, help = "Show a plain message instead of the thinking spinner")]
+    reduced_motion: bool,
+    #[arg(
+        long,
+        value_name = "ms",
+        default_value_t = 0,
+        help = "Show the computer thinking for at least this long"
+    )]
+    think_delay: u64,
+    #[arg(
+        long,
+        help = "Ring the terminal bell on invalid moves and when the game ends"
+    )]
+    bell: bool,
+    #[arg(long, help = "Plain text without colors, as when NO_COLOR is set")]
+    no_color: bool,
+    #[arg(
+        long,
+        value_name = "name",
+        value_parser = Palette::from_name,
+        help = "Piece colors: standard, deuteranopia, protanopia or tritanopia"
+    )]
+    palette: Option<Palette>,
+    #[arg(long, help = "Two people take turns at the same terminal, no computer")]
+    hotseat: bool,
+    #[arg(
+        long,
+        help = "Race the clock to beat the easy or medium computer, for the leaderboard"
+    )]
+    speedrun: bool,
+    #[arg(
+        long,
+        value_name = "n",
+        value_parser = GameBoard::parse_size,
+        default_value_t = GameBoard::CLASSIC_SIZE,
+        help = "Play on an n by n board, from 3 to 5"
+    )]
+    size: usize,
+    #[arg(
+        long = "3d",
+        help_heading = VARIANT_HEADING,
+        help = "Play on a cube of n stacked n by n boards, with lines through the layers too"
+    )]
+    cube: bool,
+    // Checked against the board size once every option is read
+    #[arg(
+        long,
+        value_name = "k",
+        help = "Win with k in a row instead of a full line, from 3 to the board size"
+    )]
+    win_length: Option<String>,
+    #[arg(
+        long,
+        help_heading = VARIANT_HEADING,
+        help = "Misère rules, where completing a line loses"
+    )]
+    misere: bool,
+    #[arg(
+        long,
+        help_heading = VARIANT_HEADING,
+        help = "Wild rules, where either player may place an X or an O"
+    )]
+    wild: bool,
+    #[arg(
+        long,
+        help_heading = VARIANT_HEADING,
+        help = "Three Men's Morris, where each player's three pieces slide once they are down"
+    )]
+    morris: bool,
+    #[arg(
+        long,
+        help_heading = VARIANT_HEADING,
+        value_name = "n",
+        value_parser = GameBoard::parse_notakto_boards,
+        help = "Notakto on n boards, from 1 to 3, where both players place X and the last line loses"
+    )]
+    notakto: Option<usize>,
+    #[arg(
+        long,
+        help_heading = VARIANT_HEADING,
+        help = "Pieces drop to the lowest empty cell of the column entered, as in Connect Four"
+    )]
+    gravity: bool,
+    #[arg(
+        long,
+        help_heading = VARIANT_HEADING,
+        value_name = "n",
+        value_parser = GameBoard::parse_blocked_cells,
+        help = "Start with n random cells, 1 or 2, blocked for both players"
+    )]
+    blocked: Option<usize>,
+    #[arg(
+        long,
+        value_name = "n",
+        help = "Allow n hints per game instead of as many as wanted"
+    )]
+    hints: Option<usize>,
+    #[arg(
+        long,
+        value_name = "x|o",
+        value_parser = Piece::from_name,
+        help = "Play this piece instead of a random one"
+    )]
+    piece: Option<Piece>,
+    #[arg(
+        long,
+        value_name = "human|computer",
+        value_parser = ["human", "computer"],
+        help = "Who moves first instead of a coin flip"
+    )]
+    first: Option<String>,
+    #[arg(long, value_name = "name", help = "Your name, shown in game messages")]
+    name: Option<String>,
+    #[arg(long, value_name = "name", help = "The computer's name")]
+    computer_name: Option<String>,
+    #[arg(
+        long,
+        value_name = "file",
+        help = "Record every input with its timing, and the seed and options to play them again"
+    )]
+    record: Option<String>,
+    #[arg(
+        long,
+        value_name = "file",
+        help = "Replay a session recorded with --record, with the options it was recorded with"
+    )]
+    replay_input: Option<String>,
+    #[arg(
+        long,
+        value_name = "file",
+        help = "Write every move, undo, redo and hint to a JSON Lines file"
+    )]
+    event_log: Option<String>,
+}
 
-    let first_up = game_board.next_up;
-    let first_up_player = game_board.player_for_id(first_up);
+// A recording's options, read back when it is replayed
+#[derive(Parser)]
+#[command(no_binary_name = true)]
+struct RecordedArgs {
+    #[command(flatten)]
+    cli: CliArgs,
+}
 
-    match first_up_player {
-        Player::Human(piece) => {
-            println!("\nYou are first up for this game. You have been assigned piece: {piece}");
+// The cli mode's options as typed, for the header of a recording. Leaves out the program and mode
+// names, --seed, and where inputs are recorded to or replayed from.
+pub fn typed_options(command_line: impl IntoIterator<Item = String>) -> Vec<String> {
+    const LEFT_OUT: [&str; 3] = ["--seed", "--record", "--replay-input"];
+
+    let mut typed = Vec::new();
+    let mut mode_seen = false;
+    let mut command_line = command_line.into_iter().skip(1);
+
+    while let Some(arg) = command_line.next() {
+        if LEFT_OUT.contains(&arg.as_str()) {
+            command_line.next();
+        } else if LEFT_OUT
+            .iter()
+            .any(|option| arg.starts_with(&format!("{option}=")))
+        {
+        } else if mode_seen {
+            typed.push(arg);
+        } else {
+            mode_seen = true;
         }
+    }
 
-        Player::Computer(piece) => println!(
-            "\nFirst up for this game is the {}. They have been assigned piece: {piece}",
-            first_up_player.name()
-        ),
-    };
+    typed
+}
+
+pub struct CliOptions {
+    show_threats: bool,
+    explain_moves: bool,
+    strategy: &'static StrategyEntry,
+    opponent: Option<&'static OpponentEntry>,
+    numbering: Numbering,
+    cell_numbers: bool,
+    reduced_motion: bool,
+    think_delay: Duration,
+    bell: bool,
+    color: bool,
+    palette: Palette,
+    hotseat: bool,
+    speedrun: bool,
+    size: usize,
+    cube: bool,
+    win_length: Option<usize>,
+    misere: bool,
+    wild: bool,
+    morris: bool,
+    gravity: bool,
+    notakto: Option<usize>,
+    blocked: usize,
+    hint_budget: Option<usize>,
+    game_setup: GameBoardBuilder,
+    record_path: Option<String>,
+    // The inputs to play instead of reading them from the terminal
+    replay: Option<Vec<(Duration, String)>>,
+    // Written at the top of a recording
+    header: SessionHeader,
+    event_log_path: Option<String>,
+    // A stored setting rather than an option, switched with z during a game
+    interface: Interface,
+}
+
+impl CliOptions {
+    // Replaying a recording that has a header plays with the options it was recorded with, and
+    // seeds every random draw the same way, so that the computer makes the same moves again.
+    // Recording seeds them too, with --seed or a seed of its own, for the header.
+    pub fn from_command_line(
+        mut args: CliArgs,
+        seed: Option<u64>,
+        typed: Vec<String>,
+    ) -> Result<Self, String> {
+        let mut header = SessionHeader {
+            seed: seed.unwrap_or_else(rand::random),
+            options: typed,
+        };
+        let (replay, replay_header) = match args.replay_input.as_deref().map(read_session) {
+            Some(session) => session.map(|session| (Some(session.inputs), session.header))?,
+            None => (None, None),
+        };
+        let seeded = args.record.is_some() || replay_header.is_some();
+
+        if let Some(recorded) = replay_header {
+            let conflicts = (!header.options.is_empty() && header.options != recorded.options)
+                || seed.is_some_and(|seed| seed != recorded.seed);
+            if conflicts {
+                return Err(format!(
+                    "'--replay-input' plays the session with the options it was recorded with, leave out any others. They were: --seed {} {}",
+                    recorded.seed,
+                    recorded.options.join(" ")
+                ));
+            }
+
+            let record = args.record.take();
+            args = RecordedArgs::try_parse_from(&recorded.options)
+                .map_err(|error| format!("The recorded options cannot be used: {error}"))?
+                .cli;
+            args.record = record;
+            header = recorded;
+        }
+
+        if seeded {
+            seed_thread_random(header.seed);
+        }
+
+        let mut options = Self::from_args(args)?;
+        options.replay = replay;
+        options.header = header;
+        Ok(options)
+    }
+
+    fn from_args(args: CliArgs) -> Result<Self, String> {
+        let strategy = match (args.strategy, args.opponent, args.difficulty) {
+            (Some(strategy), _, _) => strategy,
+            (None, Some(opponent), _) => &opponent.strategy,
+            (None, None, Some(difficulty)) => registry::strategy_for(difficulty),
+            (None, None, None) => registry::default_strategy(),
+        };
+
+        let mut game_setup = GameBoardBuilder::new();
+        if let Some(opponent) = args.opponent {
+            game_setup = game_setup.computer_name(opponent.name());
+        }
+        if let Some(piece) = args.piece {
+            game_setup = game_setup.human_piece(piece);
+        }
+        if let Some(first) = args.first {
+            game_setup = game_setup.human_first(first == "human");
+        }
+        if let Some(name) = args.name {
+            game_setup = game_setup.human_name(name);
+        }
+        if let Some(name) = args.computer_name {
+            game_setup = game_setup.computer_name(name);
+        }
+
+        let mut options = Self {
+            show_threats: args.threats,
+            explain_moves: args.explain,
+            strategy,
+            opponent: args.opponent,
+            numbering: args.numbering.unwrap_or_default(),
+            cell_numbers: !args.no_cell_numbers,
+            reduced_motion: args.reduced_motion,
+            think_delay: Duration::from_millis(args.think_delay),
+            bell: args.bell,
+            color: !args.no_color && color_supported(),
+            palette: args.palette.unwrap_or_default(),
+            hotseat: args.hotseat,
+            speedrun: args.speedrun,
+            size: args.size,
+            cube: args.cube,
+            win_length: None,
+            misere: args.misere,
+            wild: args.wild,
+            morris: args.morris,
+            gravity: args.gravity,
+            notakto: args.notakto,
+            blocked: args.blocked.unwrap_or(0),
+            hint_budget: args.hints,
+            game_setup,
+            record_path: args.record,
+            replay: None,
+            header: SessionHeader::default(),
+            event_log_path: args.event_log,
+            interface: Settings::load().interface,
+        };
+
+        if let Some(text) = args.win_length {
+            options.win_length = Some(GameBoard::parse_win_length(&text, options.size)?);
+        }
+
+        if options.morris
+            && (options.size != GameBoard::CLASSIC_SIZE
+                || options.cube
+                || options.wild
+                || options.win_length.is_some())
+        {
+            return Err(
+                "Three Men's Morris is played on the classic board, not with '--size', '--3d', '--wild' or '--win-length'"
+                    .to_string(),
+            );
+        }
+
+        if options.gravity && (options.cube || options.morris) {
+            return Err("Gravity needs a flat board, not '--3d' or '--morris'".to_string());
+        }
+
+        if options.notakto.is_some()
+            && (options.cube || options.morris || options.wild || options.gravity)
+        {
+            return Err(
+                "Notakto is played on flat boards, not with '--3d', '--morris', '--wild' or '--gravity'"
+                    .to_string(),
+            );
+        }
+
+        if options.speedrun {
+            if options.hotseat {
+                return Err("Speedruns are against the computer, not '--hotseat'".to_string());
+            }
+            if !Speedrun::allowed(options.strategy.difficulty) {
+                return Err(format!(
+                    "Speedruns are against the easy or medium computer, not {}",
+                    options.strategy.difficulty.name()
+                ));
+            }
+            // So that every time on a leaderboard was got the same way
+            let classic = options.size == GameBoard::CLASSIC_SIZE
+                && options.win_length.is_none()
+                && !(options.cube
+                    || options.misere
+                    || options.wild
+                    || options.morris
+                    || options.gravity)
+                && options.notakto.is_none()
+                && options.blocked == 0
+                && options.hint_budget.is_none();
+            if !classic {
+                return Err(
+                    "Speedruns are on the classic board and rules, not with variants, '--size', '--win-length', '--blocked' or '--hints'"
+                        .to_string(),
+                );
+            }
+        }
+
+        Ok(options)
+    }
+
+    // Zen hides everything but the board and the prompt. What was asked for, such as hints,
+    // threats, explanations and the speedrun clock, still shows.
+    fn zen(&self) -> bool {
+        self.interface == Interface::Zen
+    }
+
+    // Under gravity the numbers entered are columns, so cells show none
+    fn shows_cell_numbers(&self, game_board: &GameBoard) -> bool {
+        self.cell_numbers && !game_board.is_gravity()
+    }
+
+    fn paint(&self, text: impl Display, style: Style) -> String {
+        if self.color {
+            text.style(style).to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn piece_text(&self, piece: Piece) -> String {
+        let (red, green, blue) = self.palette.piece_color(piece);
+        self.paint(piece, Style::new().truecolor(red, green, blue).bold())
+    }
+}
 
-    let game_end_message = game_loop(&mut game_board);
-    println!("{game_end_message}");
-    println!("Thanks, play again soon!");
+// Only on a terminal, and not once NO_COLOR is set to anything, as https://no-color.org asks
+fn color_supported() -> bool {
+    io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
 }
 
-fn game_loop(game_board: &mut GameBoard) -> String {
-    let mut game_end_message = String::new();
+pub fn cli_main(options: &mut CliOptions) -> ExitReason {
+    let record = options
+        .record_path
+        .as_deref()
+        .map(|path| (path, &options.header));
+    let mut input_session = match InputSession::new(record, options.replay.take()) {
+        Ok(input_session) => input_session,
+        Err(error) => {
+            println!("{error}");
+            return ExitReason::InvalidUsage;
+        }
+    };
+
+    // Kept over every game of the session
+    let events = match EventLog::new(options.event_log_path.as_deref()) {
+        Ok(events) => Rc::new(RefCell::new(events)),
+        Err(error) => {
+            println!("{error}");
+            return ExitReason::InvalidUsage;
+        }
+    };
+
+    let opponent = options.opponent.filter(|_| !options.hotseat);
+    let mut scoreboard = Scoreboard::new();
+    let mut speedrun = options
+        .speedrun
+        .then(|| Speedrun::new(options.strategy.difficulty));
+
+    loop {
+        let mut game_board = if options.hotseat {
+            GameBoard::new_human_vs_human().with_size(options.size)
+        } else {
+            options.game_setup.clone().size(options.size).build()
+        }
+        .with_cube(options.cube);
+        if let Some(win_length) = options.win_length {
+            game_board = game_board.with_win_length(win_length);
+        }
+        if let Some(hint_budget) = options.hint_budget {
+            game_board = game_board.with_hint_budget(hint_budget);
+        }
+        game_board = game_board
+            .with_misere(options.misere)
+            .with_wild(options.wild)
+            .with_morris(options.morris)
+            .with_gravity(options.gravity)
+            .with_notakto(options.notakto)
+            .with_blocked_cells(options.blocked);
+
+        if options.zen() {
+            println!("\n{}", render_board(&game_board, options));
+        } else if scoreboard.games() == 0 {
+            println!(
+                "\nWelcome to Rusty 🦀 Tic Tac Toe:\n{}",
+                render_board(&game_board, options)
+            );
+
+            if !options.shows_cell_numbers(&game_board) {
+                println!(
+                    "\nPositions are numbered:\n{}",
+                    reference_grid(&game_board, options.numbering)
+                );
+            }
+
+            if game_board.is_cube() {
+                println!(
+                    "\nLines can run along a layer or straight or diagonally through the layers."
+                );
+            }
 
-    while game_end_message.is_empty() {
-        let next_player_up = game_board.player_for_id(game_board.next_up);
+            if game_board.win_length() < game_board.size() {
+                println!("\nGet {} in a row to win.", game_board.win_length());
+            }
+
+            if game_board.is_notakto() && game_board.layers() > 1 {
+                println!(
+                    "\nNotakto: both players place X, and a board is dead once it has a line. Whoever completes the line on the last board loses."
+                );
+            } else if game_board.is_notakto() {
+                println!("\nNotakto: both players place X, and whoever completes a line loses.");
+            } else if game_board.is_misere() {
+                println!("\nMisère rules: whoever completes a line loses.");
+            }
+
+            if game_board.is_wild() {
+                println!(
+                    "\nWild rules: place an X or an O each turn, such as 5x. Whoever completes a line wins."
+                );
+            }
+
+            if game_board.is_morris() {
+                println!(
+                    "\nThree Men's Morris: each player has {} pieces. Once they are all down, slide one to an empty cell next to it each turn, such as 1-2.",
+                    GameBoard::MORRIS_PIECES
+                );
+            }
 
-        let turn_result = match next_player_up {
-            Player::Computer(_) => {
-                display_spinner_with_message("The computer is thinking...");
-                execute_computer_turn(game_board)
+            if !game_board.blocked_positions().is_empty() {
+                println!("\nCells marked # are blocked, neither player can go there.");
             }
 
-            Player::Human(_) => execute_human_turn(game_board),
+            if game_board.is_gravity() {
+                println!(
+                    "\nGravity: enter a column, numbered 1 to {} from the left, and the piece drops to the lowest empty cell in it.",
+                    game_board.size()
+                );
+            }
+
+            if let Some(hints_left) = game_board.hints_left() {
+                println!("\nHints each game: {hints_left}");
+            }
+
+            if let Some(opponent) = opponent {
+                let head_to_head = LifetimeStats::load()
+                    .map(|stats| stats.against(opponent.name()))
+                    .unwrap_or_default();
+                println!("\n{}", opponent.scouting_report(head_to_head));
+            }
+        } else {
+            println!("\nNew game:\n{}", render_board(&game_board, options));
+        }
+
+        if let Some(run) = speedrun.as_ref().filter(|run| run.games() == 1) {
+            println!(
+                "\nSpeedrun: beat the {} computer as fast as you can. The clock is running.",
+                run.difficulty().name()
+            );
+        }
+
+        if !options.zen() {
+            announce_first_up(&game_board, options);
+
+            if let Some(opponent) = opponent {
+                println!(
+                    "\n{}: \"{}\"",
+                    opponent.name(),
+                    opponent.remark(&game_board)
+                );
+            }
+        }
+
+        let mut game = Game::new(game_board);
+        game.add_observer(Rc::clone(&events));
+        game.start();
+        let mut terminal = Terminal {
+            game,
+            options,
+            input_session: &mut input_session,
+            speedrun: speedrun.as_ref(),
         };
+        run_game(&mut terminal);
+        let game_board = terminal.game.into_board();
+
+        // Quitting part way through ends the session
+        if !game_board.is_game_over() {
+            println!("Thanks, play again soon!");
+            return ExitReason::for_game(&game_board);
+        }
 
-        println!("\nGame board:\n{game_board}");
+        if let Some(opponent) = opponent.filter(|_| !options.zen()) {
+            println!("{}: \"{}\"", opponent.name(), opponent.remark(&game_board));
+        }
+        ring_bell(options);
 
-        match turn_result {
-            Ok(()) => {
-                game_end_message = game_board.game_message();
+        scoreboard.record(&game_board);
+        if !options.hotseat {
+            if let Err(error) = stats::record_game(&game_board, options.strategy) {
+                println!("\nStatistics not saved: {error}");
             }
+        }
+        if !options.zen() {
+            print!("\nScoreboard:\n{scoreboard}");
+        }
 
-            Err(error) => {
-                if error == "exit" {
-                    game_end_message = "\nExiting the game".to_string();
-                } else {
-                    // Output the error condition and continue looping
-                    println!("\nError: {error:?}");
+        // A run only stops for a win, anything else goes straight on to the next game
+        if let Some(run) = &mut speedrun {
+            match run.game_finished(&game_board) {
+                Some(time) => {
+                    let placing = stats::record_run_time(run.difficulty(), time);
+                    println!("\n⏱ {}", run.result_text(time, &placing));
+                }
+                None => {
+                    println!(
+                        "\n⏱ {} and counting, on to game {}.",
+                        run.clock(),
+                        run.games()
+                    );
+                    continue;
                 }
             }
         }
+
+        if !play_again(&mut input_session, options) {
+            println!("Thanks, play again soon!");
+            return ExitReason::for_game(&game_board);
+        }
+
+        if let Some(run) = &mut speedrun {
+            run.restart();
+        }
     }
+}
+
+fn announce_first_up(game_board: &GameBoard, options: &CliOptions) {
+    let first_up_player = game_board.player_for_id(game_board.next_up);
+
+    match first_up_player {
+        Player::Human(piece) if options.hotseat => {
+            println!("\nTwo players, one terminal. {piece} goes first.");
+        }
+
+        Player::Human(piece) => {
+            if let Some(name) = game_board.name_of(first_up_player) {
+                print!("\nWelcome {name}.");
+            }
+            println!("\nYou are first up for this game. You have been assigned piece: {piece}");
+        }
 
-    game_end_message
+        Player::Computer(piece) => match game_board.name_of(first_up_player) {
+            Some(name) => println!(
+                "\nFirst up for this game is {name}, the computer. They have been assigned piece: {piece}"
+            ),
+            None => println!(
+                "\nFirst up for this game is the {}. They have been assigned piece: {piece}",
+                first_up_player.name()
+            ),
+        },
+    };
 }
 
-fn execute_human_turn(game_board: &mut GameBoard) -> Result<(), String> {
-    // Prompt the user
+fn play_again(input_session: &mut InputSession, options: &CliOptions) -> bool {
     print!(
-        "\nEnter a number from the available positions: {:?} or (q/Q to quit). ",
-        game_board.get_available_positions()
+        "\n{} ",
+        options.paint("Play again? (y/n)", Style::new().bold())
     );
+    if io::stdout().flush().is_err() {
+        return false;
+    }
+
+    // Running out of scripted input counts as a no
+    matches!(
+        input_session.read_line(),
+        Ok(Some(answer)) if matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+    )
+}
+
+// The terminal as a frontend, for one game of the session
+struct Terminal<'a> {
+    game: Game,
+    options: &'a mut CliOptions,
+    input_session: &'a mut InputSession,
+    speedrun: Option<&'a Speedrun>,
+}
+
+impl Frontend for Terminal<'_> {
+    fn game(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    fn prompt_move(&mut self) -> Result<(), GameError> {
+        execute_human_turn(&mut self.game, self.options, self.input_session)
+    }
+
+    fn play_computer_move(&mut self) -> Result<(), GameError> {
+        let options = &*self.options;
+        let game = &mut self.game;
+        let chosen = think_visibly(options, || game.play_computer_turn(options.strategy))?;
+
+        if !options.zen() {
+            println!(
+                "\nThe computer {}",
+                move_text(&chosen, game.board(), options)
+            );
+        }
+        Ok(())
+    }
+
+    fn render_board(&mut self, board_before_turn: &GameBoard) {
+        let game_board = self.game.board();
+
+        if self.options.zen() {
+            println!("\n{}", render_board(game_board, self.options));
+        } else {
+            println!("\nGame board:\n{}", render_board(game_board, self.options));
+        }
+        if let Some(run) = self.speedrun {
+            println!("⏱ {}", run.clock());
+        }
+
+        if self.options.explain_moves {
+            for pattern in detect_patterns(board_before_turn, game_board) {
+                println!("\n💡 {}: {}", pattern.name, pattern.explanation);
+            }
+        }
+    }
+
+    fn report_error(&mut self, error: &GameError) {
+        println!("\nError: {error}");
+        ring_bell(self.options);
+    }
+
+    fn announce_result(&mut self) {
+        println!("{}", game_message(&self.game, self.options));
+    }
+}
+
+fn execute_human_turn(
+    game: &mut Game,
+    options: &mut CliOptions,
+    input_session: &mut InputSession,
+) -> Result<(), GameError> {
+    let game_board = game.board();
+
+    // Prompt the user
+    let next_piece = options.piece_text(*game_board.player_for_id(game_board.next_up).piece());
+    if options.zen() {
+        print!(
+            "\n{next_piece}{} ",
+            options.paint(" to play:", Style::new().bold())
+        );
+    } else if game_board.is_human_vs_human() {
+        print!(
+            "\n{next_piece}{}",
+            options.paint(" to play.", Style::new().bold())
+        );
+    }
+
+    if !options.zen() {
+        let size = game_board.size();
+        let choices = if game_board.must_slide() {
+            let slides: Vec<String> = game_board
+                .legal_moves()
+                .into_iter()
+                .filter_map(|chosen| {
+                    chosen.from.map(|from| {
+                        format!(
+                            "{}-{}",
+                            options.numbering.label(from, size),
+                            options.numbering.label(chosen.position, size)
+                        )
+                    })
+                })
+                .collect();
+            format!(
+                "Enter a slide from one of your pieces to an empty cell next to it: {}",
+                slides.join(", ")
+            )
+        } else {
+            let piece_choice = if game_board.is_wild() {
+                " followed by x or o"
+            } else {
+                ""
+            };
+            if game_board.is_gravity() {
+                let open_columns: Vec<usize> = (1..=size)
+                    .filter(|&column| game_board.drop_position(column).is_ok())
+                    .collect();
+                format!("Enter a column from the open columns: {open_columns:?}{piece_choice}")
+            } else {
+                format!(
+                    "Enter a number from the available positions: {:?}{piece_choice}",
+                    options
+                        .numbering
+                        .labels(&game_board.get_available_positions(), size)
+                )
+            }
+        };
+        let assists = if options.speedrun {
+            ""
+        } else {
+            "(h/H for a hint), (u/U to undo, r/R to redo), (a/A to let the computer finish), "
+        };
+        print!(
+            "\n{}, {assists}(z/Z for zen mode) or (q/Q to quit). ",
+            options.paint(choices, Style::new().bold())
+        );
+    }
 
     if io::stdout().flush().is_err() {
-        return Err(String::from("Failed to flush stdout"));
+        return Err(GameError::InvalidInput(String::from(
+            "Failed to flush stdout",
+        )));
     }
 
     // Read user input
-    let mut input = String::new();
-    if io::stdin().read_line(&mut input).is_err() {
-        return Err(String::from("Failed to read line"));
-    }
+    let Some(input) = input_session.read_line().map_err(GameError::InvalidInput)? else {
+        // Scripted input has run out, there will never be another move
+        println!("End of input. Exiting...");
+        return Err(GameError::Quit);
+    };
 
     let input = input.trim();
 
     if input.eq_ignore_ascii_case("q") {
         println!("You entered 'q' or 'Q'. Exiting...");
-        return Err(String::from("exit"));
+        return Err(GameError::Quit);
     }
 
-    // Attempt to parse the input as an usize
-    input.parse::<usize>().map_or_else(
-        |_| Err("Invalid input. Please enter a valid number or type 'q/Q' to quit.".to_string()),
-        |position| {
-            let piece = game_board.player_for_id(game_board.next_up).piece();
-            println!("\nYou played {piece} in position: {position}");
-            game_board.play_next_up_at_position(position)
+    if options.speedrun
+        && ["h", "u", "r", "a"]
+            .iter()
+            .any(|key| input.eq_ignore_ascii_case(key))
+    {
+        return Err(GameError::InvalidInput(
+            "Hints, undo and letting the computer finish are off during a speedrun".to_string(),
+        ));
+    }
+
+    if input.eq_ignore_ascii_case("h") {
+        game.take_hint()?;
+        println!("\n{}", game.board().hint_text(options.numbering));
+        if let Some(hints_left) = game.board().hints_left() {
+            println!("Hints left this game: {hints_left}");
+        }
+        return Ok(());
+    }
+
+    if input.eq_ignore_ascii_case("z") {
+        options.interface = options.interface.toggle_zen();
+        if let Err(error) = local_data::set_interface(options.interface) {
+            println!("Interface not saved: {error}");
+        }
+        if options.zen() {
+            println!("Zen mode. Type z to bring everything back.");
+        }
+        return Ok(());
+    }
+
+    if input.eq_ignore_ascii_case("u") {
+        let undone = game.undo_turn();
+        if undone.is_empty() {
+            println!("There is nothing to undo.");
+        }
+        print_moves("Undid", &undone, game.board(), options);
+        return Ok(());
+    }
+
+    if input.eq_ignore_ascii_case("r") {
+        let redone = game.redo_turn();
+        if redone.is_empty() {
+            println!("There is nothing to redo.");
+        }
+        print_moves("Redid", &redone, game.board(), options);
+        return Ok(());
+    }
+
+    if input.eq_ignore_ascii_case("a") {
+        println!("You entered 'a' or 'A'. The computer will finish the game for you...");
+        game.swap_player(game.board().next_up);
+        return Ok(());
+    }
+
+    // Attempt to parse the input as a position, optionally followed by the piece to place
+    parse_move_input(input).map_or_else(
+        || {
+            Err(GameError::InvalidInput(
+                "Please enter a valid number or type 'q/Q' to quit".to_string(),
+            ))
+        },
+        |move_input| {
+            let game_board = game.board();
+            let position_of = |label: usize| {
+                options
+                    .numbering
+                    .position(label, game_board.size(), game_board.layers())
+                    .ok_or(GameError::InvalidPosition(label))
+            };
+            // Under gravity the number is a column for the piece to drop down
+            let position = if game_board.is_gravity() {
+                game_board.drop_position(move_input.to)?
+            } else {
+                position_of(move_input.to)?
+            };
+            let from = move_input.from.map(position_of).transpose()?;
+
+            let piece = move_input
+                .piece
+                .unwrap_or_else(|| game_board.own_move(position).piece);
+            let chosen = match from {
+                Some(from) => Move::slide(from, position, piece),
+                None => Move::place(position, piece),
+            };
+            game.play(chosen)?;
+            if !options.zen() {
+                println!("\nYou {}", move_text(&chosen, game.board(), options));
+            }
+            Ok(())
         },
     )
 }
 
-fn display_spinner_with_message(message: &str) {
+// Labels as entered, before the numbering turns them into positions
+struct MoveInput {
+    from: Option<usize>,
+    to: usize,
+    piece: Option<Piece>,
+}
+
+// A position such as "5", with a piece for wild games such as "5x" or "5 O", or a Three Men's
+// Morris slide such as "4-5" or "4 5"
+fn parse_move_input(input: &str) -> Option<MoveInput> {
+    let (labels, piece) = match input.char_indices().last() {
+        Some((index, last)) if last.is_ascii_alphabetic() => (
+            &input[..index],
+            Some(Piece::from_name(&last.to_string()).ok()?),
+        ),
+        _ => (input, None),
+    };
+
+    let labels: Vec<usize> = labels
+        .split(|separator: char| separator == '-' || separator.is_whitespace())
+        .filter(|label| !label.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()?;
+
+    match *labels.as_slice() {
+        [to] => Some(MoveInput {
+            from: None,
+            to,
+            piece,
+        }),
+        [from, to] => Some(MoveInput {
+            from: Some(from),
+            to,
+            piece,
+        }),
+        _ => None,
+    }
+}
+
+// Such as "played X in position: 5", or "slid X from position 4 to 5"
+fn move_text(chosen: &Move, game_board: &GameBoard, options: &CliOptions) -> String {
+    let size = game_board.size();
+    let label = options.numbering.label(chosen.position, size);
+    let piece = options.piece_text(chosen.piece);
+
+    match chosen.from {
+        Some(from) => format!(
+            "slid {piece} from position {} to {label}",
+            options.numbering.label(from, size)
+        ),
+        None => format!("played {piece} in position: {label}"),
+    }
+}
+
+fn print_moves(action: &str, moves: &[MoveRecord], game_board: &GameBoard, options: &CliOptions) {
+    let size = game_board.size();
+
+    for record in moves {
+        let piece = options.piece_text(game_board.piece_played(record));
+        let label = options.numbering.label(record.position, size);
+
+        match record.from {
+            Some(from) => println!(
+                "{action} turn {}: {piece} from position {} to {label}",
+                record.turn,
+                options.numbering.label(from, size)
+            ),
+            None => println!("{action} turn {}: {piece} in position {label}", record.turn),
+        }
+    }
+}
+
+// Labels are padded to the widest so the columns line up
+fn label_width(game_board: &GameBoard, numbering: Numbering) -> usize {
+    game_board
+        .positions()
+        .map(|position| {
+            numbering
+                .label(position, game_board.size())
+                .to_string()
+                .len()
+        })
+        .max()
+        .unwrap_or(1)
+}
+
+fn reference_grid(game_board: &GameBoard, numbering: Numbering) -> String {
+    let size = game_board.size();
+    let width = label_width(game_board, numbering);
+
+    game_board.layout_cells(|position| {
+        let label = numbering.label(position, size);
+        format!("[{label:>width$}] ")
+    })
+}
+
+fn render_board(game_board: &GameBoard, options: &CliOptions) -> String {
+    let threats = if options.show_threats && !game_board.is_game_over() {
+        threats(game_board)
+    } else {
+        Vec::new()
+    };
+
+    let size = game_board.size();
+    let cell_numbers = options.shows_cell_numbers(game_board);
+    let width = if cell_numbers {
+        label_width(game_board, options.numbering)
+    } else {
+        1
+    };
+
+    let mut output = game_board.layout_cells(|position| {
+        // Widths are counted apart from the text, which may hold escape codes for its color
+        let (cell, cell_width) = match game_board.get_cell_at_position(position) {
+            Some(CellState::Occupied(piece)) => (options.piece_text(*piece), 1),
+            Some(CellState::Blocked) => ("#".to_string(), 1),
+            _ => {
+                let threatening_pieces: Vec<_> = threats
+                    .iter()
+                    .filter(|(threatened_position, _)| *threatened_position == position)
+                    .map(|(_, piece)| piece.name().to_lowercase())
+                    .collect();
+
+                let mark = match threatening_pieces.as_slice() {
+                    [] if cell_numbers => options.numbering.label(position, size).to_string(),
+                    [] => " ".to_string(),
+                    [piece] => piece.clone(),
+                    _ => "*".to_string(),
+                };
+                let mark_width = mark.len();
+                (mark, mark_width)
+            }
+        };
+
+        format!("[{}{cell}] ", " ".repeat(width - cell_width))
+    });
+
+    if !threats.is_empty() {
+        output.push_str("\n(x/o: that piece completes a line here next move, *: both do)");
+    }
+
+    output
+}
+
+fn ring_bell(options: &CliOptions) {
+    if options.bell {
+        print!("\x07");
+        if io::stdout().flush().is_err() {
+            println!("Failed to flush stdout");
+        }
+    }
+}
+
+// Shows the computer thinking while `think` runs, for no less than the think delay
+fn think_visibly<T>(options: &CliOptions, think: impl FnOnce() -> T) -> T {
+    let message = "The computer is thinking...";
+    let started = Instant::now();
+
+    if options.zen() || options.reduced_motion {
+        if !options.zen() {
+            println!("{message}");
+        }
+        let result = think();
+        thread::sleep(options.think_delay.saturating_sub(started.elapsed()));
+        return result;
+    }
+
+    let (stop, stopped) = mpsc::channel::<()>();
+
+    thread::scope(|scope| {
+        scope.spawn(move || display_spinner_with_message(message, &stopped));
+
+        let result = think();
+        thread::sleep(options.think_delay.saturating_sub(started.elapsed()));
+
+        // Closing the channel stops the spinner
+        drop(stop);
+        result
+    })
+}
+
+// Spins until told to stop, clearing the message on the way out
+fn display_spinner_with_message(message: &str, stopped: &mpsc::Receiver<()>) {
     let spinner_chars = ['|', '/', '-', '\\'];
 
     let display_character = |message: &str, character: char| {
@@ -108,30 +1153,37 @@ fn display_spinner_with_message(message: &str) {
         }
     };
 
-    let iterations = 0..32;
-    for i in iterations {
-        // Use modulus to cycle through the spinner characters
-        let spinner_value = spinner_chars[i % spinner_chars.len()];
-
+    for spinner_value in spinner_chars.into_iter().cycle() {
         display_character(message, spinner_value);
 
-        // Wait for 100ms before updating the spinner
-        thread::sleep(Duration::from_millis(100));
+        // Update the spinner every 100ms until the channel is closed
+        if stopped.recv_timeout(Duration::from_millis(100)) != Err(RecvTimeoutError::Timeout) {
+            break;
+        }
     }
 
     let clear_message = ' '.to_string().repeat(message.len());
     display_character(&clear_message, ' ');
 }
 
-impl GameBoard {
-    fn game_message(&self) -> String {
-        match self.game_state() {
-            GameState::Winner(player) => match player {
-                Player::Human(piece) => format!("\n✨{piece}✨ You won! 🥇"),
-                Player::Computer(piece) => format!("\n✨{piece}✨ The computer won! 🥇"),
-            },
-            GameState::Draw => "\nThis game results in a draw.".to_string(),
-            GameState::InProgress => String::new(),
-        }
+fn game_message(game: &Game, options: &CliOptions) -> String {
+    let Some(result) = game.result_text() else {
+        return "\nExiting the game".to_string();
+    };
+
+    match game.board().determine_winning_player() {
+        // Both place X in Notakto, so the winner has no piece of their own to show
+        Some(_) if game.board().is_notakto() => format!(
+            "\n{}",
+            options.paint(format!("{result} 🥇"), Style::new().green().bold())
+        ),
+        Some(winner) => format!(
+            "\n{}",
+            options.paint(
+                format!("✨{}✨ {result} 🥇", winner.piece()),
+                Style::new().green().bold()
+            )
+        ),
+        None => format!("\n{result}"),
     }
 }
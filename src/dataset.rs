@@ -0,0 +1,145 @@
+use crate::exit_code::ExitReason;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use tic_tac_toe::engine::{best_move, MoveScore};
+use tic_tac_toe::game_model::{CellState, GameBoard, Piece};
+
+#[derive(clap::Args)]
+pub struct DatasetArgs {
+    #[arg(
+        long,
+        value_name = "file",
+        help = "Write the CSV to a file instead of standard output"
+    )]
+    output: Option<String>,
+}
+
+// Every position that can come up in a game, with the perfect move and its value, as numeric CSV.
+// Cells and the side to move are 1 for X, -1 for O and 0 for empty. The value is from the point of
+// view of the side to move: 1 win, 0 draw, -1 loss.
+pub fn export_dataset_main(args: &DatasetArgs) -> ExitReason {
+    let output_path = args.output.as_ref();
+
+    let result = match output_path {
+        Some(path) => File::create(path)
+            .map_err(|error| format!("Could not create '{path}': {error}"))
+            .and_then(|file| write_dataset(BufWriter::new(file))),
+        None => write_dataset(io::stdout().lock()),
+    };
+
+    match result {
+        Ok(rows) => {
+            if output_path.is_some() {
+                println!("Exported {rows} positions");
+            }
+            ExitReason::Completed
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            ExitReason::InvalidUsage
+        }
+    }
+}
+
+fn write_dataset(mut output: impl Write) -> Result<usize, String> {
+    let positions = enumerate_positions();
+
+    // Only classic boards are enumerated
+    let header: Vec<String> = (1..=GameBoard::CLASSIC_SIZE * GameBoard::CLASSIC_SIZE)
+        .map(|position| format!("cell_{position}"))
+        .chain(["to_move", "best_move", "value"].map(String::from))
+        .collect();
+    writeln!(output, "{}", header.join(",")).map_err(|error| error.to_string())?;
+
+    for game_board in &positions {
+        let Some((position, score)) = best_move(game_board) else {
+            continue;
+        };
+
+        writeln!(output, "{}", csv_row(game_board, position, score))
+            .map_err(|error| error.to_string())?;
+    }
+
+    output.flush().map_err(|error| error.to_string())?;
+
+    Ok(positions.len())
+}
+
+// Every unfinished position reachable from the empty board, X moving first
+fn enumerate_positions() -> Vec<GameBoard> {
+    let mut seen = HashSet::new();
+    let mut positions = Vec::new();
+    let mut pending = vec![GameBoard::new_computer_vs_computer()];
+
+    while let Some(game_board) = pending.pop() {
+        if game_board.is_game_over() || !seen.insert(game_board.to_string()) {
+            continue;
+        }
+
+        for position in game_board.get_available_positions() {
+            let mut next_board = game_board.clone();
+            if next_board.play_next_up_at_position(position).is_ok() {
+                pending.push(next_board);
+            }
+        }
+
+        positions.push(game_board);
+    }
+
+    positions
+}
+
+fn csv_row(game_board: &GameBoard, position: usize, score: MoveScore) -> String {
+    let mut fields: Vec<String> = game_board
+        .positions()
+        .map(|cell| match game_board.get_cell_at_position(cell) {
+            Some(CellState::Occupied(piece)) => piece_value(*piece),
+            Some(CellState::Empty | CellState::Blocked) | None => "0",
+        })
+        .map(String::from)
+        .collect();
+
+    let value = match score {
+        MoveScore::Win => "1",
+        MoveScore::Draw => "0",
+        MoveScore::Loss => "-1",
+    };
+
+    fields.push(piece_value(*game_board.player_for_id(game_board.next_up).piece()).to_string());
+    fields.push(position.to_string());
+    fields.push(value.to_string());
+
+    fields.join(",")
+}
+
+const fn piece_value(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "1",
+        Piece::O => "-1",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_unfinished_position_is_enumerated_once() {
+        // The well known count of distinct unfinished tic-tac-toe positions
+        assert_eq!(enumerate_positions().len(), 4520);
+    }
+
+    #[test]
+    fn test_csv_row() {
+        let mut game_board = GameBoard::new_computer_vs_computer();
+        for position in [1, 4, 2, 5] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+
+        assert_eq!(
+            csv_row(&game_board, 3, MoveScore::Win),
+            "1,1,0,-1,-1,0,0,0,0,1,3,1"
+        );
+    }
+}
@@ -0,0 +1,1127 @@
+use crate::game_model::{threats, CanonicalForm, GameBoard, Move, Piece};
+use crate::numbering::Numbering;
+use crate::random_source::RandomSource;
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
+use std::fmt;
+
+// Takes a winning move if there is one, otherwise blocks the opponent's, otherwise prefers the
+// center, then a random corner, then a random edge. Under misère rules completing a line loses,
+// so it keeps clear of its own lines instead.
+pub fn win_or_block_move(game_board: &GameBoard, random: &mut dyn RandomSource) -> Option<usize> {
+    let mover = game_board.next_piece();
+    let threats = threats(game_board);
+
+    if game_board.is_misere() {
+        let losing_moves: Vec<usize> = threats
+            .iter()
+            .filter(|&&(_, piece)| piece == mover)
+            .map(|&(position, _)| position)
+            .collect();
+        let moves = ordered_moves(game_board);
+        let safe_moves: Vec<usize> = moves
+            .iter()
+            .copied()
+            .filter(|position| !losing_moves.contains(position))
+            .collect();
+
+        // Every move loses when there is no safe one
+        return if safe_moves.is_empty() {
+            preferred_move(game_board, moves, random)
+        } else {
+            preferred_move(game_board, safe_moves, random)
+        };
+    }
+
+    // In wild games a line of either piece wins for whoever completes it
+    let winning_move = threats
+        .iter()
+        .find(|&&(_, piece)| piece == mover || game_board.is_wild())
+        .map(|&(position, _)| position);
+
+    let blocking_move = threats
+        .iter()
+        .find(|&&(_, piece)| piece != mover)
+        .map(|&(position, _)| position);
+
+    winning_move
+        .or(blocking_move)
+        .or_else(|| preferred_move(game_board, ordered_moves(game_board), random))
+}
+
+// The whole move for a strategy that only picks a position: the piece to place there in a wild
+// game, or the piece to slide there in Three Men's Morris. One that wins on the spot, otherwise one
+// that leaves the opponent no immediate win, otherwise the first. Any move will do when none can
+// get to the position.
+pub fn complete_move(game_board: &GameBoard, position: usize) -> Option<Move> {
+    let mover = *game_board.player_for_id(game_board.next_up).piece();
+    let legal_moves = game_board.legal_moves();
+    let reaching_moves: Vec<Move> = legal_moves
+        .iter()
+        .copied()
+        .filter(|chosen| chosen.position == position)
+        .collect();
+    let candidates = if reaching_moves.is_empty() {
+        legal_moves
+    } else {
+        reaching_moves
+    };
+    let outcomes: Vec<(Move, Option<GameBoard>)> = candidates
+        .into_iter()
+        .map(|chosen| {
+            let mut next_board = game_board.clone();
+            (
+                chosen,
+                next_board.play_next_up(chosen).ok().map(|()| next_board),
+            )
+        })
+        .collect();
+
+    let winning_move = outcomes.iter().find(|(_, next_board)| {
+        next_board.as_ref().is_some_and(|next_board| {
+            next_board
+                .determine_winning_player()
+                .is_some_and(|winner| *winner.piece() == mover)
+        })
+    });
+    let safe_move = || {
+        outcomes.iter().find(|(_, next_board)| {
+            next_board.as_ref().is_some_and(|next_board| {
+                !next_board.legal_moves().into_iter().any(|reply| {
+                    let mut reply_board = next_board.clone();
+                    reply_board.play_next_up(reply).is_ok()
+                        && reply_board
+                            .determine_winning_player()
+                            .is_some_and(|winner| *winner.piece() != mover)
+                })
+            })
+        })
+    };
+
+    winning_move
+        .or_else(safe_move)
+        .or_else(|| outcomes.first())
+        .map(|&(chosen, _)| chosen)
+}
+
+// A random pick among the highest priority of the moves, which are in priority order
+fn preferred_move(
+    game_board: &GameBoard,
+    moves: Vec<usize>,
+    random: &mut dyn RandomSource,
+) -> Option<usize> {
+    let best_priority = move_priority(game_board, *moves.first()?);
+    let best_moves: Vec<usize> = moves
+        .into_iter()
+        .filter(|&position| move_priority(game_board, position) == best_priority)
+        .collect();
+
+    best_moves.get(random.next_index(best_moves.len())).copied()
+}
+
+// How much the weighted heuristic values each feature of a move. Kept as plain numbers so they
+// can be tuned.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct HeuristicWeights {
+    pub win: f64,
+    pub block: f64,
+    pub fork: f64,
+    pub center: f64,
+    pub corner: f64,
+    pub edge: f64,
+    // Chance of ignoring all of the above and playing a random move
+    pub blunder_rate: f64,
+}
+
+impl HeuristicWeights {
+    pub const NAMES: [&'static str; 7] = [
+        "win",
+        "block",
+        "fork",
+        "center",
+        "corner",
+        "edge",
+        "blunder_rate",
+    ];
+
+    pub const fn values(&self) -> [f64; 7] {
+        [
+            self.win,
+            self.block,
+            self.fork,
+            self.center,
+            self.corner,
+            self.edge,
+            self.blunder_rate,
+        ]
+    }
+
+    pub const fn from_values(values: [f64; 7]) -> Self {
+        let [win, block, fork, center, corner, edge, blunder_rate] = values;
+
+        Self {
+            win,
+            block,
+            fork,
+            center,
+            corner,
+            edge,
+            blunder_rate,
+        }
+    }
+}
+
+// The move with the highest weighted score, ties broken at random
+pub fn weighted_move(
+    game_board: &GameBoard,
+    weights: &HeuristicWeights,
+    random: &mut dyn RandomSource,
+) -> Option<usize> {
+    if random.next_fraction() < weights.blunder_rate {
+        return game_board.random_available_position_from(random);
+    }
+
+    let mover = *game_board.player_for_id(game_board.next_up).piece();
+    let threats_before = threats(game_board);
+
+    // Includes the slides of Three Men's Morris, scored by where they end up
+    let scored_moves: Vec<(usize, f64)> = game_board
+        .legal_moves()
+        .into_iter()
+        .filter(|chosen| chosen.piece == game_board.next_piece())
+        .filter_map(|chosen| {
+            let position = chosen.position;
+            let mut next_board = game_board.clone();
+            next_board.play_next_up(chosen).ok()?;
+
+            let mut threatened_positions: Vec<usize> = threats(&next_board)
+                .into_iter()
+                .filter(|&(_, piece)| piece == mover)
+                .map(|(position, _)| position)
+                .collect();
+            threatened_positions.dedup();
+
+            let mut score = match move_priority(game_board, position) {
+                0 => weights.center,
+                1 => weights.corner,
+                _ => weights.edge,
+            };
+            match next_board.determine_winning_player() {
+                Some(winner) if *winner.piece() == mover => score += weights.win,
+                // Only under misère rules, where completing a line loses
+                Some(_) => score -= weights.win,
+                None => (),
+            }
+            // Blocks and forks only help when lines are worth completing
+            if !game_board.is_misere() {
+                if threats_before.contains(&(position, GameBoard::other_piece(mover))) {
+                    score += weights.block;
+                }
+                if threatened_positions.len() >= 2 {
+                    score += weights.fork;
+                }
+            }
+
+            Some((position, score))
+        })
+        .collect();
+
+    let best_score = scored_moves
+        .iter()
+        .map(|&(_, score)| score)
+        .max_by(f64::total_cmp)?;
+    let best_moves: Vec<usize> = scored_moves
+        .into_iter()
+        .filter(|&(_, score)| score >= best_score)
+        .map(|(position, _)| position)
+        .collect();
+
+    best_moves.get(random.next_index(best_moves.len())).copied()
+}
+
+// Larger than any score a position can have
+const SCORE_BOUND: i32 = 100;
+
+// Searching a board larger than the classic one, or several of them, to the end takes far too
+// long, so it is only searched this many moves ahead. A draw there means neither side wins
+// within those moves.
+const LARGE_BOARD_DEPTH: usize = 4;
+// Cubes have many more lines to check, so they are searched less far ahead still
+const CUBE_DEPTH: usize = 3;
+// Three Men's Morris pieces can slide back and forth until the turn limit, too long to search
+const MORRIS_DEPTH: usize = 8;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EngineConfig {
+    // How many moves ahead to look, counting the move being chosen. None searches to the end.
+    pub max_depth: Option<usize>,
+}
+
+impl EngineConfig {
+    pub const FULL_DEPTH: Self = Self { max_depth: None };
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self::FULL_DEPTH
+    }
+}
+
+// Perfect play within the configured depth: a random pick among the moves with the best score
+pub fn minimax_move(
+    game_board: &GameBoard,
+    config: EngineConfig,
+    random: &mut dyn RandomSource,
+) -> Option<usize> {
+    minimax_full_move(game_board, config, random).map(|chosen| chosen.position)
+}
+
+// The same with the piece to place, which wild games leave to the mover, and where it slides from
+// in Three Men's Morris
+pub fn minimax_full_move(
+    game_board: &GameBoard,
+    config: EngineConfig,
+    random: &mut dyn RandomSource,
+) -> Option<Move> {
+    if game_board.is_game_over() {
+        return None;
+    }
+
+    let remaining_depth = config.max_depth.map(|depth| depth.saturating_sub(1));
+    let scored_moves = scored_moves(game_board, remaining_depth);
+
+    let best_score = scored_moves.iter().map(|&(_, score)| score).max()?;
+    let best_moves: Vec<Move> = scored_moves
+        .into_iter()
+        .filter(|&(_, score)| score == best_score)
+        .map(|(chosen, _)| chosen)
+        .collect();
+
+    best_moves.get(random.next_index(best_moves.len())).copied()
+}
+
+// How a stored score relates to the position's real one. Searches cut short by alpha-beta only
+// learn a bound.
+#[derive(Clone, Copy)]
+enum Bound {
+    Exact,
+    AtLeast,
+    AtMost,
+}
+
+// A position by its canonical form, the piece of the player about to move, the moves played so far
+// and how much further the search looks. Three Men's Morris games end at a turn limit, so the same
+// cells can be worth less late in the game.
+type PositionKey = (CanonicalForm, Piece, usize, Option<usize>);
+
+// Scores of the positions already searched, for the rest of one search. Rotated and mirrored
+// positions share an entry, which is most of them early in a game.
+type TranspositionTable = HashMap<PositionKey, (i32, Bound)>;
+
+#[derive(Default)]
+struct Search {
+    table: TranspositionTable,
+    // Positions scored, counting those found in the table
+    nodes: usize,
+}
+
+// How many positions a minimax search scores to choose a move here, for measuring how fast the
+// engine searches
+pub fn search_nodes(game_board: &GameBoard, config: EngineConfig) -> usize {
+    let mut search = Search::default();
+    let remaining_depth = config.max_depth.map(|depth| depth.saturating_sub(1));
+    search_moves(game_board, remaining_depth, &mut search);
+
+    search.nodes
+}
+
+// Every legal move with its negamax score for the player making it, best candidates first
+fn scored_moves(game_board: &GameBoard, remaining_depth: Option<usize>) -> Vec<(Move, i32)> {
+    search_moves(game_board, remaining_depth, &mut Search::default())
+}
+
+fn search_moves(
+    game_board: &GameBoard,
+    remaining_depth: Option<usize>,
+    search: &mut Search,
+) -> Vec<(Move, i32)> {
+    let remaining_depth =
+        remaining_depth.or_else(|| game_board.search_depth().map(|depth| depth - 1));
+
+    candidate_moves(game_board)
+        .into_iter()
+        .filter_map(|chosen| {
+            let mut next_board = game_board.clone();
+            next_board.play_next_up(chosen).ok()?;
+
+            // A full window for each move, so that equally good moves get equal scores
+            Some((
+                chosen,
+                -negamax(
+                    &next_board,
+                    remaining_depth,
+                    -SCORE_BOUND,
+                    SCORE_BOUND,
+                    search,
+                ),
+            ))
+        })
+        .collect()
+}
+
+// Score of the position for the player about to move. Wins score higher the sooner they happen,
+// and positions past the depth limit count as a draw.
+// Scores outside alpha..beta are only bounds, which is all the caller needs to discard them.
+fn negamax(
+    game_board: &GameBoard,
+    remaining_depth: Option<usize>,
+    mut alpha: i32,
+    mut beta: i32,
+    search: &mut Search,
+) -> i32 {
+    search.nodes += 1;
+
+    if let Some(winner) = game_board.determine_winning_player() {
+        // The player who just moved completed a line, which under misère rules loses
+        let empty_cells = game_board.empty_positions().len();
+        let score = 1 + i32::try_from(empty_cells).unwrap_or(0);
+        let mover = game_board.player_for_id(game_board.next_up);
+        return if winner.piece() == mover.piece() {
+            score
+        } else {
+            -score
+        };
+    }
+
+    let moves = candidate_moves(game_board);
+    if moves.is_empty() || remaining_depth == Some(0) {
+        return 0;
+    }
+
+    let key = (
+        game_board.canonical_form(),
+        *game_board.player_for_id(game_board.next_up).piece(),
+        game_board.history().len(),
+        remaining_depth,
+    );
+    if let Some(&(score, bound)) = search.table.get(&key) {
+        match bound {
+            Bound::Exact => return score,
+            Bound::AtLeast => alpha = alpha.max(score),
+            Bound::AtMost => beta = beta.min(score),
+        }
+        if alpha >= beta {
+            return score;
+        }
+    }
+    let window = (alpha, beta);
+
+    let next_depth = remaining_depth.map(|depth| depth - 1);
+    let mut best_score = i32::MIN;
+
+    for chosen in moves {
+        let mut next_board = game_board.clone();
+        if next_board.play_next_up(chosen).is_err() {
+            continue;
+        }
+
+        best_score = best_score.max(-negamax(&next_board, next_depth, -beta, -alpha, search));
+        alpha = alpha.max(best_score);
+
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    let bound = if best_score <= window.0 {
+        Bound::AtMost
+    } else if best_score >= window.1 {
+        Bound::AtLeast
+    } else {
+        Bound::Exact
+    };
+    search.table.insert(key, (best_score, bound));
+
+    best_score
+}
+
+// The outcome a move leads to for the player making it, with perfect play from both sides after it
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MoveScore {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl fmt::Display for MoveScore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Win => write!(f, "win"),
+            Self::Draw => write!(f, "draw"),
+            Self::Loss => write!(f, "loss"),
+        }
+    }
+}
+
+impl GameBoard {
+    // How many moves ahead the engine looks unless told otherwise, None for to the end of the game.
+    // Only classic games are small enough to search to the end.
+    pub fn search_depth(&self) -> Option<usize> {
+        if self.is_cube() {
+            Some(CUBE_DEPTH)
+        } else if self.is_morris() {
+            Some(MORRIS_DEPTH)
+        } else if self.size() > Self::CLASSIC_SIZE || self.is_wild() || self.layers() > 1 {
+            Some(LARGE_BOARD_DEPTH)
+        } else {
+            None
+        }
+    }
+
+    // One move for each empty cell. In wild games that is the better of the two pieces, and in
+    // Three Men's Morris the best slide there.
+    pub fn evaluate_moves(&self) -> Vec<(Move, MoveScore)> {
+        if self.is_game_over() {
+            return Vec::new();
+        }
+
+        let mut scores = scored_moves(self, None);
+        scores.sort_by_key(|&(chosen, score)| (chosen.position, Reverse(score)));
+        scores.dedup_by_key(|(chosen, _)| chosen.position);
+
+        scores
+            .into_iter()
+            .map(|(chosen, score)| (chosen, move_score(score)))
+            .collect()
+    }
+
+    // Every move's score on one line, each labelled the way the player enters it
+    pub fn hint_text(&self, numbering: Numbering) -> String {
+        let scores: Vec<String> = self
+            .evaluate_moves()
+            .into_iter()
+            .map(|(chosen, score)| {
+                // Under gravity moves are entered as columns
+                let label = match self.coordinate_at_position(chosen.position) {
+                    Some(coordinate) if self.is_gravity() => {
+                        format!("column {}", coordinate.col() + 1)
+                    }
+                    _ => numbering.label(chosen.position, self.size()).to_string(),
+                };
+                // Wild games also need the piece to place, and slides where the piece comes from
+                if self.is_wild() {
+                    format!("{label} {}: {score}", chosen.piece)
+                } else if let Some(from) = chosen.from {
+                    format!("{}-{label}: {score}", numbering.label(from, self.size()))
+                } else {
+                    format!("{label}: {score}")
+                }
+            })
+            .collect();
+
+        let heading = if self.search_depth().is_none() {
+            "With perfect play from here"
+        } else {
+            "Looking a few moves ahead"
+        };
+
+        format!("{heading}: {}", scores.join(", "))
+    }
+}
+
+fn move_score(score: i32) -> MoveScore {
+    match score.cmp(&0) {
+        Ordering::Greater => MoveScore::Win,
+        Ordering::Equal => MoveScore::Draw,
+        Ordering::Less => MoveScore::Loss,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SolvedResult {
+    Win(Piece),
+    Draw,
+}
+
+impl fmt::Display for SolvedResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Win(piece) => write!(f, "{piece} wins"),
+            Self::Draw => write!(f, "draw"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Solution {
+    pub result: SolvedResult,
+    // The moves from here to the end of the game with perfect play from both sides
+    pub principal_variation: Vec<usize>,
+}
+
+// The perfect move for the player about to move and where it leads. Quick wins and slow losses
+// are preferred, and the first of equally good moves is taken so the choice is deterministic.
+pub fn best_move(game_board: &GameBoard) -> Option<(usize, MoveScore)> {
+    best_scored_move(game_board).map(|(chosen, score)| (chosen.position, move_score(score)))
+}
+
+fn best_scored_move(game_board: &GameBoard) -> Option<(Move, i32)> {
+    scored_moves(game_board, None)
+        .into_iter()
+        .rev()
+        .max_by_key(|&(_, score)| score)
+}
+
+pub fn solve(game_board: &GameBoard) -> Solution {
+    let mut board = game_board.clone();
+    let mut principal_variation = Vec::new();
+
+    while !board.is_game_over() {
+        let Some((chosen, _)) = best_scored_move(&board) else {
+            break;
+        };
+
+        if board.play_next_up(chosen).is_err() {
+            break;
+        }
+        principal_variation.push(chosen.position);
+    }
+
+    let result = board
+        .determine_winning_player()
+        .map_or(SolvedResult::Draw, |player| {
+            SolvedResult::Win(*player.piece())
+        });
+
+    Solution {
+        result,
+        principal_variation,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MctsConfig {
+    // Random playouts per move. More play stronger and slower.
+    pub simulations: usize,
+}
+
+impl MctsConfig {
+    pub const DEFAULT: Self = Self { simulations: 2000 };
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+// Balances trying promising moves against exploring rarely visited ones
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+struct SearchNode {
+    board: GameBoard,
+    chosen: Option<Move>,
+    // The piece that moved into this node, None at the root
+    mover: Option<Piece>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Move>,
+    visits: u32,
+    // From the mover's point of view: a win counts 1 and a draw a half
+    reward: f64,
+}
+
+impl SearchNode {
+    fn new(
+        board: GameBoard,
+        chosen: Option<Move>,
+        mover: Option<Piece>,
+        parent: Option<usize>,
+    ) -> Self {
+        let untried = if board.is_game_over() {
+            Vec::new()
+        } else {
+            board.legal_moves()
+        };
+
+        Self {
+            board,
+            chosen,
+            mover,
+            parent,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+
+    fn upper_confidence_bound(&self, parent_visits: u32) -> f64 {
+        let visits = f64::from(self.visits.max(1));
+
+        self.reward / visits + EXPLORATION * (f64::from(parent_visits).ln() / visits).sqrt()
+    }
+}
+
+// Monte Carlo tree search: plays out random games and picks the most explored move
+pub fn mcts_move(
+    game_board: &GameBoard,
+    config: MctsConfig,
+    random: &mut dyn RandomSource,
+) -> Option<usize> {
+    mcts_full_move(game_board, config, random).map(|chosen| chosen.position)
+}
+
+// The same with the piece to place in wild games and where it slides from in Three Men's Morris
+pub fn mcts_full_move(
+    game_board: &GameBoard,
+    config: MctsConfig,
+    random: &mut dyn RandomSource,
+) -> Option<Move> {
+    if game_board.is_game_over() {
+        return None;
+    }
+
+    let mut nodes = vec![SearchNode::new(game_board.clone(), None, None, None)];
+
+    for _ in 0..config.simulations {
+        // Selection: follow the most promising fully expanded nodes
+        let mut current = 0;
+        while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+            let parent_visits = nodes[current].visits;
+            current = nodes[current]
+                .children
+                .iter()
+                .copied()
+                .max_by(|&first, &second| {
+                    nodes[first]
+                        .upper_confidence_bound(parent_visits)
+                        .total_cmp(&nodes[second].upper_confidence_bound(parent_visits))
+                })
+                .unwrap_or(current);
+        }
+
+        // Expansion: add one untried move as a new node
+        if !nodes[current].untried.is_empty() {
+            let untried = &mut nodes[current].untried;
+            let chosen = untried.swap_remove(random.next_index(untried.len()));
+
+            let mut board = nodes[current].board.clone();
+            let mover = *board.player_for_id(board.next_up).piece();
+            if board.play_next_up(chosen).is_ok() {
+                nodes.push(SearchNode::new(
+                    board,
+                    Some(chosen),
+                    Some(mover),
+                    Some(current),
+                ));
+                let child = nodes.len() - 1;
+                nodes[current].children.push(child);
+                current = child;
+            }
+        }
+
+        // Simulation: finish the game with random moves
+        let mut board = nodes[current].board.clone();
+        while !board.is_game_over() {
+            let moves = board.legal_moves();
+            let Some(&chosen) = moves.get(random.next_index(moves.len())) else {
+                break;
+            };
+            if board.play_next_up(chosen).is_err() {
+                break;
+            }
+        }
+        let winner = board
+            .determine_winning_player()
+            .map(|player| *player.piece());
+
+        // Backpropagation: credit every node on the path from its mover's point of view
+        let mut node = Some(current);
+        while let Some(index) = node {
+            nodes[index].visits += 1;
+            nodes[index].reward += match (winner, nodes[index].mover) {
+                (None, _) => 0.5,
+                (Some(winner), Some(mover)) if winner == mover => 1.0,
+                _ => 0.0,
+            };
+            node = nodes[index].parent;
+        }
+    }
+
+    nodes[0]
+        .children
+        .iter()
+        .max_by_key(|&&child| nodes[child].visits)
+        .and_then(|&child| nodes[child].chosen)
+}
+
+// The legal moves in the order of their cells, the mover's own piece first in wild games
+fn candidate_moves(game_board: &GameBoard) -> Vec<Move> {
+    let mover = *game_board.player_for_id(game_board.next_up).piece();
+    let mut moves = game_board.legal_moves();
+    moves.sort_by_key(|chosen| {
+        (
+            move_priority(game_board, chosen.position),
+            chosen.position,
+            chosen.piece != mover,
+        )
+    });
+    moves
+}
+
+// Center first, then corners, then edges. The strongest moves tend to come first, which lets
+// alpha-beta cut off the rest sooner.
+fn ordered_moves(game_board: &GameBoard) -> Vec<usize> {
+    let mut positions = game_board.get_available_positions();
+    positions.sort_by_key(|&position| move_priority(game_board, position));
+    positions
+}
+
+// Boards with an even size have four center cells. On a cube the center and corners are those of
+// the whole cube.
+fn move_priority(game_board: &GameBoard, position: usize) -> usize {
+    let Some(coordinate) = game_board.coordinate_at_position(position) else {
+        return 2;
+    };
+    let last = game_board.size() - 1;
+    let is_center = |index: &usize| *index == last / 2 || *index == last.div_ceil(2);
+    let is_edge = |index: &usize| *index == 0 || *index == last;
+
+    let mut indices = vec![coordinate.row(), coordinate.col()];
+    if game_board.is_cube() {
+        indices.push(coordinate.layer());
+    }
+
+    if indices.iter().all(is_center) {
+        0
+    } else if indices.iter().all(is_edge) {
+        1
+    } else {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_model::Player;
+    use crate::random_source::{ReplayedRandom, SeededRandom, ThreadRandom};
+
+    #[test]
+    fn test_win_or_block_prefers_winning() {
+        // X: 1, 2 and O: 4, 5, X to move wins at 3 rather than blocking at 6
+        let game_board = board_after(&[1, 4, 2, 5]);
+
+        assert_eq!(win_or_block_move(&game_board, &mut ThreadRandom), Some(3));
+        assert_eq!(
+            minimax_move(&game_board, EngineConfig::default(), &mut ThreadRandom),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_win_or_block_blocks() {
+        // X: 1, 2 and O: 5, O to move must block at 3
+        let game_board = board_after(&[1, 5, 2]);
+
+        assert_eq!(win_or_block_move(&game_board, &mut ThreadRandom), Some(3));
+        assert_eq!(
+            minimax_move(&game_board, EngineConfig::default(), &mut ThreadRandom),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_misere_play_keeps_clear_of_its_own_lines() {
+        // X: 1, 2 and O: 4, 5, X to move must not complete the top row at 3
+        let mut game_board = board_after(&[]).with_misere(true);
+        for position in [1, 4, 2, 5] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+
+        for _ in 0..10 {
+            assert_ne!(win_or_block_move(&game_board, &mut ThreadRandom), Some(3));
+            assert_ne!(
+                minimax_move(&game_board, EngineConfig::default(), &mut ThreadRandom),
+                Some(3)
+            );
+        }
+    }
+
+    #[test]
+    fn test_wild_play_completes_a_line_of_either_piece() {
+        // O at 1 and 2, placed by X then O, so X can win with an O at 3
+        let mut game_board = board_after(&[]).with_wild(true);
+        for position in [1, 2] {
+            assert!(game_board
+                .play_next_up(Move::place(position, Piece::O))
+                .is_ok());
+        }
+
+        let winning_move = Move::place(3, Piece::O);
+        assert_eq!(complete_move(&game_board, 3), Some(winning_move));
+        assert_eq!(
+            minimax_full_move(&game_board, EngineConfig::default(), &mut ThreadRandom),
+            Some(winning_move)
+        );
+    }
+
+    #[test]
+    fn test_morris_play_slides_a_piece_into_line() {
+        // X: 1, 2, 6 and O: 4, 5, 9, X wins by sliding 6 up to 3
+        let mut game_board = board_after(&[]).with_morris(true);
+        for position in [1, 5, 2, 9, 6, 4] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+
+        let winning_slide = Move::slide(6, 3, Piece::X);
+        assert_eq!(complete_move(&game_board, 3), Some(winning_slide));
+        assert_eq!(
+            minimax_full_move(&game_board, EngineConfig::default(), &mut ThreadRandom),
+            Some(winning_slide)
+        );
+    }
+
+    #[test]
+    fn test_gravity_play_drops_into_the_winning_column() {
+        // X along the bottom row of a 4x4 board but for its last cell, O on top of them
+        let mut game_board = board_after(&[]).with_size(4).with_gravity(true);
+        for position in [13, 9, 14, 10, 15, 11] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+
+        assert_eq!(game_board.get_available_positions(), vec![5, 6, 7, 16]);
+        assert_eq!(
+            minimax_move(&game_board, EngineConfig::default(), &mut ThreadRandom),
+            Some(16)
+        );
+    }
+
+    #[test]
+    fn test_cube_play_finds_lines_through_the_layers() {
+        // X: 5, 14 down the middle and O: 1, 2, X to move completes the pillar at 23
+        let mut game_board = board_after(&[]).with_cube(true);
+        for position in [5, 1, 14, 2] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+
+        assert_eq!(win_or_block_move(&game_board, &mut ThreadRandom), Some(23));
+        assert_eq!(
+            minimax_move(&game_board, EngineConfig::default(), &mut ThreadRandom),
+            Some(23)
+        );
+        assert_eq!(ordered_moves(&board_after(&[]).with_cube(true))[0], 14);
+    }
+
+    #[test]
+    fn test_win_or_block_prefers_center_then_corners() {
+        assert_eq!(
+            win_or_block_move(&board_after(&[]), &mut ThreadRandom),
+            Some(5)
+        );
+
+        // The corners are drawn from in order, so the third draw picks the third corner
+        for (draw, corner) in [(0, 1), (1, 3), (2, 7), (3, 9)] {
+            assert_eq!(
+                win_or_block_move(&board_after(&[5]), &mut ReplayedRandom::new(vec![draw])),
+                Some(corner)
+            );
+        }
+    }
+
+    #[test]
+    fn test_minimax_picks_among_equally_good_moves_with_the_random_source() {
+        // Every opening move draws with perfect play. They are searched center first, then the
+        // corners, then the edges, and drawn from in that order.
+        let game_board = board_after(&[]);
+
+        for (draw, position) in [5, 1, 3, 7, 9, 2, 4, 6, 8].into_iter().enumerate() {
+            assert_eq!(
+                minimax_move(
+                    &game_board,
+                    EngineConfig::FULL_DEPTH,
+                    &mut ReplayedRandom::new(vec![draw])
+                ),
+                Some(position)
+            );
+        }
+    }
+
+    #[test]
+    fn test_minimax_answers_corner_opening_with_center() {
+        let game_board = board_after(&[1]);
+
+        assert_eq!(
+            minimax_move(&game_board, EngineConfig::default(), &mut ThreadRandom),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_minimax_against_itself_is_a_draw() {
+        let mut game_board = board_after(&[]);
+
+        while let Some(position) =
+            minimax_move(&game_board, EngineConfig::default(), &mut ThreadRandom)
+        {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+
+        assert!(game_board.determine_winning_player().is_none());
+        assert!(game_board.is_board_full());
+    }
+
+    #[test]
+    fn test_larger_boards_are_searched_a_few_moves_ahead() {
+        let mut game_board = board_after(&[]).with_size(4);
+        assert_eq!(&ordered_moves(&game_board)[..4], [6, 7, 10, 11]);
+
+        // X: 1, 2, 3, 16 and O: 5, 6, 7, O to move completes the second row at 8
+        for position in [1, 5, 2, 6, 3, 7] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+        assert!(game_board.play_next_up_at_position(16).is_ok());
+
+        assert_eq!(
+            minimax_move(&game_board, EngineConfig::FULL_DEPTH, &mut ThreadRandom),
+            Some(8)
+        );
+    }
+
+    #[test]
+    fn test_depth_limited_search_still_takes_a_win() {
+        let game_board = board_after(&[1, 4, 2, 5]);
+        let config = EngineConfig { max_depth: Some(1) };
+
+        assert_eq!(
+            minimax_move(&game_board, config, &mut ThreadRandom),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_moves_are_ordered_center_corners_edges() {
+        let game_board = board_after(&[]);
+
+        assert_eq!(ordered_moves(&game_board), vec![5, 1, 3, 7, 9, 2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn test_evaluate_moves() {
+        assert!(board_after(&[])
+            .evaluate_moves()
+            .iter()
+            .all(|&(_, score)| score == MoveScore::Draw));
+
+        // X: 1, 2 and O: 4, 5 with X to move, 3 wins, 6 blocks and anything else loses to O at 6
+        let scores: Vec<_> = board_after(&[1, 4, 2, 5])
+            .evaluate_moves()
+            .into_iter()
+            .map(|(chosen, score)| (chosen.position, score))
+            .collect();
+        assert_eq!(
+            scores,
+            vec![
+                (3, MoveScore::Win),
+                (6, MoveScore::Draw),
+                (7, MoveScore::Loss),
+                (8, MoveScore::Loss),
+                (9, MoveScore::Loss),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hint_text_labels_moves_as_they_are_entered() {
+        assert_eq!(
+            board_after(&[1, 4, 2, 5]).hint_text(Numbering::Standard),
+            "With perfect play from here: 3: win, 6: draw, 7: loss, 8: loss, 9: loss"
+        );
+        assert!(board_after(&[1, 4, 2, 5])
+            .hint_text(Numbering::Numpad)
+            .contains("9: win"));
+
+        let gravity = GameBoard::new_with_first_up(Player::Human(Piece::X)).with_gravity(true);
+        let hint = gravity.hint_text(Numbering::Standard);
+        for column in 1..=3 {
+            assert!(hint.contains(&format!("column {column}: ")), "{hint}");
+        }
+    }
+
+    #[test]
+    fn test_mirrored_positions_get_mirrored_scores() {
+        let scores_after = |moves: &[usize]| -> Vec<(usize, MoveScore)> {
+            board_after(moves)
+                .evaluate_moves()
+                .into_iter()
+                .map(|(chosen, score)| (chosen.position, score))
+                .collect()
+        };
+
+        // Turned half way round, position p becomes 10 - p
+        let mut turned: Vec<_> = scores_after(&[9, 5])
+            .into_iter()
+            .map(|(position, score)| (10 - position, score))
+            .collect();
+        turned.sort_unstable_by_key(|&(position, _)| position);
+
+        assert_eq!(turned, scores_after(&[1, 5]));
+    }
+
+    #[test]
+    fn test_search_nodes_are_counted() {
+        let game_board = board_after(&[1, 4, 2, 5]);
+
+        // One for the position after each of the five moves, and more to look past them
+        assert_eq!(
+            search_nodes(&game_board, EngineConfig { max_depth: Some(1) }),
+            5
+        );
+        assert!(search_nodes(&game_board, EngineConfig::FULL_DEPTH) > 5);
+    }
+
+    #[test]
+    fn test_solve() {
+        let solution = solve(&board_after(&[]));
+        assert_eq!(solution.result, SolvedResult::Draw);
+        assert_eq!(solution.principal_variation.len(), 9);
+
+        // X: 1 and O: 2, X forces a win through a fork
+        let solution = solve(&board_after(&[1, 2]));
+        assert_eq!(solution.result, SolvedResult::Win(Piece::X));
+        assert_eq!(solution.principal_variation.len(), 5);
+
+        let solution = solve(&board_after(&[1, 4, 2, 5, 3]));
+        assert_eq!(solution.result, SolvedResult::Win(Piece::X));
+        assert!(solution.principal_variation.is_empty());
+    }
+
+    #[test]
+    fn test_mcts_takes_a_win_and_blocks() {
+        let mut random = SeededRandom::new(7);
+
+        assert_eq!(
+            mcts_move(
+                &board_after(&[1, 4, 2, 5]),
+                MctsConfig::DEFAULT,
+                &mut random
+            ),
+            Some(3)
+        );
+        assert_eq!(
+            mcts_move(&board_after(&[1, 5, 2]), MctsConfig::DEFAULT, &mut random),
+            Some(3)
+        );
+    }
+
+    fn board_after(moves: &[usize]) -> GameBoard {
+        let mut game_board = GameBoard::new_with_first_up(Player::Human(Piece::X));
+
+        for &position in moves {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+
+        game_board
+    }
+}
@@ -0,0 +1,234 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tic_tac_toe::game::GameObserver;
+use tic_tac_toe::game_model::{GameBoard, GameState, MoveRecord, PlayerID};
+
+// Everything that happens in a game as JSON Lines, one object per event. Every event has its
+// name, the game it belongs to counted from 1, and when it happened in milliseconds since the
+// Unix epoch. The remaining fields depend on the event:
+//   game_started: size, layers, misere, wild, morris, gravity, notakto, first_up and players,
+//   each with their piece, kind and name. Notakto boards are counted as layers.
+//   move, undo, redo: turn, piece and position, and from for a Three Men's Morris slide
+//   hint: turn, the turn the hint was asked for
+//   swap: turn, the turn it happened before, piece, and kind, who plays that piece from now on
+//   game_ended: result, "X wins", "O wins", "draw" or "unfinished", hints_used, and line for a
+//   win
+pub struct EventLog {
+    output: Option<BufWriter<File>>,
+    game: usize,
+}
+
+impl EventLog {
+    pub fn new(path: Option<&str>) -> Result<Self, String> {
+        let output = match path {
+            Some(path) => Some(BufWriter::new(
+                File::create(path).map_err(|error| format!("Cannot create {path}: {error}"))?,
+            )),
+            None => None,
+        };
+
+        Ok(Self { output, game: 0 })
+    }
+
+    fn game_started(&mut self, game_board: &GameBoard) {
+        self.game += 1;
+
+        let players: Vec<String> = [PlayerID::Player1, PlayerID::Player2]
+            .into_iter()
+            .map(|player_id| {
+                let player = game_board.player_for_id(player_id);
+                format!(
+                    "{{\"piece\":{},\"kind\":{},\"name\":{}}}",
+                    json_string(player.piece().name()),
+                    json_string(&player.name().to_ascii_lowercase()),
+                    json_string(&game_board.display_name(player))
+                )
+            })
+            .collect();
+        let first_up = game_board.player_for_id(game_board.next_up).piece();
+        let blocked: Vec<String> = game_board
+            .blocked_positions()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        self.write(
+            "game_started",
+            &[
+                ("size", game_board.size().to_string()),
+                ("layers", game_board.layers().to_string()),
+                ("misere", game_board.is_misere().to_string()),
+                ("wild", game_board.is_wild().to_string()),
+                ("morris", game_board.is_morris().to_string()),
+                ("gravity", game_board.is_gravity().to_string()),
+                ("notakto", game_board.is_notakto().to_string()),
+                ("blocked", format!("[{}]", blocked.join(","))),
+                ("first_up", json_string(first_up.name())),
+                ("players", format!("[{}]", players.join(","))),
+            ],
+        );
+    }
+
+    fn move_records(&mut self, event: &str, records: &[MoveRecord], game_board: &GameBoard) {
+        for record in records {
+            let piece = game_board.piece_played(record);
+            let mut fields = vec![
+                ("turn", record.turn.to_string()),
+                ("piece", json_string(piece.name())),
+                ("position", record.position.to_string()),
+            ];
+            if let Some(from) = record.from {
+                fields.push(("from", from.to_string()));
+            }
+
+            self.write(event, &fields);
+        }
+    }
+
+    // The hint just taken
+    fn hint(&mut self, game_board: &GameBoard) {
+        self.write(
+            "hint",
+            &[("turn", (game_board.history().len() + 1).to_string())],
+        );
+    }
+
+    fn player_swapped(&mut self, game_board: &GameBoard, player_id: PlayerID) {
+        let player = game_board.player_for_id(player_id);
+        self.write(
+            "swap",
+            &[
+                ("turn", (game_board.history().len() + 1).to_string()),
+                ("piece", json_string(player.piece().name())),
+                ("kind", json_string(&player.name().to_ascii_lowercase())),
+            ],
+        );
+    }
+
+    fn game_ended(&mut self, game_board: &GameBoard) {
+        let mut fields = match game_board.game_state() {
+            GameState::Winner(player, line) => vec![
+                ("result", json_string(&format!("{} wins", player.piece()))),
+                ("line", json_string(&line.name())),
+            ],
+            GameState::Draw => vec![("result", json_string("draw"))],
+            GameState::InProgress => vec![("result", json_string("unfinished"))],
+        };
+
+        fields.insert(1, ("hints_used", game_board.hints_used().to_string()));
+
+        self.write("game_ended", &fields);
+    }
+
+    fn write(&mut self, event: &str, fields: &[(&str, String)]) {
+        let Some(output) = &mut self.output else {
+            return;
+        };
+
+        let time_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_millis());
+        let line = event_line(event, self.game, time_ms, fields);
+
+        // A log that cannot be written is given up on rather than ending the game
+        if let Err(error) = writeln!(output, "{line}").and_then(|()| output.flush()) {
+            println!("Event log not written: {error}");
+            self.output = None;
+        }
+    }
+}
+
+impl GameObserver for EventLog {
+    fn on_game_started(&mut self, game_board: &GameBoard) {
+        self.game_started(game_board);
+    }
+
+    fn on_move_played(&mut self, game_board: &GameBoard, record: &MoveRecord) {
+        self.move_records("move", &[*record], game_board);
+    }
+
+    fn on_moves_undone(&mut self, game_board: &GameBoard, records: &[MoveRecord]) {
+        self.move_records("undo", records, game_board);
+    }
+
+    fn on_moves_redone(&mut self, game_board: &GameBoard, records: &[MoveRecord]) {
+        self.move_records("redo", records, game_board);
+    }
+
+    fn on_hint_taken(&mut self, game_board: &GameBoard) {
+        self.hint(game_board);
+    }
+
+    fn on_player_swapped(&mut self, game_board: &GameBoard, player_id: PlayerID) {
+        self.player_swapped(game_board, player_id);
+    }
+
+    fn on_game_over(&mut self, game_board: &GameBoard, _state: &GameState) {
+        self.game_ended(game_board);
+    }
+}
+
+// Values are already JSON, only the names are quoted here
+fn event_line(event: &str, game: usize, time_ms: u128, fields: &[(&str, String)]) -> String {
+    let mut members = vec![
+        format!("\"event\":{}", json_string(event)),
+        format!("\"game\":{game}"),
+        format!("\"time_ms\":{time_ms}"),
+    ];
+    members.extend(
+        fields
+            .iter()
+            .map(|(name, value)| format!("{}:{value}", json_string(name))),
+    );
+
+    format!("{{{}}}", members.join(","))
+}
+
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            character if character.is_control() => {
+                quoted.push_str(&format!("\\u{:04x}", u32::from(character)));
+            }
+            character => quoted.push(character),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_lines_are_json_objects() {
+        assert_eq!(
+            event_line(
+                "move",
+                2,
+                1_700_000_000_000,
+                &[("turn", "1".to_string()), ("piece", json_string("X"))]
+            ),
+            r#"{"event":"move","game":2,"time_ms":1700000000000,"turn":1,"piece":"X"}"#
+        );
+    }
+
+    #[test]
+    fn test_names_are_escaped() {
+        assert_eq!(
+            json_string("Ada \"The\" \\ Great\n"),
+            r#""Ada \"The\" \\ Great\n""#
+        );
+        assert_eq!(json_string("\u{7}"), r#""\u0007""#);
+    }
+}
@@ -0,0 +1,61 @@
+use std::process::ExitCode;
+use tic_tac_toe::game_model::{GameBoard, GameState, PlayerID};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExitReason {
+    Completed = 0,
+    InvalidUsage = 2,
+    EndedEarly = 3,
+    InvalidInput = 4,
+    SelfTestFailed = 5,
+    Player1Won = 10,
+    Player2Won = 11,
+    Draw = 12,
+}
+
+impl ExitReason {
+    pub const ALL: [Self; 8] = [
+        Self::Completed,
+        Self::InvalidUsage,
+        Self::EndedEarly,
+        Self::InvalidInput,
+        Self::SelfTestFailed,
+        Self::Player1Won,
+        Self::Player2Won,
+        Self::Draw,
+    ];
+
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Completed => "Completed (non-game commands)",
+            Self::InvalidUsage => "Invalid command line usage",
+            Self::EndedEarly => "Game ended early (quit or end of input)",
+            Self::InvalidInput => "Invalid input (an illegal or unreadable move)",
+            Self::SelfTestFailed => "A 'selftest' check failed",
+            Self::Player1Won => "Player 1 won (the human in 'cli', the first mover in 'engine')",
+            Self::Player2Won => "Player 2 won (the computer in 'cli')",
+            Self::Draw => "Draw",
+        }
+    }
+}
+
+impl ExitReason {
+    pub fn for_game(game_board: &GameBoard) -> Self {
+        match game_board.game_state() {
+            GameState::Winner(player, _)
+                if &player == game_board.player_for_id(PlayerID::Player1) =>
+            {
+                Self::Player1Won
+            }
+            GameState::Winner(..) => Self::Player2Won,
+            GameState::Draw => Self::Draw,
+            GameState::InProgress => Self::EndedEarly,
+        }
+    }
+}
+
+impl From<ExitReason> for ExitCode {
+    fn from(reason: ExitReason) -> Self {
+        Self::from(reason as u8)
+    }
+}
@@ -0,0 +1,133 @@
+use crate::game::{Game, Turn};
+use crate::game_model::{GameBoard, GameError};
+
+// What a frontend needs to run a game: a way to ask the human for their turn, to play the
+// computer's, and to show what happened. The turns themselves are played by play_turn and
+// run_game, the same for every frontend.
+pub trait Frontend {
+    fn game(&mut self) -> &mut Game;
+
+    // Reads the human's input and acts on it, such as by playing a move or taking back the last
+    // one. A frontend that polls, like a window, can return before the human has done anything.
+    fn prompt_move(&mut self) -> Result<(), GameError>;
+
+    fn play_computer_move(&mut self) -> Result<(), GameError>;
+
+    // After every turn, even one that changed nothing
+    fn render_board(&mut self, board_before_turn: &GameBoard);
+
+    // Errors that leave the game going, such as an illegal move
+    fn report_error(&mut self, error: &GameError);
+
+    // Once the game is over or the human has quit
+    fn announce_result(&mut self);
+}
+
+// Plays whoever is next up, and returns whether the game goes on
+pub fn play_turn(frontend: &mut impl Frontend) -> bool {
+    let board_before_turn = frontend.game().board().clone();
+
+    let turn_result = match frontend.game().turn() {
+        Turn::Human => frontend.prompt_move(),
+        Turn::Computer => frontend.play_computer_move(),
+        Turn::Over => Ok(()),
+    };
+    frontend.render_board(&board_before_turn);
+
+    match turn_result {
+        Ok(()) => frontend.game().turn() != Turn::Over,
+        Err(GameError::Quit) => false,
+        Err(error) => {
+            frontend.report_error(&error);
+            true
+        }
+    }
+}
+
+// Plays turns until the game ends, for frontends that can wait on the human
+pub fn run_game(frontend: &mut impl Frontend) {
+    while play_turn(frontend) {}
+
+    if frontend.game().turn() != Turn::Over {
+        frontend.game().abandon();
+    }
+    frontend.announce_result();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plays the given positions for the human and keeps a transcript of what it was asked to do
+    struct Scripted {
+        game: Game,
+        positions: Vec<usize>,
+        transcript: Vec<String>,
+    }
+
+    impl Scripted {
+        fn new(game_board: GameBoard, positions: &[usize]) -> Self {
+            Self {
+                game: Game::new(game_board),
+                positions: positions.iter().rev().copied().collect(),
+                transcript: Vec::new(),
+            }
+        }
+    }
+
+    impl Frontend for Scripted {
+        fn game(&mut self) -> &mut Game {
+            &mut self.game
+        }
+
+        fn prompt_move(&mut self) -> Result<(), GameError> {
+            let position = self.positions.pop().ok_or(GameError::Quit)?;
+            let chosen = self.game.board().own_move(position);
+            self.game.play(chosen)
+        }
+
+        fn play_computer_move(&mut self) -> Result<(), GameError> {
+            unreachable!("Only humans play in these tests")
+        }
+
+        fn render_board(&mut self, board_before_turn: &GameBoard) {
+            if self.game.board() != board_before_turn {
+                self.transcript.push("board".to_string());
+            }
+        }
+
+        fn report_error(&mut self, error: &GameError) {
+            self.transcript.push(error.to_string());
+        }
+
+        fn announce_result(&mut self) {
+            let result = self.game.result_text();
+            self.transcript
+                .push(result.unwrap_or_else(|| "quit".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_a_game_runs_to_its_result() {
+        let game_board = GameBoard::new_human_vs_human();
+        let mut frontend = Scripted::new(game_board, &[1, 4, 2, 5, 3]);
+
+        run_game(&mut frontend);
+
+        assert_eq!(frontend.transcript.len(), 6);
+        assert_eq!(frontend.transcript[5], "X won on the top row!");
+    }
+
+    #[test]
+    fn test_errors_are_reported_and_the_game_goes_on_until_quit() {
+        let game_board = GameBoard::new_human_vs_human();
+        let mut frontend = Scripted::new(game_board, &[1, 1]);
+
+        run_game(&mut frontend);
+
+        assert_eq!(frontend.transcript.len(), 3);
+        assert_ne!(frontend.transcript[1], "board");
+        assert_eq!(frontend.transcript[2], "quit");
+        assert_eq!(frontend.game.turn(), Turn::Human);
+    }
+}
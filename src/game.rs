@@ -0,0 +1,387 @@
+use crate::game_model::{
+    execute_computer_turn, redo_turn, undo_turn, GameBoard, GameError, GameState, Move, MoveRecord,
+    PlayerID,
+};
+use crate::strategy::MoveStrategy;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Told what happens in a game as it happens, so that frontends and logs can follow it without
+// the rules printing anything themselves. Each event has the board as it is after the event.
+pub trait GameObserver {
+    fn on_game_started(&mut self, _game_board: &GameBoard) {}
+
+    fn on_move_played(&mut self, _game_board: &GameBoard, _record: &MoveRecord) {}
+
+    // The moves taken back or played again, the most recent first
+    fn on_moves_undone(&mut self, _game_board: &GameBoard, _records: &[MoveRecord]) {}
+
+    fn on_moves_redone(&mut self, _game_board: &GameBoard, _records: &[MoveRecord]) {}
+
+    fn on_hint_taken(&mut self, _game_board: &GameBoard) {}
+
+    // A player handed over to the computer or taken back by a human, with the board showing who
+    // plays them now
+    fn on_player_swapped(&mut self, _game_board: &GameBoard, _player_id: PlayerID) {}
+
+    // After a move that did not end the game
+    fn on_turn_changed(&mut self, _game_board: &GameBoard, _next_up: PlayerID) {}
+
+    // After the move that ended the game, or with the game still in progress when it is abandoned
+    fn on_game_over(&mut self, _game_board: &GameBoard, _state: &GameState) {}
+}
+
+// For observers that outlive the game they are watching, such as a log kept over several games
+impl<T: GameObserver> GameObserver for Rc<RefCell<T>> {
+    fn on_game_started(&mut self, game_board: &GameBoard) {
+        self.borrow_mut().on_game_started(game_board);
+    }
+
+    fn on_move_played(&mut self, game_board: &GameBoard, record: &MoveRecord) {
+        self.borrow_mut().on_move_played(game_board, record);
+    }
+
+    fn on_moves_undone(&mut self, game_board: &GameBoard, records: &[MoveRecord]) {
+        self.borrow_mut().on_moves_undone(game_board, records);
+    }
+
+    fn on_moves_redone(&mut self, game_board: &GameBoard, records: &[MoveRecord]) {
+        self.borrow_mut().on_moves_redone(game_board, records);
+    }
+
+    fn on_hint_taken(&mut self, game_board: &GameBoard) {
+        self.borrow_mut().on_hint_taken(game_board);
+    }
+
+    fn on_player_swapped(&mut self, game_board: &GameBoard, player_id: PlayerID) {
+        self.borrow_mut().on_player_swapped(game_board, player_id);
+    }
+
+    fn on_turn_changed(&mut self, game_board: &GameBoard, next_up: PlayerID) {
+        self.borrow_mut().on_turn_changed(game_board, next_up);
+    }
+
+    fn on_game_over(&mut self, game_board: &GameBoard, state: &GameState) {
+        self.borrow_mut().on_game_over(game_board, state);
+    }
+}
+
+// What a frontend does next: wait for the human, let the computer think, or show the result
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Turn {
+    Human,
+    Computer,
+    Over,
+}
+
+// Drives a game for the frontends, which only read input and show the board. Every change to the
+// board goes through here, so that the observers hear about it.
+pub struct Game {
+    game_board: GameBoard,
+    observers: Vec<Box<dyn GameObserver>>,
+    // Picked by the human while the computer was to move, played once it is their turn
+    premove: Option<Move>,
+}
+
+impl Game {
+    pub fn new(game_board: GameBoard) -> Self {
+        Self {
+            game_board,
+            observers: Vec::new(),
+            premove: None,
+        }
+    }
+
+    pub fn add_observer(&mut self, observer: impl GameObserver + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    // Tells the observers added so far that the game has begun
+    pub fn start(&mut self) {
+        for observer in &mut self.observers {
+            observer.on_game_started(&self.game_board);
+        }
+    }
+
+    pub const fn board(&self) -> &GameBoard {
+        &self.game_board
+    }
+
+    // The board once the game is done with, leaving the observers behind
+    pub fn into_board(self) -> GameBoard {
+        self.game_board
+    }
+
+    pub fn turn(&self) -> Turn {
+        if self.game_board.is_game_over() {
+            Turn::Over
+        } else if self.game_board.is_computers_turn() {
+            Turn::Computer
+        } else {
+            Turn::Human
+        }
+    }
+
+    pub fn play(&mut self, chosen: Move) -> Result<(), GameError> {
+        self.game_board.play_next_up(chosen)?;
+        self.move_played();
+
+        Ok(())
+    }
+
+    pub fn play_computer_turn(&mut self, strategy: &dyn MoveStrategy) -> Result<Move, GameError> {
+        let chosen = execute_computer_turn(&mut self.game_board, strategy)?;
+        self.move_played();
+
+        Ok(chosen)
+    }
+
+    // Replaces any premove queued before. Only its cell counts, and in wild games its piece, as
+    // the rest can change before it is played.
+    pub fn queue_premove(&mut self, chosen: Move) {
+        self.premove = Some(chosen);
+    }
+
+    pub const fn premove(&self) -> Option<Move> {
+        self.premove
+    }
+
+    // Takes the premove off the queue once it is the human's turn and plays it, if it is still
+    // legal. It is dropped if the computer took its cell or ended the game meanwhile.
+    pub fn play_premove(&mut self) -> Result<Option<Move>, GameError> {
+        if self.turn() == Turn::Computer {
+            return Ok(None);
+        }
+        let Some(queued) = self.premove.take() else {
+            return Ok(None);
+        };
+
+        let piece = if self.game_board.is_wild() {
+            queued.piece
+        } else {
+            self.game_board.next_piece()
+        };
+        // Under gravity the column was picked, and the piece lands wherever that is now
+        let chosen = match self.game_board.coordinate_at_position(queued.position) {
+            Some(coordinate) if self.game_board.is_gravity() => {
+                self.game_board.drop_move(coordinate.col() + 1, piece).ok()
+            }
+            _ => Some(Move::place(queued.position, piece)),
+        };
+
+        match chosen {
+            Some(chosen) if self.game_board.legal_moves().contains(&chosen) => {
+                self.play(chosen).map(|()| Some(chosen))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // Takes back the last human move and the computer's replies to it, and any premove
+    pub fn undo_turn(&mut self) -> Vec<MoveRecord> {
+        self.premove = None;
+        let undone = undo_turn(&mut self.game_board);
+        if !undone.is_empty() {
+            self.notify(|observer, game_board| observer.on_moves_undone(game_board, &undone));
+        }
+
+        undone
+    }
+
+    pub fn redo_turn(&mut self) -> Vec<MoveRecord> {
+        let redone = redo_turn(&mut self.game_board);
+        if !redone.is_empty() {
+            self.notify(|observer, game_board| observer.on_moves_redone(game_board, &redone));
+        }
+
+        redone
+    }
+
+    pub fn take_hint(&mut self) -> Result<(), GameError> {
+        self.game_board.take_hint()?;
+        for observer in &mut self.observers {
+            observer.on_hint_taken(&self.game_board);
+        }
+
+        Ok(())
+    }
+
+    // Hands a player's turns over to the computer or back to a human
+    pub fn swap_player(&mut self, player_id: PlayerID) {
+        self.game_board.swap_player(player_id);
+        for observer in &mut self.observers {
+            observer.on_player_swapped(&self.game_board, player_id);
+        }
+    }
+
+    // Ends the game where it stands, such as when the player quits part way through
+    pub fn abandon(&mut self) {
+        let state = self.game_board.game_state();
+        for observer in &mut self.observers {
+            observer.on_game_over(&self.game_board, &state);
+        }
+    }
+
+    // Who won and how, such as "You won along the top row!", or that it was a draw. None while
+    // the game is still going.
+    pub fn result_text(&self) -> Option<String> {
+        match self.game_board.game_state() {
+            GameState::Winner(player, line) => {
+                let how = self.game_board.how_won(&line);
+                let who = self.game_board.display_name(&player);
+                Some(format!("{who} won {how}!"))
+            }
+            GameState::Draw => Some("This game results in a draw.".to_string()),
+            GameState::InProgress => None,
+        }
+    }
+
+    fn move_played(&mut self) {
+        let Some(record) = self.game_board.history().last().copied() else {
+            return;
+        };
+
+        self.notify(|observer, game_board| observer.on_move_played(game_board, &record));
+    }
+
+    // Tells every observer about a change to the board, and then whose turn it is or how the game
+    // ended
+    fn notify(&mut self, event: impl Fn(&mut dyn GameObserver, &GameBoard)) {
+        let game_over = self
+            .game_board
+            .is_game_over()
+            .then(|| self.game_board.game_state());
+
+        for observer in &mut self.observers {
+            event(observer.as_mut(), &self.game_board);
+
+            match &game_over {
+                Some(state) => observer.on_game_over(&self.game_board, state),
+                None => observer.on_turn_changed(&self.game_board, self.game_board.next_up),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_model::{Piece, Player};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // Writes each event down where the test can still read it once the game has the observer
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+
+    impl GameObserver for Recorder {
+        fn on_move_played(&mut self, _game_board: &GameBoard, record: &MoveRecord) {
+            self.0
+                .borrow_mut()
+                .push(format!("move {}", record.position));
+        }
+
+        fn on_turn_changed(&mut self, game_board: &GameBoard, next_up: PlayerID) {
+            let piece = game_board.player_for_id(next_up).piece();
+            self.0.borrow_mut().push(format!("{piece} to play"));
+        }
+
+        fn on_player_swapped(&mut self, game_board: &GameBoard, player_id: PlayerID) {
+            let player = game_board.player_for_id(player_id);
+            self.0
+                .borrow_mut()
+                .push(format!("{} to {}", player.piece(), player.name()));
+        }
+
+        fn on_game_over(&mut self, _game_board: &GameBoard, state: &GameState) {
+            let result = match state {
+                GameState::Winner(player, _) => format!("{} wins", player.piece()),
+                GameState::Draw | GameState::InProgress => "draw".to_string(),
+            };
+            self.0.borrow_mut().push(result);
+        }
+    }
+
+    #[test]
+    fn test_observers_follow_every_move_to_the_end() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut game = Game::new(GameBoard::new_with_first_up(Player::Human(Piece::X)));
+        game.add_observer(Recorder(Rc::clone(&events)));
+
+        for position in [1, 4, 2, 5, 3] {
+            assert!(game.play(game.board().own_move(position)).is_ok());
+        }
+
+        assert_eq!(
+            events.borrow()[..4],
+            ["move 1", "O to play", "move 4", "X to play"]
+        );
+        assert_eq!(events.borrow()[8..], ["move 3", "X wins"]);
+    }
+
+    #[test]
+    fn test_observers_hear_players_being_swapped() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut game = Game::new(GameBoard::new_with_first_up(Player::Human(Piece::X)));
+        game.add_observer(Recorder(Rc::clone(&events)));
+
+        game.swap_player(PlayerID::Player1);
+        game.swap_player(PlayerID::Player1);
+
+        assert_eq!(*events.borrow(), ["X to Computer", "X to Human"]);
+        assert_eq!(game.turn(), Turn::Human);
+    }
+
+    #[test]
+    fn test_premoves_wait_for_the_human_and_are_dropped_once_illegal() {
+        let mut game = Game::new(GameBoard::new_with_first_up(Player::Computer(Piece::X)));
+        game.queue_premove(Move::place(5, Piece::O));
+
+        assert_eq!(game.play_premove(), Ok(None));
+        assert!(game.premove().is_some());
+
+        // The computer takes the cell first
+        assert!(game.play(Move::place(5, Piece::X)).is_ok());
+        assert_eq!(game.play_premove(), Ok(None));
+        assert_eq!(game.premove(), None);
+
+        game.queue_premove(Move::place(1, Piece::X));
+        assert_eq!(game.play_premove(), Ok(Some(Move::place(1, Piece::O))));
+        assert_eq!(game.board().history().len(), 2);
+    }
+
+    #[test]
+    fn test_gravity_premoves_land_in_their_column() {
+        let game_board =
+            GameBoard::new_with_first_up(Player::Computer(Piece::X)).with_gravity(true);
+        let mut game = Game::new(game_board);
+        game.queue_premove(Move::place(7, Piece::O));
+
+        assert!(game.play(Move::place(7, Piece::X)).is_ok());
+
+        assert_eq!(game.play_premove(), Ok(Some(Move::place(4, Piece::O))));
+    }
+
+    #[test]
+    fn test_result_text_names_the_winner_as_the_board_does() {
+        let mut game = Game::new(GameBoard::new_with_first_up(Player::Computer(Piece::X)));
+        assert_eq!(game.result_text(), None);
+
+        for position in [1, 4, 2, 5, 3] {
+            assert!(game.play(game.board().own_move(position)).is_ok());
+        }
+
+        assert!(game
+            .result_text()
+            .is_some_and(|text| text.starts_with("Computer won ")));
+    }
+
+    #[test]
+    fn test_illegal_moves_are_not_reported() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut game = Game::new(GameBoard::new_with_first_up(Player::Human(Piece::X)));
+        game.add_observer(Recorder(Rc::clone(&events)));
+
+        assert!(game.play(Move::place(10, Piece::X)).is_err());
+        assert!(events.borrow().is_empty());
+    }
+}
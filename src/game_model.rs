@@ -1,8 +1,15 @@
-use rand::Rng;
+use crate::bitboard::Bitboard;
+use crate::random_source::{RandomSource, ThreadRandom};
+use crate::strategy::MoveStrategy;
+use crate::table::Table;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
-use std::ops::RangeInclusive;
+use std::ops::{Range, RangeInclusive};
+use std::rc::Rc;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Piece {
     X,
     O,
@@ -15,6 +22,13 @@ impl Piece {
             Self::X => "X",
         }
     }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        [Self::X, Self::O]
+            .into_iter()
+            .find(|piece| piece.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown piece '{name}', expected X or O"))
+    }
 }
 
 impl fmt::Display for Piece {
@@ -26,19 +40,94 @@ impl fmt::Display for Piece {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum CellState {
-    Empty,
-    Occupied(Piece),
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GameError {
+    InvalidPosition(usize),
+    CellOccupied(usize),
+    CellBlocked(usize),
+    GameOver,
+    NoAvailablePositions,
+    NoHintsLeft,
+    NotYourPiece(Piece),
+    // Three Men's Morris: every piece is down and one has to slide, or not yet
+    MustSlide,
+    CannotSlide,
+    // A slide from a cell without the mover's piece, or to a cell not next to it
+    NothingToSlide(usize),
+    NotAdjacent(usize, usize),
+    // Under gravity pieces only go in the lowest empty cell of a column, counted from 1
+    NotLowestInColumn(usize),
+    InvalidColumn(usize),
+    ColumnFull(usize),
+    // A Notakto board, counted from 1, that already has a line
+    BoardDead(usize),
+    // The player chose to stop, or their scripted input ran out
+    Quit,
+    // Input that could not be read or understood, with the message to show
+    InvalidInput(String),
 }
 
-impl Default for CellState {
-    fn default() -> Self {
-        Self::Empty
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidPosition(position) => {
+                write!(f, "{position} is not a valid game board position")
+            }
+            Self::CellOccupied(position) => write!(f, "Position {position} is already occupied"),
+            Self::CellBlocked(position) => write!(f, "Position {position} is blocked"),
+            Self::GameOver => write!(f, "The game is already over"),
+            Self::NoAvailablePositions => write!(f, "No available positions"),
+            Self::NoHintsLeft => write!(f, "There are no hints left for this game"),
+            Self::NotYourPiece(piece) => {
+                write!(
+                    f,
+                    "{piece} is not yours to play, only wild games allow either piece"
+                )
+            }
+            Self::MustSlide => write!(
+                f,
+                "All your pieces are on the board, slide one to an empty cell next to it"
+            ),
+            Self::CannotSlide => write!(
+                f,
+                "Pieces only slide in Three Men's Morris, once all three are on the board"
+            ),
+            Self::NothingToSlide(position) => {
+                write!(f, "There is no piece of yours at position {position}")
+            }
+            Self::NotAdjacent(from, to) => write!(f, "Position {to} is not next to {from}"),
+            Self::NotLowestInColumn(position) => write!(
+                f,
+                "Pieces drop to the bottom, position {position} is above an empty cell"
+            ),
+            Self::InvalidColumn(column) => write!(f, "There is no column {column}"),
+            Self::ColumnFull(column) => write!(f, "Column {column} is full"),
+            Self::BoardDead(board) => write!(f, "Board {board} is dead, it already has a line"),
+            Self::Quit => write!(f, "Exiting the game"),
+            Self::InvalidInput(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+// Lets model errors pass through code that reports plain messages
+impl From<GameError> for String {
+    fn from(error: GameError) -> Self {
+        error.to_string()
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CellState {
+    #[default]
+    Empty,
+    Occupied(Piece),
+    // Taken out of play before the game starts, neither player can go there
+    Blocked,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Player {
     Computer(Piece),
     Human(Piece),
@@ -57,6 +146,43 @@ impl Player {
             Self::Human(piece) | Self::Computer(piece) => piece,
         }
     }
+
+    // The same piece, but played by the other kind of player
+    pub const fn swapped(&self) -> Self {
+        match self {
+            Self::Computer(piece) => Self::Human(*piece),
+            Self::Human(piece) => Self::Computer(*piece),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Difficulty {
+    #[default]
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 3] = [Self::Easy, Self::Medium, Self::Hard];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                format!("Unknown difficulty '{name}', expected one of: easy, medium, hard")
+            })
+    }
 }
 
 #[derive(PartialEq, Eq)]
@@ -66,31 +192,204 @@ enum Coin {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PlayerID {
     Player1,
     Player2,
 }
 
+impl PlayerID {
+    pub const fn other(self) -> Self {
+        match self {
+            Self::Player1 => Self::Player2,
+            Self::Player2 => Self::Player1,
+        }
+    }
+}
+
+// A piece placed on a cell. Only wild games let it be anything but the mover's own piece.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Move {
+    pub position: usize,
+    pub piece: Piece,
+    // Where the piece slides from in Three Men's Morris, None when placing a new one
+    pub from: Option<usize>,
+}
+
+impl Move {
+    pub const fn place(position: usize, piece: Piece) -> Self {
+        Self {
+            position,
+            piece,
+            from: None,
+        }
+    }
+
+    pub const fn slide(from: usize, position: usize, piece: Piece) -> Self {
+        Self {
+            position,
+            piece,
+            from: Some(from),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveRecord {
+    pub player: PlayerID,
+    pub position: usize,
+    // Counted from 1 for the first move of the game
+    pub turn: usize,
+    // Only kept for wild games and Notakto, elsewhere the piece is always the player's own
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub piece: Option<Piece>,
+    // The cell a Three Men's Morris piece slid from
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub from: Option<usize>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameBoard {
     player_1: Player,
     player_2: Player,
     pub next_up: PlayerID,
-    cells: [[CellState; 3]; 3],
+    // Square layers of rows, one layer after another. Flat boards have a single layer.
+    cells: Bitboard,
+    // Pieces in a row needed to win, None for the full width of the board
+    win_length: Option<usize>,
+    // Misère rules, where completing a line loses
+    #[cfg_attr(feature = "serde", serde(default))]
+    misere: bool,
+    // Wild rules, where either player may place either piece
+    #[cfg_attr(feature = "serde", serde(default))]
+    wild: bool,
+    // A 3D board, a cube with as many layers as it is wide
+    #[cfg_attr(feature = "serde", serde(default))]
+    cube: bool,
+    // Three Men's Morris, where pieces slide once each player's three are down
+    #[cfg_attr(feature = "serde", serde(default))]
+    morris: bool,
+    // Pieces drop to the lowest empty cell of their column, as in Connect Four
+    #[cfg_attr(feature = "serde", serde(default))]
+    gravity: bool,
+    // Notakto on this many boards, laid out like the layers of a cube. Both players place X, a
+    // board is dead once it has a line, and whoever finishes off the last one loses.
+    #[cfg_attr(feature = "serde", serde(default))]
+    notakto: Option<usize>,
+    // Names chosen at game start, indexed by player 1 then player 2
+    names: [Option<String>; 2],
+    history: Vec<MoveRecord>,
+    // Undone moves, the most recently undone last
+    undone: Vec<MoveRecord>,
+    // Hints allowed in this game, None for as many as wanted
+    hint_budget: Option<usize>,
+    // The turn each hint was asked for on, undoing a move does not give its hints back
+    #[cfg_attr(feature = "serde", serde(default))]
+    hint_turns: Vec<usize>,
+}
+
+// Sets up a human vs computer game. Anything not chosen is left to chance, as in GameBoard::new.
+#[derive(Debug, Default, Clone)]
+pub struct GameBoardBuilder {
+    human_piece: Option<Piece>,
+    human_first: Option<bool>,
+    human_name: Option<String>,
+    computer_name: Option<String>,
+    size: Option<usize>,
+}
+
+impl GameBoardBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub const fn human_piece(mut self, piece: Piece) -> Self {
+        self.human_piece = Some(piece);
+        self
+    }
+
+    pub const fn human_first(mut self, human_first: bool) -> Self {
+        self.human_first = Some(human_first);
+        self
+    }
+
+    pub fn human_name(mut self, name: impl Into<String>) -> Self {
+        self.human_name = Some(name.into());
+        self
+    }
+
+    pub fn computer_name(mut self, name: impl Into<String>) -> Self {
+        self.computer_name = Some(name.into());
+        self
+    }
+
+    pub const fn size(mut self, size: usize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn build(self) -> GameBoard {
+        self.build_with_random_source(&mut ThreadRandom)
+    }
+
+    // The human is always player 1
+    pub fn build_with_random_source(self, random: &mut dyn RandomSource) -> GameBoard {
+        let human_piece = self
+            .human_piece
+            .unwrap_or_else(|| GameBoard::random_piece(random));
+        let human_first = self
+            .human_first
+            .unwrap_or_else(|| GameBoard::flip_coin(random) == Coin::Heads);
+
+        GameBoard {
+            player_1: Player::Human(human_piece),
+            player_2: Player::Computer(GameBoard::other_piece(human_piece)),
+            next_up: if human_first {
+                PlayerID::Player1
+            } else {
+                PlayerID::Player2
+            },
+            cells: Bitboard::empty(self.size.unwrap_or(GameBoard::CLASSIC_SIZE), 1),
+            win_length: None,
+            misere: false,
+            wild: false,
+            cube: false,
+            morris: false,
+            gravity: false,
+            notakto: None,
+            names: [self.human_name, self.computer_name],
+            history: Vec::new(),
+            undone: Vec::new(),
+            hint_budget: None,
+            hint_turns: Vec::new(),
+        }
+    }
 }
 
 pub struct Coordinate {
+    // Always 0 on a flat board
+    layer: usize,
     row: usize,
     col: usize,
 }
 
 impl fmt::Debug for Coordinate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Coordinate {{ row: {}, col: {} }}", self.row, self.col)
+        write!(
+            f,
+            "Coordinate {{ layer: {}, row: {}, col: {} }}",
+            self.layer, self.row, self.col
+        )
     }
 }
 
 impl Coordinate {
+    pub const fn layer(&self) -> usize {
+        self.layer
+    }
+
     pub const fn row(&self) -> usize {
         self.row
     }
@@ -102,635 +401,2531 @@ impl Coordinate {
 
 #[allow(dead_code)]
 impl Coordinate {
-    pub const TOP_LEFT: Self = Self { row: 0, col: 0 };
-    pub const TOP_CENTER: Self = Self { row: 0, col: 1 };
-    pub const TOP_RIGHT: Self = Self { row: 0, col: 2 };
+    pub const TOP_LEFT: Self = Self {
+        layer: 0,
+        row: 0,
+        col: 0,
+    };
+    pub const TOP_CENTER: Self = Self {
+        layer: 0,
+        row: 0,
+        col: 1,
+    };
+    pub const TOP_RIGHT: Self = Self {
+        layer: 0,
+        row: 0,
+        col: 2,
+    };
+
+    pub const MIDDLE_LEFT: Self = Self {
+        layer: 0,
+        row: 1,
+        col: 0,
+    };
+    pub const MIDDLE_CENTER: Self = Self {
+        layer: 0,
+        row: 1,
+        col: 1,
+    };
+    pub const MIDDLE_RIGHT: Self = Self {
+        layer: 0,
+        row: 1,
+        col: 2,
+    };
+
+    pub const BOTTOM_LEFT: Self = Self {
+        layer: 0,
+        row: 2,
+        col: 0,
+    };
+    pub const BOTTOM_CENTER: Self = Self {
+        layer: 0,
+        row: 2,
+        col: 1,
+    };
+    pub const BOTTOM_RIGHT: Self = Self {
+        layer: 0,
+        row: 2,
+        col: 2,
+    };
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LineKind {
+    // Counted from 0, rows from the top and columns from the left
+    Row(usize),
+    Column(usize),
+    // From the top left corner to the bottom right
+    Diagonal,
+    AntiDiagonal,
+    // Only on a cube: straight down through the layers, slanting down through them along a row or
+    // column, or through them from corner to corner
+    Pillar,
+    LayerDiagonal,
+    SpaceDiagonal,
+}
+
+impl LineKind {
+    // The kind of line starting at the row and column that runs the way of the step
+    const fn of(step: LineStep, row: usize, col: usize) -> Self {
+        match step {
+            [0, 0, _] => Self::Row(row),
+            [0, _, 0] => Self::Column(col),
+            [0, 1, 1] => Self::Diagonal,
+            [0, _, _] => Self::AntiDiagonal,
+            [_, 0, 0] => Self::Pillar,
+            [_, 0, _] | [_, _, 0] => Self::LayerDiagonal,
+            _ => Self::SpaceDiagonal,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WinningLine {
+    pub kind: LineKind,
+    // As many as the board's win length
+    pub positions: Vec<usize>,
+    pub board_size: usize,
+    // Counted from 0, the layer of a cube that a line within one layer lies in
+    pub layer: Option<usize>,
+}
 
-    pub const MIDDLE_LEFT: Self = Self { row: 1, col: 0 };
-    pub const MIDDLE_CENTER: Self = Self { row: 1, col: 1 };
-    pub const MIDDLE_RIGHT: Self = Self { row: 1, col: 2 };
+// The layers, rows and columns moved from one cell of a line to the next, each -1, 0 or 1
+type LineStep = [isize; 3];
+
+// Rows, columns and both diagonals, then the lines through the layers that only cubes have
+const FLAT_LINE_STEPS: usize = 4;
+const LINE_STEPS: [LineStep; 13] = [
+    [0, 0, 1],
+    [0, 1, 0],
+    [0, 1, 1],
+    [0, 1, -1],
+    [1, 0, 0],
+    [1, 0, 1],
+    [1, 0, -1],
+    [1, 1, 0],
+    [1, -1, 0],
+    [1, 1, 1],
+    [1, 1, -1],
+    [1, -1, 1],
+    [1, -1, -1],
+];
+
+// Where a line of win_length cells starts and the way it runs from there
+#[derive(Clone, Copy)]
+struct LineStart {
+    kind: LineKind,
+    layer: usize,
+    row: usize,
+    col: usize,
+    step: LineStep,
+}
+
+// A way of turning or flipping a square board, given the last row or column and where a cell is.
+// The mirror image left to right comes first, as it is the only one that keeps the bottom of the
+// board at the bottom.
+type Symmetry = fn(usize, usize, usize) -> (usize, usize);
+
+const SYMMETRIES: [Symmetry; 7] = [
+    |last, row, col| (row, last - col),
+    |last, row, col| (last - row, col),
+    |_, row, col| (col, row),
+    |last, row, col| (col, last - row),
+    |last, row, col| (last - row, last - col),
+    |last, row, col| (last - col, row),
+    |last, row, col| (last - col, last - row),
+];
+
+// Every way of turning and flipping a flat board `size` cells wide, leaving it as it is first, as
+// the position each position moves to counted from 1
+pub fn symmetries(size: usize) -> Vec<Vec<usize>> {
+    let last = size.saturating_sub(1);
+    let identity: Symmetry = |_, row, col| (row, col);
+
+    [identity]
+        .iter()
+        .chain(&SYMMETRIES)
+        .map(|symmetry| {
+            (0..size * size)
+                .map(|index| {
+                    let (row, col) = symmetry(last, index / size, index % size);
+                    row * size + col + 1
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// A line with a bit set for each of its cells, to hold up against the pieces on the board
+#[derive(Clone, Copy)]
+struct Line {
+    start: LineStart,
+    mask: u128,
+}
+
+// The board's size, layers, win length and whether it is a cube
+type BoardShape = (usize, usize, usize, bool);
+
+thread_local! {
+    // The lines of each shape of board, worked out the first time one is played rather than on
+    // every check for a win
+    static LINES: RefCell<HashMap<BoardShape, Rc<[Line]>>> = RefCell::new(HashMap::new());
+}
+
+impl WinningLine {
+    // Rows and columns between the outer and middle ones are numbered, counting from 1. Diagonals
+    // shorter than the board are named by the way they run.
+    pub fn name(&self) -> String {
+        let last = self.board_size - 1;
+        let corner_to_corner = self.positions.len() == self.board_size;
+        let name = |index: usize, first: &str, last_name: &str, line: &str| {
+            if index == 0 {
+                format!("{first} {line}")
+            } else if index == last {
+                format!("{last_name} {line}")
+            } else if index * 2 == last {
+                format!("middle {line}")
+            } else {
+                format!("{line} {}", index + 1)
+            }
+        };
+
+        let line = match self.kind {
+            LineKind::Row(row) => name(row, "top", "bottom", "row"),
+            LineKind::Column(col) => name(col, "left", "right", "column"),
+            LineKind::Diagonal if corner_to_corner => "diagonal from the top left".to_string(),
+            LineKind::AntiDiagonal if corner_to_corner => "diagonal from the top right".to_string(),
+            LineKind::Diagonal => "diagonal running down to the right".to_string(),
+            LineKind::AntiDiagonal => "diagonal running down to the left".to_string(),
+            LineKind::Pillar => "pillar through the layers".to_string(),
+            LineKind::LayerDiagonal => "diagonal through the layers".to_string(),
+            LineKind::SpaceDiagonal if corner_to_corner => {
+                "diagonal from corner to corner of the cube".to_string()
+            }
+            LineKind::SpaceDiagonal => "diagonal across the cube".to_string(),
+        };
 
-    pub const BOTTOM_LEFT: Self = Self { row: 2, col: 0 };
-    pub const BOTTOM_CENTER: Self = Self { row: 2, col: 1 };
-    pub const BOTTOM_RIGHT: Self = Self { row: 2, col: 2 };
+        match self.layer {
+            Some(layer) => format!("{line} of layer {}", layer + 1),
+            None => line,
+        }
+    }
+}
+
+impl fmt::Display for WinningLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// A position turned and flipped into whichever of its symmetries sorts first, so that positions
+// that are rotations or mirror images of each other have the same form
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+pub struct CanonicalForm(Bitboard);
+
+impl CanonicalForm {
+    pub fn cell_at_position(&self, position: usize) -> Option<CellState> {
+        let Self(cells) = self;
+        let cell_count = cells.size() * cells.size() * cells.layers();
+
+        (1..=cell_count)
+            .contains(&position)
+            .then(|| *cells.cell(position - 1))
+    }
 }
 
-pub enum GameState<'a> {
-    Winner(&'a Player),
+// Owns what it says, so frontends can keep it in their state or send it to another thread
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GameState {
+    Winner(Player, WinningLine),
     Draw,
     InProgress,
 }
 
 impl GameBoard {
-    pub const POSITIONS: RangeInclusive<usize> = 1..=9;
+    pub const CLASSIC_SIZE: usize = 3;
+    // The sizes frontends offer. The search strategies get slow on anything larger.
+    pub const SIZES: RangeInclusive<usize> = 3..=5;
+    // Each player's pieces in Three Men's Morris, and the turns after which a game nobody has won
+    // is a draw, as pieces can slide back and forth for ever
+    pub const MORRIS_PIECES: usize = 3;
+    pub const MORRIS_TURN_LIMIT: usize = 50;
+    // How many boards frontends offer for Notakto
+    pub const NOTAKTO_BOARDS: RangeInclusive<usize> = 1..=3;
+    // How many cells frontends offer to block as a handicap
+    pub const BLOCKED_CELLS: RangeInclusive<usize> = 1..=2;
 
     pub fn new() -> Self {
-        let random_piece = Self::random_piece();
-        let human = Player::Human(random_piece);
-        let computer = Player::Computer(Self::other_piece(random_piece));
+        Self::new_with_random_source(&mut ThreadRandom)
+    }
+
+    pub fn new_with_random_source(random: &mut dyn RandomSource) -> Self {
+        GameBoardBuilder::new().build_with_random_source(random)
+    }
+
+    // The same players on an empty board of another size
+    pub fn with_size(self, size: usize) -> Self {
+        let layers = if self.cube {
+            size
+        } else {
+            self.notakto.unwrap_or(1)
+        };
 
         Self {
-            player_1: human,
-            player_2: computer,
-            next_up: Self::random_player_from(PlayerID::Player1, PlayerID::Player2),
-            cells: [[CellState::default(); 3]; 3],
+            cells: Bitboard::empty(size, layers),
+            win_length: None,
+            history: Vec::new(),
+            undone: Vec::new(),
+            hint_turns: Vec::new(),
+            ..self
         }
     }
 
-    pub fn player_for_id(&self, player_id: PlayerID) -> &Player {
-        if player_id == PlayerID::Player1 {
-            &self.player_1
-        } else {
-            &self.player_2
+    // Wins with fewer in a row than the board is wide, as long as the board is at least that wide
+    pub fn with_win_length(self, win_length: usize) -> Self {
+        Self {
+            win_length: (win_length < self.size()).then_some(win_length),
+            ..self
         }
     }
 
-    pub fn get_cell_at_position(&self, position: usize) -> Option<&CellState> {
-        let coordinate = Self::coordinate_at_position(position)?;
+    pub fn with_misere(self, misere: bool) -> Self {
+        Self { misere, ..self }
+    }
 
-        Some(&self.cells[coordinate.row()][coordinate.col()])
+    // Notakto always plays by misère rules
+    pub const fn is_misere(&self) -> bool {
+        self.misere || self.notakto.is_some()
     }
 
-    fn set_cell_at_position(&mut self, cell: CellState, position: usize) -> Result<(), String> {
-        let Some(coordinate) = Self::coordinate_at_position(position) else {
-            return Err(format!("{position} is not a valid game board position"));
-        };
+    pub fn with_wild(self, wild: bool) -> Self {
+        Self { wild, ..self }
+    }
 
-        self.cells[coordinate.row()][coordinate.col()] = cell;
+    pub const fn is_wild(&self) -> bool {
+        self.wild
+    }
 
-        Ok(())
+    pub fn with_gravity(self, gravity: bool) -> Self {
+        Self { gravity, ..self }
     }
 
-    pub fn play_next_up_at_position(&mut self, position: usize) -> Result<(), String> {
-        let Some(cell) = self.get_cell_at_position(position) else {
-            return Err(format!("{position} is not a valid game board position"));
-        };
+    pub const fn is_gravity(&self) -> bool {
+        self.gravity
+    }
+
+    // Where a piece dropped in a column lands, the empty cell it falls to before hitting a piece,
+    // a blocked cell or the bottom. Columns are counted from 1 on the left.
+    pub fn drop_position(&self, column: usize) -> Result<usize, GameError> {
+        let col = column
+            .checked_sub(1)
+            .filter(|&col| col < self.size())
+            .ok_or(GameError::InvalidColumn(column))?;
+
+        (0..self.size())
+            .map(|row| self.position_at_coordinate(row, col))
+            .take_while(|&position| self.get_cell_at_position(position) == Some(&CellState::Empty))
+            .last()
+            .ok_or(GameError::ColumnFull(column))
+    }
+
+    // The piece dropped in a column, ready to play
+    pub fn drop_move(&self, column: usize, piece: Piece) -> Result<Move, GameError> {
+        self.drop_position(column)
+            .map(|position| Move::place(position, piece))
+    }
+
+    // An empty cube of the same size, or back to an empty flat board
+    pub fn with_cube(self, cube: bool) -> Self {
+        let size = self.size();
+        let layers = if cube { size } else { 1 };
 
-        if matches!(&cell, CellState::Occupied(_)) {
-            return Err(String::from("Spot on board is already occupied"));
+        Self {
+            cube,
+            cells: Bitboard::empty(size, layers),
+            history: Vec::new(),
+            undone: Vec::new(),
+            hint_turns: Vec::new(),
+            ..self
         }
+    }
 
-        let next_piece = match self.next_up {
-            PlayerID::Player1 => match self.player_1 {
-                Player::Computer(piece) | Player::Human(piece) => piece,
-            },
-            PlayerID::Player2 => match self.player_2 {
-                Player::Computer(piece) | Player::Human(piece) => piece,
-            },
+    pub const fn is_cube(&self) -> bool {
+        self.cube
+    }
+
+    // Empty Notakto boards, as many as asked for, or back to the usual game
+    pub fn with_notakto(self, boards: Option<usize>) -> Self {
+        let game_board = Self {
+            notakto: boards,
+            ..self
         };
 
-        self.set_cell_at_position(CellState::Occupied(next_piece), position)?;
+        Self {
+            cells: Bitboard::empty(game_board.size(), game_board.layers()),
+            history: Vec::new(),
+            undone: Vec::new(),
+            hint_turns: Vec::new(),
+            ..game_board
+        }
+    }
 
-        self.next_up = match self.next_up {
-            PlayerID::Player1 => PlayerID::Player2,
-            PlayerID::Player2 => PlayerID::Player1,
-        };
+    pub const fn is_notakto(&self) -> bool {
+        self.notakto.is_some()
+    }
 
-        Ok(())
+    // A Notakto board with a line on it, where nothing more can be played
+    pub fn is_board_dead(&self, layer: usize) -> bool {
+        self.notakto.is_some()
+            && self
+                .lines()
+                .iter()
+                .any(|line| line.start.layer == layer && self.cells.owner(line.mask).is_some())
     }
 
-    pub fn is_computers_turn(&self) -> bool {
-        let next_player = self.player_for_id(self.next_up);
+    // Blocks this many empty cells at random. Cells are reset by changing the board's size or
+    // shape, so this comes after those.
+    pub fn with_blocked_cells(self, count: usize) -> Self {
+        self.with_blocked_cells_from(count, &mut ThreadRandom)
+    }
 
-        match next_player {
-            Player::Computer(_) => true,
-            Player::Human(_) => false,
+    pub fn with_blocked_cells_from(mut self, count: usize, random: &mut dyn RandomSource) -> Self {
+        for _ in 0..count {
+            let empty_positions = self.empty_positions();
+            let Some(&position) = empty_positions.get(random.next_index(empty_positions.len()))
+            else {
+                break;
+            };
+            _ = self.set_cell_at_position(CellState::Blocked, position);
         }
+
+        self
     }
 
-    fn determine_winner(&self) -> Option<&Piece> {
-        for row in self.get_rows() {
-            if let Some(piece) = Self::determine_winner_of_line(&row) {
-                return Some(piece);
-            }
-        }
+    pub fn blocked_positions(&self) -> Vec<usize> {
+        self.positions()
+            .filter(|&position| self.get_cell_at_position(position) == Some(&CellState::Blocked))
+            .collect()
+    }
 
-        for column in self.get_columns() {
-            if let Some(piece) = Self::determine_winner_of_line(&column) {
-                return Some(piece);
-            }
-        }
+    pub fn with_morris(self, morris: bool) -> Self {
+        Self { morris, ..self }
+    }
 
-        for diagonal in self.get_diagonals() {
-            if let Some(piece) = Self::determine_winner_of_line(&diagonal) {
-                return Some(piece);
-            }
-        }
+    pub const fn is_morris(&self) -> bool {
+        self.morris
+    }
 
-        None
+    // Whether the next player has to slide a piece rather than place one
+    pub fn must_slide(&self) -> bool {
+        let piece = *self.player_for_id(self.next_up).piece();
+
+        self.morris && self.pieces_placed(piece) >= Self::MORRIS_PIECES
     }
 
-    pub fn determine_winning_player(&self) -> Option<&Player> {
-        let winning_piece = self.determine_winner()?;
+    fn pieces_placed(&self, piece: Piece) -> usize {
+        self.cells.pieces(piece).count_ones() as usize
+    }
+
+    // The empty cells next to a position along a row or column, or along a diagonal through the
+    // center, which is where a piece there can slide to
+    pub fn slide_destinations(&self, from: usize) -> Vec<usize> {
+        let Some(start) = self.coordinate_at_position(from) else {
+            return Vec::new();
+        };
+        let last = self.size() - 1;
+        let on_diagonal = |coordinate: &Coordinate| coordinate.row() == coordinate.col();
+        let on_anti_diagonal =
+            |coordinate: &Coordinate| coordinate.row() + coordinate.col() == last;
+
+        self.get_available_positions()
+            .into_iter()
+            .filter(|&to| {
+                let Some(end) = self.coordinate_at_position(to) else {
+                    return false;
+                };
+
+                match (
+                    start.row().abs_diff(end.row()),
+                    start.col().abs_diff(end.col()),
+                ) {
+                    (0, 1) | (1, 0) => true,
+                    (1, 1) => {
+                        (on_diagonal(&start) && on_diagonal(&end))
+                            || (on_anti_diagonal(&start) && on_anti_diagonal(&end))
+                    }
+                    _ => false,
+                }
+            })
+            .collect()
+    }
 
-        if self.player_1.piece() == winning_piece {
-            Some(&self.player_1)
+    pub fn layers(&self) -> usize {
+        if self.cube {
+            self.size()
         } else {
-            Some(&self.player_2)
+            self.notakto.unwrap_or(1)
         }
     }
 
-    const fn coordinate_at_position(position: usize) -> Option<Coordinate> {
-        match position {
-            1 => Some(Coordinate::TOP_LEFT),
-            3 => Some(Coordinate::TOP_RIGHT),
-            4 => Some(Coordinate::MIDDLE_LEFT),
-            2 => Some(Coordinate::TOP_CENTER),
-            5 => Some(Coordinate::MIDDLE_CENTER),
-            6 => Some(Coordinate::MIDDLE_RIGHT),
-            7 => Some(Coordinate::BOTTOM_LEFT),
-            8 => Some(Coordinate::BOTTOM_CENTER),
-            9 => Some(Coordinate::BOTTOM_RIGHT),
-            _ => None,
-        }
+    // A win length for a board of the given size
+    pub fn parse_win_length(text: &str, size: usize) -> Result<usize, String> {
+        text.parse()
+            .ok()
+            .filter(|win_length| (Self::CLASSIC_SIZE..=size).contains(win_length))
+            .ok_or_else(|| {
+                format!(
+                    "Win length must be from {} to the board size {size}, got '{text}'",
+                    Self::CLASSIC_SIZE
+                )
+            })
     }
 
-    pub fn get_available_positions(&self) -> Vec<usize> {
-        let mut available_positions: Vec<usize> = Vec::new();
+    pub fn with_hint_budget(self, hint_budget: usize) -> Self {
+        Self {
+            hint_budget: Some(hint_budget),
+            ..self
+        }
+    }
 
-        Self::POSITIONS.for_each(|position| {
-            if let Some(cell) = self.get_cell_at_position(position) {
-                if *cell == CellState::Empty {
-                    available_positions.push(position);
-                }
-            }
-        });
+    // None when there is no limit
+    pub fn hints_left(&self) -> Option<usize> {
+        self.hint_budget
+            .map(|budget| budget.saturating_sub(self.hint_turns.len()))
+    }
 
-        available_positions
+    pub fn hints_used(&self) -> usize {
+        self.hint_turns.len()
     }
 
-    pub fn is_board_full(&self) -> bool {
-        self.get_available_positions().is_empty()
+    // Spends a hint on the coming turn. Frontends only show the hint once this succeeds.
+    pub fn take_hint(&mut self) -> Result<(), GameError> {
+        if self.hints_left() == Some(0) {
+            return Err(GameError::NoHintsLeft);
+        }
+
+        self.hint_turns.push(self.history.len() + 1);
+        Ok(())
     }
 
-    pub fn get_random_available_position(&self) -> Option<usize> {
-        let available_positions = self.get_available_positions();
+    pub fn win_length(&self) -> usize {
+        self.win_length.unwrap_or_else(|| self.size())
+    }
 
-        if available_positions.is_empty() {
-            return None;
-        };
+    pub fn parse_notakto_boards(text: &str) -> Result<usize, String> {
+        text.parse()
+            .ok()
+            .filter(|boards| Self::NOTAKTO_BOARDS.contains(boards))
+            .ok_or_else(|| {
+                format!(
+                    "Notakto is played on {} to {} boards, got '{text}'",
+                    Self::NOTAKTO_BOARDS.start(),
+                    Self::NOTAKTO_BOARDS.end()
+                )
+            })
+    }
 
-        available_positions
-            .get(rand::thread_rng().gen_range(0..available_positions.len()))
-            .copied()
+    pub fn parse_blocked_cells(text: &str) -> Result<usize, String> {
+        text.parse()
+            .ok()
+            .filter(|count| Self::BLOCKED_CELLS.contains(count))
+            .ok_or_else(|| {
+                format!(
+                    "Between {} and {} cells can be blocked, got '{text}'",
+                    Self::BLOCKED_CELLS.start(),
+                    Self::BLOCKED_CELLS.end()
+                )
+            })
     }
 
-    pub fn game_state(&self) -> GameState<'_> {
-        self.determine_winning_player().map_or_else(
-            || {
-                if self.is_board_full() {
-                    GameState::Draw
-                } else {
-                    GameState::InProgress
-                }
-            },
-            GameState::Winner,
-        )
+    pub fn parse_size(text: &str) -> Result<usize, String> {
+        text.parse()
+            .ok()
+            .filter(|size| Self::SIZES.contains(size))
+            .ok_or_else(|| {
+                format!(
+                    "Board size must be from {} to {}, got '{text}'",
+                    Self::SIZES.start(),
+                    Self::SIZES.end()
+                )
+            })
     }
 
-    pub fn is_game_over(&self) -> bool {
-        self.determine_winning_player().is_some() || self.is_board_full()
+    // The width of the board, which is the same as its height and for a cube its depth
+    pub const fn size(&self) -> usize {
+        self.cells.size()
     }
 
-    fn determine_winner_of_line<'a>(line: &[&'a CellState]) -> Option<&'a Piece> {
-        // Lifetimes required here to guarantee that the outgoing type (`Piece`) doesn't
-        // outlive the incoming type that it is tied to `CellState`.
+    // Every position on the board, top-left first, then on through each layer of a cube
+    pub fn positions(&self) -> RangeInclusive<usize> {
+        1..=self.layers() * self.size() * self.size()
+    }
 
-        let first_piece = match &line[0] {
-            CellState::Occupied(piece) => Some(piece),
-            CellState::Empty => None,
+    // Boards read from elsewhere, such as a saved game, may have rows of the wrong length, a win
+    // length that does not fit, two players with the same piece or moves off the board
+    #[cfg(feature = "serde")]
+    pub fn is_well_formed(&self) -> bool {
+        let positions = self.positions();
+        let on_board = |record: &MoveRecord| {
+            positions.contains(&record.position)
+                && record.from.is_none_or(|from| positions.contains(&from))
         };
 
-        let distinguished_piece = first_piece?; // returns None from the function if first_piece doesn't exist
+        self.cells.layers() == self.layers()
+            && (Self::CLASSIC_SIZE..=self.size()).contains(&self.win_length())
+            && self.player_1.piece() != self.player_2.piece()
+            && self.history.iter().chain(&self.undone).all(on_board)
+    }
 
-        if line
-            .iter()
-            .filter(
-                |&cell| matches!(cell, CellState::Occupied(piece) if piece == distinguished_piece),
-            )
-            .count()
-            == line.len()
-        {
-            Some(distinguished_piece)
-        } else {
-            None
+    pub fn new_with_first_up(first_up_player: Player) -> Self {
+        let other_player = match first_up_player {
+            Player::Computer(piece) => Player::Human(Self::other_piece(piece)),
+            Player::Human(piece) => Player::Computer(Self::other_piece(piece)),
+        };
+
+        Self {
+            player_1: first_up_player,
+            player_2: other_player,
+            next_up: PlayerID::Player1,
+            cells: Bitboard::empty(Self::CLASSIC_SIZE, 1),
+            win_length: None,
+            misere: false,
+            wild: false,
+            cube: false,
+            morris: false,
+            gravity: false,
+            notakto: None,
+            names: [None, None],
+            history: Vec::new(),
+            undone: Vec::new(),
+            hint_budget: None,
+            hint_turns: Vec::new(),
         }
     }
 
-    fn get_rows(&self) -> [[&CellState; 3]; 3] {
-        std::array::from_fn(|row| std::array::from_fn(|col| &self.cells[row][col]))
+    // The computer plays both sides, X moves first
+    pub fn new_computer_vs_computer() -> Self {
+        let mut game_board = Self::new_with_first_up(Player::Computer(Piece::X));
+        game_board.swap_player(PlayerID::Player2);
+        game_board
     }
 
-    fn get_columns(&self) -> [[&CellState; 3]; 3] {
-        std::array::from_fn(|col| std::array::from_fn(|row| &self.cells[row][col]))
+    // Two people sharing the frontend, X moves first
+    pub fn new_human_vs_human() -> Self {
+        let mut game_board = Self::new_with_first_up(Player::Human(Piece::X));
+        game_board.swap_player(PlayerID::Player2);
+        game_board
     }
 
-    fn get_diagonals(&self) -> [[&CellState; 3]; 2] {
-        let main_diagonal = std::array::from_fn(|i| &self.cells[i][i]);
-        let anti_diagonal = std::array::from_fn(|i| &self.cells[i][2 - i]);
-
-        [main_diagonal, anti_diagonal]
+    // The name chosen for this player at game start, if any
+    pub fn name_of(&self, player: &Player) -> Option<&str> {
+        let index = usize::from(player != &self.player_1);
+        self.names[index].as_deref()
     }
 
-    fn random_player_from(player_1: PlayerID, player_2: PlayerID) -> PlayerID {
-        if Self::flip_coin() == Coin::Heads {
-            player_1
-        } else {
-            player_2
+    // How the player is referred to in results: their name, else their piece when two people
+    // share the board, else "You" or "Computer"
+    pub fn display_name(&self, player: &Player) -> String {
+        match (player, self.name_of(player)) {
+            (_, Some(name)) => name.to_string(),
+            // Both place X in Notakto, so their pieces say nothing about who is who
+            (Player::Human(_), None) if self.is_human_vs_human() && self.is_notakto() => {
+                let number = if player == &self.player_1 { 1 } else { 2 };
+                format!("Player {number}")
+            }
+            (Player::Human(piece), None) if self.is_human_vs_human() => piece.to_string(),
+            (Player::Human(_), None) => "You".to_string(),
+            (Player::Computer(_), None) => "Computer".to_string(),
         }
     }
 
-    fn random_piece() -> Piece {
-        if Self::flip_coin() == Coin::Heads {
-            Piece::O
+    pub fn is_human_vs_human(&self) -> bool {
+        matches!(
+            (&self.player_1, &self.player_2),
+            (Player::Human(_), Player::Human(_))
+        )
+    }
+
+    pub fn player_for_id(&self, player_id: PlayerID) -> &Player {
+        if player_id == PlayerID::Player1 {
+            &self.player_1
         } else {
-            Piece::X
+            &self.player_2
         }
     }
 
-    fn other_piece(piece: Piece) -> Piece {
-        if piece == Piece::O {
-            Piece::X
-        } else {
-            Piece::O
+    pub fn swap_player(&mut self, player_id: PlayerID) {
+        // Only who is in control of the piece changes, the board is left untouched
+        match player_id {
+            PlayerID::Player1 => self.player_1 = self.player_1.swapped(),
+            PlayerID::Player2 => self.player_2 = self.player_2.swapped(),
         }
     }
 
-    fn flip_coin() -> Coin {
-        let mut random_number_generator = rand::thread_rng();
-        let zero_or_one: u8 = random_number_generator.gen_range(0..=1);
+    pub fn history(&self) -> &[MoveRecord] {
+        &self.history
+    }
+
+    // Takes back the last move, it can be played again with redo until a new move is made
+    pub fn undo(&mut self) -> Option<MoveRecord> {
+        let record = self.history.pop()?;
 
-        if zero_or_one == 0 {
-            Coin::Heads
-        } else {
-            Coin::Tails
+        self.set_cell_at_position(CellState::Empty, record.position)
+            .ok()?;
+        if let Some(from) = record.from {
+            self.set_cell_at_position(CellState::Occupied(self.piece_played(&record)), from)
+                .ok()?;
         }
+        self.next_up = record.player;
+        self.undone.push(record);
+
+        Some(record)
     }
-}
 
-impl fmt::Display for GameBoard {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    pub fn redo(&mut self) -> Option<MoveRecord> {
+        let record = self.undone.pop()?;
+        self.place_next_up(Move {
+            position: record.position,
+            piece: self.piece_played(&record),
+            from: record.from,
+        })
+        .ok()?;
+
+        Some(record)
+    }
+
+    pub fn piece_played(&self, record: &MoveRecord) -> Piece {
+        record
+            .piece
+            .unwrap_or_else(|| *self.player_for_id(record.player).piece())
+    }
+
+    // The piece the next player places: their own, except in Notakto where everyone places X
+    pub fn next_piece(&self) -> Piece {
+        if self.notakto.is_some() {
+            Piece::X
+        } else {
+            *self.player_for_id(self.next_up).piece()
+        }
+    }
+
+    // The next player's own piece placed at the position
+    pub fn own_move(&self, position: usize) -> Move {
+        Move::place(position, self.next_piece())
+    }
+
+    // Every empty cell with each piece the next player may place there, or once Three Men's Morris
+    // pieces slide, every slide of theirs
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.must_slide() {
+            let piece = *self.player_for_id(self.next_up).piece();
+
+            return self
+                .positions()
+                .filter(|&from| {
+                    self.get_cell_at_position(from) == Some(&CellState::Occupied(piece))
+                })
+                .flat_map(|from| {
+                    self.slide_destinations(from)
+                        .into_iter()
+                        .map(move |position| Move::slide(from, position, piece))
+                })
+                .collect();
+        }
+
+        let pieces = if self.wild {
+            vec![Piece::X, Piece::O]
+        } else {
+            vec![self.next_piece()]
+        };
+
+        self.get_available_positions()
+            .into_iter()
+            .flat_map(|position| {
+                pieces
+                    .iter()
+                    .map(move |&piece| Move::place(position, piece))
+            })
+            .collect()
+    }
+
+    // Whether the next player could play here right now
+    pub fn is_legal_move(&self, position: usize) -> bool {
+        self.get_available_positions().contains(&position) && !self.is_game_over()
+    }
+
+    pub fn get_cell_at_position(&self, position: usize) -> Option<&CellState> {
+        self.positions()
+            .contains(&position)
+            .then(|| self.cells.cell(position - 1))
+    }
+
+    fn set_cell_at_position(&mut self, cell: CellState, position: usize) -> Result<(), GameError> {
+        if !self.positions().contains(&position) {
+            return Err(GameError::InvalidPosition(position));
+        }
+
+        self.cells.set(position - 1, cell);
+
+        Ok(())
+    }
+
+    pub fn play_next_up_at_position(&mut self, position: usize) -> Result<(), GameError> {
+        self.play_next_up(self.own_move(position))
+    }
+
+    pub fn play_next_up(&mut self, chosen: Move) -> Result<(), GameError> {
+        self.place_next_up(chosen)?;
+        self.undone.clear();
+
+        Ok(())
+    }
+
+    fn place_next_up(&mut self, chosen: Move) -> Result<(), GameError> {
+        let Move {
+            position,
+            piece,
+            from,
+        } = chosen;
+        let Some(cell) = self.get_cell_at_position(position) else {
+            return Err(GameError::InvalidPosition(position));
+        };
+
+        match cell {
+            CellState::Occupied(_) => return Err(GameError::CellOccupied(position)),
+            CellState::Blocked => return Err(GameError::CellBlocked(position)),
+            CellState::Empty => (),
+        }
+
+        if self.gravity && !self.get_available_positions().contains(&position) {
+            return Err(GameError::NotLowestInColumn(position));
+        }
+
+        if self.is_game_over() {
+            return Err(GameError::GameOver);
+        }
+
+        if let Some(layer) = self
+            .coordinate_at_position(position)
+            .map(|coordinate| coordinate.layer())
+            .filter(|&layer| self.is_board_dead(layer))
+        {
+            return Err(GameError::BoardDead(layer + 1));
+        }
+
+        if !self.wild && piece != self.next_piece() {
+            return Err(GameError::NotYourPiece(piece));
+        }
+
+        match (from, self.must_slide()) {
+            (None, true) => return Err(GameError::MustSlide),
+            (Some(_), false) => return Err(GameError::CannotSlide),
+            (Some(from), true) => {
+                if self.get_cell_at_position(from) != Some(&CellState::Occupied(piece)) {
+                    return Err(GameError::NothingToSlide(from));
+                }
+                if !self.slide_destinations(from).contains(&position) {
+                    return Err(GameError::NotAdjacent(from, position));
+                }
+                self.set_cell_at_position(CellState::Empty, from)?;
+            }
+            (None, false) => (),
+        }
+
+        self.set_cell_at_position(CellState::Occupied(piece), position)?;
+        self.history.push(MoveRecord {
+            player: self.next_up,
+            position,
+            turn: self.history.len() + 1,
+            piece: (self.wild || self.notakto.is_some()).then_some(piece),
+            from,
+        });
+
+        self.next_up = self.next_up.other();
+
+        Ok(())
+    }
+
+    pub fn is_computers_turn(&self) -> bool {
+        let next_player = self.player_for_id(self.next_up);
+
+        match next_player {
+            Player::Computer(_) => true,
+            Player::Human(_) => false,
+        }
+    }
+
+    fn determine_winner(&self) -> Option<Piece> {
+        // Notakto carries on until every board has a line
+        if self.notakto.is_some() && !(0..self.layers()).all(|layer| self.is_board_dead(layer)) {
+            return None;
+        }
+
+        self.lines()
+            .iter()
+            .find_map(|line| self.cells.owner(line.mask))
+    }
+
+    // Whoever completed a line. In wild games and Notakto that is whoever moved last, whatever the
+    // piece.
+    fn line_completer(&self) -> Option<PlayerID> {
+        let line_piece = self.determine_winner()?;
+
+        if self.wild || self.notakto.is_some() {
+            return self.history.last().map(|record| record.player);
+        }
+
+        if *self.player_1.piece() == line_piece {
+            Some(PlayerID::Player1)
+        } else {
+            Some(PlayerID::Player2)
+        }
+    }
+
+    pub fn determine_winning_player(&self) -> Option<&Player> {
+        let completer = self.line_completer()?;
+        // Under misère rules completing a line loses
+        let winner = if self.is_misere() {
+            completer.other()
+        } else {
+            completer
+        };
+
+        Some(self.player_for_id(winner))
+    }
+
+    pub fn coordinate_at_position(&self, position: usize) -> Option<Coordinate> {
+        if !self.positions().contains(&position) {
+            return None;
+        }
+
+        let size = self.size();
+        let index = position - 1;
+
+        Some(Coordinate {
+            layer: index / (size * size),
+            row: index / size % size,
+            col: index % size,
+        })
+    }
+
+    fn position_at_coordinate(&self, row: usize, col: usize) -> usize {
+        row * self.size() + col + 1
+    }
+
+    // The empty cells a piece can go in, under gravity only those at the bottom of their column
+    // and in Notakto only those on boards still in play
+    pub fn get_available_positions(&self) -> Vec<usize> {
+        let mut available_positions = self.empty_positions();
+
+        if self.notakto.is_some() {
+            let dead_layers: Vec<usize> = (0..self.layers())
+                .filter(|&layer| self.is_board_dead(layer))
+                .collect();
+            available_positions.retain(|&position| {
+                self.coordinate_at_position(position)
+                    .is_some_and(|coordinate| !dead_layers.contains(&coordinate.layer()))
+            });
+        }
+
+        if self.gravity {
+            available_positions.retain(|&position| {
+                self.coordinate_at_position(position)
+                    .is_some_and(|coordinate| {
+                        self.drop_position(coordinate.col() + 1) == Ok(position)
+                    })
+            });
+        }
+
+        available_positions
+    }
+
+    // Each layer is turned and flipped alike. Under gravity the board is only mirrored left to
+    // right, as pieces still have to fall down it.
+    pub fn canonical_form(&self) -> CanonicalForm {
+        let last = self.size().saturating_sub(1);
+        let symmetries = if self.gravity {
+            &SYMMETRIES[..1]
+        } else {
+            &SYMMETRIES[..]
+        };
+
+        CanonicalForm(
+            symmetries
+                .iter()
+                .map(|symmetry| self.cells.moved(|row, col| symmetry(last, row, col)))
+                .fold(self.cells.clone(), Ord::min),
+        )
+    }
+
+    pub fn empty_positions(&self) -> Vec<usize> {
+        let mut empty_positions: Vec<usize> = Vec::new();
+
+        self.positions().for_each(|position| {
+            if let Some(cell) = self.get_cell_at_position(position) {
+                if *cell == CellState::Empty {
+                    empty_positions.push(position);
+                }
+            }
+        });
+
+        empty_positions
+    }
+
+    pub fn is_board_full(&self) -> bool {
+        self.get_available_positions().is_empty()
+    }
+
+    // Nobody can play on: the board is full, or in Three Men's Morris the turn limit is reached or
+    // the player to move is boxed in
+    fn is_out_of_moves(&self) -> bool {
+        if self.morris {
+            self.history.len() >= Self::MORRIS_TURN_LIMIT || self.legal_moves().is_empty()
+        } else {
+            self.is_board_full()
+        }
+    }
+
+    pub fn get_random_available_position(&self) -> Option<usize> {
+        self.random_available_position_from(&mut ThreadRandom)
+    }
+
+    pub fn random_available_position_from(&self, random: &mut dyn RandomSource) -> Option<usize> {
+        let available_positions = self.get_available_positions();
+
+        if available_positions.is_empty() {
+            return None;
+        };
+
+        available_positions
+            .get(random.next_index(available_positions.len()))
+            .copied()
+    }
+
+    pub fn game_state(&self) -> GameState {
+        match (self.determine_winning_player(), self.winning_line()) {
+            (Some(player), Some(line)) => GameState::Winner(player.clone(), line),
+            _ if self.is_out_of_moves() => GameState::Draw,
+            _ => GameState::InProgress,
+        }
+    }
+
+    // How the game was won, to follow the winner's name
+    pub fn how_won(&self, line: &WinningLine) -> String {
+        // With several Notakto boards, the one finished off last
+        let board = line
+            .positions
+            .first()
+            .and_then(|&position| self.coordinate_at_position(position))
+            .filter(|_| self.notakto.is_some() && self.layers() > 1)
+            .map_or_else(String::new, |coordinate| {
+                format!(" of board {}", coordinate.layer() + 1)
+            });
+
+        match self.line_completer() {
+            Some(completer) if self.is_notakto() => {
+                let player = self.player_for_id(completer);
+                let who = match (player, self.name_of(player)) {
+                    (Player::Human(_), None) if !self.is_human_vs_human() => "you".to_string(),
+                    _ => self.display_name(player),
+                };
+                format!("as {who} completed the {line}{board}")
+            }
+            Some(completer) if self.is_misere() => format!(
+                "as {} completed the {line}{board}",
+                self.player_for_id(completer).piece()
+            ),
+            _ => format!("on the {line}"),
+        }
+    }
+
+    pub fn winning_line(&self) -> Option<WinningLine> {
+        // In Notakto the line on the board the last move finished off
+        let last_layer = self
+            .history
+            .last()
+            .filter(|_| self.notakto.is_some())
+            .and_then(|record| self.coordinate_at_position(record.position))
+            .map(|coordinate| coordinate.layer());
+        let start = self
+            .lines()
+            .iter()
+            .find(|line| {
+                last_layer.is_none_or(|layer| line.start.layer == layer)
+                    && self.cells.owner(line.mask).is_some()
+            })?
+            .start;
+
+        Some(WinningLine {
+            kind: start.kind,
+            positions: self
+                .line_coordinates(start)
+                .map(|(row, col)| self.position_at_coordinate(row, col))
+                .collect(),
+            board_size: self.size(),
+            layer: (self.cube && start.step[0] == 0).then_some(start.layer),
+        })
+    }
+
+    // Every run of win_length cells: along the rows, then the columns, then both diagonals, then
+    // on a cube the lines through the layers
+    fn line_starts(&self) -> impl Iterator<Item = LineStart> {
+        let size = self.size();
+        let layers = self.layers();
+        let win_length = self.win_length();
+        let steps = if self.cube {
+            LINE_STEPS.len()
+        } else {
+            FLAT_LINE_STEPS
+        };
+        // Where a line fits along one direction, given which way it runs along it. Lines running
+        // backwards start at the far end.
+        let starts = move |step: isize, extent: usize| -> Range<usize> {
+            match step {
+                0 => 0..extent,
+                1 => 0..extent + 1 - win_length,
+                _ => win_length - 1..extent,
+            }
+        };
+
+        LINE_STEPS.into_iter().take(steps).flat_map(move |step| {
+            starts(step[0], layers).flat_map(move |layer| {
+                starts(step[1], size).flat_map(move |row| {
+                    starts(step[2], size).map(move |col| LineStart {
+                        kind: LineKind::of(step, row, col),
+                        layer,
+                        row,
+                        col,
+                        step,
+                    })
+                })
+            })
+        })
+    }
+
+    // The (row, column) of each cell in the line, with rows counting on through the layers
+    fn line_coordinates(&self, start: LineStart) -> impl Iterator<Item = (usize, usize)> {
+        let LineStart {
+            layer,
+            row,
+            col,
+            step,
+            ..
+        } = start;
+        let size = self.size();
+        let along = |from: usize, step: isize, index: usize| match step {
+            0 => from,
+            1 => from + index,
+            _ => from - index,
+        };
+
+        (0..self.win_length()).map(move |index| {
+            (
+                along(layer, step[0], index) * size + along(row, step[1], index),
+                along(col, step[2], index),
+            )
+        })
+    }
+
+    fn lines(&self) -> Rc<[Line]> {
+        let shape = (self.size(), self.layers(), self.win_length(), self.cube);
+
+        LINES.with(|lines| {
+            Rc::clone(lines.borrow_mut().entry(shape).or_insert_with(|| {
+                self.line_starts()
+                    .map(|start| Line {
+                        start,
+                        mask: self
+                            .line_coordinates(start)
+                            .fold(0, |mask, (row, col)| mask | 1 << (row * self.size() + col)),
+                    })
+                    .collect()
+            }))
+        })
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.determine_winning_player().is_some() || self.is_out_of_moves()
+    }
+
+    fn random_piece(random: &mut dyn RandomSource) -> Piece {
+        if Self::flip_coin(random) == Coin::Heads {
+            Piece::O
+        } else {
+            Piece::X
+        }
+    }
+
+    pub fn other_piece(piece: Piece) -> Piece {
+        if piece == Piece::O {
+            Piece::X
+        } else {
+            Piece::O
+        }
+    }
+
+    fn flip_coin(random: &mut dyn RandomSource) -> Coin {
+        if random.next_index(2) == 0 {
+            Coin::Heads
+        } else {
+            Coin::Tails
+        }
+    }
+}
+
+impl GameBoard {
+    // Text for every cell, a row to a line. The layers of a cube and Notakto boards sit side by
+    // side, each under its number.
+    pub fn layout_cells(&self, cell_text: impl Fn(usize) -> String) -> String {
+        let size = self.size();
+        let layers: Vec<Vec<String>> = (0..self.layers())
+            .map(|layer| {
+                (0..size)
+                    .map(|row| {
+                        let first = (layer * size + row) * size + 1;
+                        (first..first + size).map(&cell_text).collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
         let mut output = String::new();
+        if self.layers() > 1 {
+            let headings: Vec<String> = layers
+                .iter()
+                .enumerate()
+                .map(|(layer, rows)| {
+                    let width = rows.iter().map(|row| visible_width(row)).max();
+                    format!(
+                        "{:<width$}",
+                        self.layer_heading(layer),
+                        width = width.unwrap_or(0)
+                    )
+                })
+                .collect();
+            output.push_str(headings.join(" ").trim_end());
+            output.push('\n');
+        }
+
+        for row in 0..size {
+            let line: Vec<&str> = layers.iter().map(|rows| rows[row].as_str()).collect();
+            output.push_str(&line.join(" "));
+            output.push('\n');
+        }
+
+        output.pop();
+        output
+    }
+}
+
+impl GameBoard {
+    fn layer_heading(&self, layer: usize) -> String {
+        if self.cube {
+            format!("Layer {}", layer + 1)
+        } else if self.is_board_dead(layer) {
+            format!("Board {} dead", layer + 1)
+        } else {
+            format!("Board {}", layer + 1)
+        }
+    }
+}
+
+// A human vs computer game with everything left to chance
+impl Default for GameBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The columns text takes up on a terminal, leaving out the escape codes that color it
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+
+    for c in text.chars() {
+        match c {
+            '\x1b' => in_escape = true,
+            _ if in_escape => in_escape = !c.is_ascii_alphabetic(),
+            _ => width += 1,
+        }
+    }
+
+    width
+}
+
+impl fmt::Display for GameBoard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let output = self.layout_cells(|position| match self.get_cell_at_position(position) {
+            Some(CellState::Occupied(piece)) => format!("[{}] ", piece.name()),
+            Some(CellState::Blocked) => "[#] ".to_string(),
+            _ => "[ ] ".to_string(),
+        });
+
+        write!(f, "{output}")
+    }
+}
+
+pub fn threats(game_board: &GameBoard) -> Vec<(usize, Piece)> {
+    // A threat is an empty cell where a piece would complete a line on the next move
+    let mut threats = Vec::new();
+
+    // Notakto only has lines of X, and any of them finishes off its board
+    if game_board.is_notakto() {
+        return game_board
+            .get_available_positions()
+            .into_iter()
+            .filter(|&position| {
+                let mut trial_board = game_board.clone();
+                trial_board
+                    .set_cell_at_position(CellState::Occupied(Piece::X), position)
+                    .is_ok()
+                    && game_board
+                        .coordinate_at_position(position)
+                        .is_some_and(|coordinate| trial_board.is_board_dead(coordinate.layer()))
+            })
+            .map(|position| (position, Piece::X))
+            .collect();
+    }
+
+    // Three Men's Morris pieces may have to slide there, which only their own player can do
+    if game_board.is_morris() {
+        for player_id in [PlayerID::Player1, PlayerID::Player2] {
+            let mut player_board = game_board.clone();
+            player_board.next_up = player_id;
+            let piece = *player_board.player_for_id(player_id).piece();
+
+            for chosen in player_board.legal_moves() {
+                let mut trial_board = player_board.clone();
+                if trial_board.place_next_up(chosen).is_ok()
+                    && trial_board.determine_winner() == Some(piece)
+                    && !threats.contains(&(chosen.position, piece))
+                {
+                    threats.push((chosen.position, piece));
+                }
+            }
+        }
+
+        threats.sort_by_key(|&(position, piece)| (position, piece == Piece::O));
+        return threats;
+    }
+
+    for position in game_board.get_available_positions() {
+        for piece in [Piece::X, Piece::O] {
+            let mut trial_board = game_board.clone();
+
+            if trial_board
+                .set_cell_at_position(CellState::Occupied(piece), position)
+                .is_ok()
+                && trial_board.determine_winner() == Some(piece)
+            {
+                threats.push((position, piece));
+            }
+        }
+    }
+
+    threats
+}
+
+// Returns the move played, frontends announce it in their own numbering
+pub fn execute_computer_turn(
+    game_board: &mut GameBoard,
+    strategy: &dyn MoveStrategy,
+) -> Result<Move, GameError> {
+    choose_computer_move(game_board, strategy).map_or_else(
+        || Err(GameError::NoAvailablePositions),
+        |chosen| game_board.play_next_up(chosen).map(|()| chosen),
+    )
+}
+
+// The strategy's pick for the next player, along with the piece to place in wild games and where
+// it slides from in Three Men's Morris
+pub fn choose_computer_move(game_board: &GameBoard, strategy: &dyn MoveStrategy) -> Option<Move> {
+    if game_board.is_wild() || game_board.is_morris() {
+        strategy.choose_full_move(game_board)
+    } else {
+        strategy
+            .choose_move(game_board)
+            .map(|position| game_board.own_move(position))
+    }
+}
+
+// Undoes moves back to the last one a human made, so the computer does not simply replay its
+// reply. Returns the moves undone, the most recent first.
+pub fn undo_turn(game_board: &mut GameBoard) -> Vec<MoveRecord> {
+    let mut undone: Vec<MoveRecord> = game_board.undo().into_iter().collect();
+
+    while game_board.is_computers_turn() {
+        let Some(record) = game_board.undo() else {
+            break;
+        };
+        undone.push(record);
+    }
+
+    undone
+}
+
+// Redoes the human move undone last along with the computer replies that followed it
+pub fn redo_turn(game_board: &mut GameBoard) -> Vec<MoveRecord> {
+    let mut redone: Vec<MoveRecord> = game_board.redo().into_iter().collect();
+
+    while game_board.is_computers_turn() {
+        let Some(record) = game_board.redo() else {
+            break;
+        };
+        redone.push(record);
+    }
+
+    redone
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ScoreEntry {
+    pub player: String,
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+}
+
+// Results per player over a session of games, players listed in the order they first played
+#[derive(Debug, Default, Clone)]
+pub struct Scoreboard {
+    games: usize,
+    entries: Vec<ScoreEntry>,
+}
+
+impl Scoreboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub const fn games(&self) -> usize {
+        self.games
+    }
+
+    pub fn entries(&self) -> &[ScoreEntry] {
+        &self.entries
+    }
+
+    // Unfinished games are not counted
+    pub fn record(&mut self, game_board: &GameBoard) {
+        self.tally(game_board, |count| *count += 1);
+    }
+
+    // Takes a finished game back off the board, such as when its last move is undone
+    pub fn retract(&mut self, game_board: &GameBoard) {
+        self.tally(game_board, |count| *count = count.saturating_sub(1));
+    }
+
+    fn tally(&mut self, game_board: &GameBoard, change: impl Fn(&mut usize)) {
+        let state = game_board.game_state();
+        if matches!(state, GameState::InProgress) {
+            return;
+        }
+        change(&mut self.games);
+
+        for player in [&game_board.player_1, &game_board.player_2] {
+            let name = game_board.display_name(player);
+            let index = self
+                .entries
+                .iter()
+                .position(|entry| entry.player == name)
+                .unwrap_or_else(|| {
+                    self.entries.push(ScoreEntry {
+                        player: name,
+                        wins: 0,
+                        losses: 0,
+                        draws: 0,
+                    });
+                    self.entries.len() - 1
+                });
+            let entry = &mut self.entries[index];
+
+            match &state {
+                GameState::Winner(winner, _) if winner == player => change(&mut entry.wins),
+                GameState::Winner(..) => change(&mut entry.losses),
+                GameState::Draw | GameState::InProgress => change(&mut entry.draws),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Scoreboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut table = Table::new(&["Player", "Wins", "Losses", "Draws"]);
+
+        for entry in &self.entries {
+            table.add_row(vec![
+                entry.player.clone(),
+                entry.wins.to_string(),
+                entry.losses.to_string(),
+                entry.draws.to_string(),
+            ]);
+        }
+
+        write!(f, "{table}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_source::ReplayedRandom;
+    use Piece::O;
+
+    #[test]
+    fn test_initial_board_is_empty() {
+        let game_board = GameBoard::new();
+
+        let test_cell_at = |position: usize| {
+            let cell = game_board.get_cell_at_position(position);
+            assert_eq!(cell, Some(&CellState::Empty));
+        };
+
+        game_board.positions().for_each(test_cell_at);
+    }
+
+    #[test]
+    fn test_place_x_on_empty_cell() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Computer(Piece::X));
+        let result = game_board.play_next_up_at_position(1);
+
+        assert!(result.is_ok());
+        assert_eq!(
+            game_board.get_cell_at_position(1),
+            Some(&CellState::Occupied(Piece::X))
+        );
+    }
+
+    #[test]
+    fn test_place_o_on_empty_cell() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Computer(O));
+        let result = game_board.play_next_up_at_position(1);
+
+        assert!(result.is_ok());
+        assert_eq!(
+            game_board.get_cell_at_position(1),
+            Some(&CellState::Occupied(O))
+        );
+    }
+
+    #[test]
+    fn test_invalid_moves_are_reported() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Computer(O));
+
+        assert_eq!(
+            game_board.play_next_up_at_position(10),
+            Err(GameError::InvalidPosition(10))
+        );
+
+        assert!(first_player_top_row_win(&mut game_board).is_ok());
+        assert_eq!(
+            game_board.play_next_up_at_position(9),
+            Err(GameError::GameOver)
+        );
+    }
+
+    #[test]
+    fn test_place_piece_on_occupied_cell() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Computer(O));
+
+        assert!(game_board.play_next_up_at_position(1).is_ok());
+        assert_eq!(
+            game_board.play_next_up_at_position(1),
+            Err(GameError::CellOccupied(1))
+        );
+
+        assert_eq!(
+            game_board.get_cell_at_position(1),
+            Some(&CellState::Occupied(O))
+        );
+    }
+
+    #[test]
+    fn test_empty_board_has_no_winner() {
+        let game_board = GameBoard::new();
+
+        assert_eq!(None, game_board.determine_winner());
+    }
+
+    #[test]
+    fn test_board_with_a_winning_row_has_a_winner() {
+        let mut game_board = GameBoard::new();
+
+        let result = game_board
+            .play_next_up_at_position(1)
+            .and_then(|_| game_board.play_next_up_at_position(4))
+            .and_then(|_| game_board.play_next_up_at_position(2))
+            .and_then(|_| game_board.play_next_up_at_position(7))
+            .and_then(|_| game_board.play_next_up_at_position(3));
+
+        assert!(result.is_ok());
+
+        // row: 1,2,3
+        assert!(game_board.determine_winner().is_some());
+    }
+
+    #[test]
+    fn test_board_with_a_winning_column_has_a_winner() {
+        let mut game_board = GameBoard::new();
+
+        let result = game_board
+            .play_next_up_at_position(1)
+            .and_then(|_| game_board.play_next_up_at_position(2))
+            .and_then(|_| game_board.play_next_up_at_position(4))
+            .and_then(|_| game_board.play_next_up_at_position(9))
+            .and_then(|_| game_board.play_next_up_at_position(7));
+
+        assert!(result.is_ok());
+
+        // column: 1,4,7
+        assert!(game_board.determine_winner().is_some());
+    }
+
+    #[test]
+    fn test_board_with_a_winning_forward_diagonal_has_a_winner() {
+        let mut game_board = GameBoard::new();
+
+        let result = game_board
+            .play_next_up_at_position(1)
+            .and_then(|_| game_board.play_next_up_at_position(2))
+            .and_then(|_| game_board.play_next_up_at_position(5))
+            .and_then(|_| game_board.play_next_up_at_position(7))
+            .and_then(|_| game_board.play_next_up_at_position(9));
+
+        assert!(result.is_ok());
+
+        // diagonal: 1,5,9
+        assert!(game_board.determine_winner().is_some());
+    }
+
+    #[test]
+    fn test_board_with_a_winning_backward_diagonal_has_a_winner() {
+        let mut game_board = GameBoard::new();
+
+        let result = game_board
+            .play_next_up_at_position(3)
+            .and_then(|_| game_board.play_next_up_at_position(2))
+            .and_then(|_| game_board.play_next_up_at_position(5))
+            .and_then(|_| game_board.play_next_up_at_position(1))
+            .and_then(|_| game_board.play_next_up_at_position(7));
+
+        assert!(result.is_ok());
+
+        // diagonal: 3,5,7
+        assert!(game_board.determine_winner().is_some());
+    }
+
+    #[test]
+    fn test_invalid_cell_positions_return_none() {
+        let game_board = GameBoard::new();
+
+        assert_eq!(game_board.get_cell_at_position(0), None);
+
+        assert_eq!(game_board.get_cell_at_position(100), None);
+    }
+
+    #[test]
+    fn test_valid_cell_positions_return_valid_cells() {
+        let game_board = GameBoard::new();
+
+        assert_eq!(
+            game_board.get_cell_at_position(1),
+            Some(get_cell_at_coordinate(&game_board, &Coordinate::TOP_LEFT))
+        );
+        assert_eq!(
+            game_board.get_cell_at_position(2),
+            Some(get_cell_at_coordinate(&game_board, &Coordinate::TOP_CENTER))
+        );
+        assert_eq!(
+            game_board.get_cell_at_position(3),
+            Some(get_cell_at_coordinate(&game_board, &Coordinate::TOP_RIGHT))
+        );
 
-        for row in self.cells {
-            for cell in row {
-                match cell {
-                    CellState::Empty => output.push_str("[ ] "),
-                    CellState::Occupied(piece) => {
-                        let occupied_cell = format!("[{}] ", piece.name());
-                        output.push_str(occupied_cell.as_str());
-                    }
-                }
-            }
-            output.push('\n');
-        }
+        assert_eq!(
+            game_board.get_cell_at_position(4),
+            Some(get_cell_at_coordinate(
+                &game_board,
+                &Coordinate::MIDDLE_LEFT
+            ))
+        );
+        assert_eq!(
+            game_board.get_cell_at_position(5),
+            Some(get_cell_at_coordinate(
+                &game_board,
+                &Coordinate::MIDDLE_CENTER
+            ))
+        );
+        assert_eq!(
+            game_board.get_cell_at_position(6),
+            Some(get_cell_at_coordinate(
+                &game_board,
+                &Coordinate::MIDDLE_RIGHT
+            ))
+        );
 
-        output.pop();
-        write!(f, "{output}")
+        assert_eq!(
+            game_board.get_cell_at_position(7),
+            Some(get_cell_at_coordinate(
+                &game_board,
+                &Coordinate::BOTTOM_LEFT
+            ))
+        );
+        assert_eq!(
+            game_board.get_cell_at_position(8),
+            Some(get_cell_at_coordinate(
+                &game_board,
+                &Coordinate::BOTTOM_CENTER
+            ))
+        );
+        assert_eq!(
+            game_board.get_cell_at_position(9),
+            Some(get_cell_at_coordinate(
+                &game_board,
+                &Coordinate::BOTTOM_RIGHT
+            ))
+        );
     }
-}
 
-pub fn execute_computer_turn(game_board: &mut GameBoard) -> Result<(), String> {
-    game_board.get_random_available_position().map_or_else(
-        || Err("No available positions".to_string()),
-        |position| {
-            // display_spinner_with_message("The computer is thinking...");
-            let piece = game_board.player_for_id(game_board.next_up).piece();
-            println!("\nThe computer played {piece} in position: {position}");
-            game_board.play_next_up_at_position(position)
-        },
-    )
-}
+    #[test]
+    fn test_player_id() {
+        let game_board = GameBoard::new();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use Piece::O;
+        assert_eq!(
+            *game_board.player_for_id(PlayerID::Player1),
+            game_board.player_1
+        );
+        assert_eq!(
+            *game_board.player_for_id(PlayerID::Player2),
+            game_board.player_2
+        );
+    }
 
     #[test]
-    fn test_initial_board_is_empty() {
-        let game_board = GameBoard::new();
+    fn test_determine_winning_player_resolves_to_x_if_x_wins() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Computer(Piece::X));
+        let result = first_player_top_row_win(&mut game_board);
 
-        let test_cell_at = |position: usize| {
-            let cell = game_board.get_cell_at_position(position);
-            assert_eq!(cell, Some(&CellState::Empty));
-        };
+        assert!(result.is_ok());
+
+        assert!(matches!(
+            game_board.determine_winning_player(),
+            Some(&Player::Computer(Piece::X))
+        ));
+    }
+
+    #[test]
+    fn test_determine_winning_player_resolves_to_o_if_o_wins() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Computer(O));
+        let result = first_player_top_row_win(&mut game_board);
+
+        assert!(result.is_ok());
 
-        GameBoard::POSITIONS.for_each(test_cell_at);
+        assert!(matches!(
+            game_board.determine_winning_player(),
+            Some(&Player::Computer(Piece::O))
+        ));
     }
 
     #[test]
-    fn test_place_x_on_empty_cell() {
-        let mut game_board = new_with_first_up(Player::Computer(Piece::X));
+    fn test_determine_winning_player_resolves_to_none_if_there_is_no_winner() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Computer(O));
+
         let result = game_board.play_next_up_at_position(1);
 
         assert!(result.is_ok());
+        assert!(game_board.determine_winning_player().is_none());
+    }
+
+    #[test]
+    fn test_winning_player_can_be_player_2() {
+        let first_up = Player::Computer(O);
+        let _second_up = Player::Human(Piece::X);
+
+        let mut game_board = GameBoard::new_with_first_up(first_up);
+
+        let result = first_player_top_row_win(&mut game_board);
+
+        assert!(result.is_ok());
+        assert!(matches!(
+            game_board.determine_winning_player(),
+            Some(_second_up)
+        ));
+    }
+
+    #[test]
+    fn test_board_is_full() {
+        let mut game_board = GameBoard::new();
+        // Moves stop being accepted once someone wins, so fill the cells directly
+        game_board.positions().for_each(|position| {
+            _ = game_board.set_cell_at_position(CellState::Occupied(Piece::X), position);
+        });
+
+        assert!(game_board.is_board_full());
+    }
+
+    #[test]
+    fn test_empty_board_is_not_full() {
+        let game_board = GameBoard::new();
+
+        assert!(!game_board.is_board_full());
+    }
+
+    #[test]
+    fn test_board_with_less_than_9_positions_is_not_full() {
+        let mut game_board = GameBoard::new();
+
+        let all_positions = game_board.positions();
+        let end_position = all_positions.end();
+        let new_end_position = *end_position - 1;
+        let less_than_all_positions = *all_positions.start()..=new_end_position;
+
+        less_than_all_positions
+            .for_each(|position| _ = game_board.play_next_up_at_position(position));
+
+        assert!(!game_board.is_board_full());
+    }
+
+    #[test]
+    fn test_get_random_available_position_is_a_not_none() {
+        let random_position = GameBoard::new().get_random_available_position();
+
+        assert!(random_position.is_some());
+    }
+
+    #[test]
+    fn test_name_of_human_player() {
+        let human_name = "Human";
+
+        assert_eq!(human_name, Player::Human(Piece::X).name());
+        assert_eq!(human_name, Player::Human(O).name());
+    }
+
+    #[test]
+    fn test_name_of_computer_player() {
+        let computer_name = "Computer";
+
+        assert_eq!(computer_name, Player::Computer(Piece::X).name());
+        assert_eq!(computer_name, Player::Computer(O).name());
+    }
+
+    #[test]
+    fn test_piece_retrieval() {
+        assert_eq!(&Piece::X, Player::Computer(Piece::X).piece());
+        assert_eq!(&O, Player::Computer(O).piece());
+        assert_eq!(&Piece::X, Player::Human(Piece::X).piece());
+        assert_eq!(&O, Player::Human(O).piece());
+    }
+
+    #[test]
+    fn test_swapped_player_keeps_piece() {
         assert_eq!(
-            game_board.get_cell_at_position(1),
-            Some(&CellState::Occupied(Piece::X))
+            Player::Computer(Piece::X),
+            Player::Human(Piece::X).swapped()
         );
+        assert_eq!(Player::Human(O), Player::Computer(O).swapped());
     }
 
     #[test]
-    fn test_place_o_on_empty_cell() {
-        let mut game_board = new_with_first_up(Player::Computer(O));
-        let result = game_board.play_next_up_at_position(1);
+    fn test_swap_player_preserves_board() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Human(Piece::X));
+
+        assert!(game_board.play_next_up_at_position(5).is_ok());
+        let cells_before_swap = game_board.cells.clone();
+
+        game_board.swap_player(PlayerID::Player1);
+
+        assert_eq!(
+            *game_board.player_for_id(PlayerID::Player1),
+            Player::Computer(Piece::X)
+        );
+        assert_eq!(game_board.cells, cells_before_swap);
+        assert_eq!(game_board.next_up, PlayerID::Player2);
+    }
+
+    #[test]
+    fn test_empty_board_has_no_threats() {
+        assert!(threats(&GameBoard::new()).is_empty());
+    }
+
+    #[test]
+    fn test_threats_are_reported_for_both_pieces() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Human(Piece::X));
+
+        // X: 1, 2 threatens 3. O: 4, 5 threatens 6.
+        let result = game_board
+            .play_next_up_at_position(1)
+            .and_then(|_| game_board.play_next_up_at_position(4))
+            .and_then(|_| game_board.play_next_up_at_position(2))
+            .and_then(|_| game_board.play_next_up_at_position(5));
+
+        assert!(result.is_ok());
+        assert_eq!(threats(&game_board), vec![(3, Piece::X), (6, O)]);
+    }
+
+    #[test]
+    fn test_cell_can_be_threatened_by_both_pieces() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Human(Piece::X));
+
+        // X: 1, 4 and O: 8, 9 both threaten 7.
+        let result = game_board
+            .play_next_up_at_position(1)
+            .and_then(|_| game_board.play_next_up_at_position(8))
+            .and_then(|_| game_board.play_next_up_at_position(4))
+            .and_then(|_| game_board.play_next_up_at_position(9));
 
         assert!(result.is_ok());
+        assert_eq!(threats(&game_board), vec![(7, Piece::X), (7, O)]);
+    }
+
+    #[test]
+    fn test_computer_vs_computer_board() {
+        let game_board = GameBoard::new_computer_vs_computer();
+
         assert_eq!(
-            game_board.get_cell_at_position(1),
-            Some(&CellState::Occupied(O))
+            game_board.player_for_id(PlayerID::Player1),
+            &Player::Computer(Piece::X)
         );
+        assert_eq!(
+            game_board.player_for_id(PlayerID::Player2),
+            &Player::Computer(O)
+        );
+        assert!(game_board.is_computers_turn());
     }
 
     #[test]
-    fn test_place_piece_on_occupied_cell() {
-        let mut game_board = new_with_first_up(Player::Computer(O));
+    fn test_human_vs_human_board() {
+        let game_board = GameBoard::new_human_vs_human();
+
+        assert!(game_board.is_human_vs_human());
+        assert!(!game_board.is_computers_turn());
+        assert!(!GameBoard::new_computer_vs_computer().is_human_vs_human());
+    }
+
+    #[test]
+    fn test_builder_applies_choices() {
+        let game_board = GameBoardBuilder::new()
+            .human_piece(O)
+            .human_first(false)
+            .human_name("Ada")
+            .build();
+
+        assert_eq!(
+            game_board.player_for_id(PlayerID::Player1),
+            &Player::Human(O)
+        );
+        assert_eq!(game_board.next_up, PlayerID::Player2);
+        assert_eq!(game_board.name_of(&Player::Human(O)), Some("Ada"));
+        assert_eq!(game_board.name_of(&Player::Computer(Piece::X)), None);
+    }
+
+    #[test]
+    fn test_undo_and_redo_restore_the_board() {
+        let mut game_board = GameBoard::new_human_vs_human();
+        assert!(game_board.play_next_up_at_position(5).is_ok());
+        let after_first_move = game_board.clone();
+        assert!(game_board.play_next_up_at_position(1).is_ok());
+        let after_second_move = game_board.clone();
+
+        let undone = game_board.undo();
+
+        assert_eq!(
+            undone,
+            Some(MoveRecord {
+                player: PlayerID::Player2,
+                position: 1,
+                turn: 2,
+                piece: None,
+                from: None,
+            })
+        );
+        assert_eq!(game_board.get_cell_at_position(1), Some(&CellState::Empty));
+        assert_eq!(game_board.next_up, PlayerID::Player2);
+        assert_eq!(game_board.history(), after_first_move.history());
+
+        assert_eq!(game_board.redo(), undone);
+        assert_eq!(game_board, after_second_move);
+        assert_eq!(game_board.redo(), None);
+    }
+
+    #[test]
+    fn test_new_move_discards_undone_moves() {
+        let mut game_board = GameBoard::new_human_vs_human();
+        assert!(game_board.play_next_up_at_position(5).is_ok());
+        assert!(game_board.undo().is_some());
+        assert!(game_board.play_next_up_at_position(1).is_ok());
+
+        assert_eq!(game_board.redo(), None);
+        assert_eq!(game_board.undo().map(|record| record.position), Some(1));
+        assert_eq!(game_board.undo(), None);
+    }
+
+    #[test]
+    fn test_undo_turn_takes_back_the_computer_reply() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Human(Piece::X));
+        assert!(game_board.play_next_up_at_position(5).is_ok());
+        assert!(game_board.play_next_up_at_position(1).is_ok());
+
+        let undone: Vec<usize> = undo_turn(&mut game_board)
+            .iter()
+            .map(|record| record.position)
+            .collect();
+
+        assert_eq!(undone, vec![1, 5]);
+        assert!(game_board.history().is_empty());
+        assert_eq!(redo_turn(&mut game_board).len(), 2);
+        assert_eq!(game_board.history().len(), 2);
+    }
+
+    #[test]
+    fn test_scoreboard_counts_finished_games_per_player() {
+        let mut scoreboard = Scoreboard::new();
+
+        let mut won_game = GameBoard::new_human_vs_human();
+        assert!(first_player_top_row_win(&mut won_game).is_ok());
+        scoreboard.record(&won_game);
+        scoreboard.record(&GameBoard::new_human_vs_human());
+
+        assert_eq!(scoreboard.games(), 1);
+        assert_eq!(
+            scoreboard.entries(),
+            [
+                ScoreEntry {
+                    player: "X".to_string(),
+                    wins: 1,
+                    losses: 0,
+                    draws: 0,
+                },
+                ScoreEntry {
+                    player: "O".to_string(),
+                    wins: 0,
+                    losses: 1,
+                    draws: 0,
+                },
+            ]
+        );
+
+        scoreboard.retract(&won_game);
+
+        assert_eq!(scoreboard.games(), 0);
+        assert!(scoreboard
+            .entries()
+            .iter()
+            .all(|entry| entry.wins == 0 && entry.losses == 0));
+    }
+
+    #[test]
+    fn test_winner_reports_the_winning_line() {
+        let mut game_board = GameBoard::new_human_vs_human();
+        assert!(first_player_top_row_win(&mut game_board).is_ok());
 
-        assert!(game_board.play_next_up_at_position(1).is_ok());
-        assert!(game_board.play_next_up_at_position(1).is_err());
+        let GameState::Winner(player, line) = game_board.game_state() else {
+            panic!("expected a winner");
+        };
 
-        assert_eq!(
-            game_board.get_cell_at_position(1),
-            Some(&CellState::Occupied(O))
-        );
+        assert_eq!(player.piece(), &Piece::X);
+        assert_eq!(line.kind, LineKind::Row(0));
+        assert_eq!(line.positions, [1, 2, 3]);
+        assert_eq!(line.to_string(), "top row");
     }
 
     #[test]
-    fn test_empty_board_has_no_winner() {
-        let game_board = GameBoard::new();
+    fn test_completing_a_line_loses_under_misere_rules() {
+        let mut game_board = GameBoard::new_human_vs_human().with_misere(true);
+        assert!(first_player_top_row_win(&mut game_board).is_ok());
 
-        assert_eq!(None, game_board.determine_winner());
+        let GameState::Winner(player, line) = game_board.game_state() else {
+            panic!("expected a winner");
+        };
+
+        assert_eq!(player.piece(), &Piece::O);
+        assert_eq!(game_board.how_won(&line), "as X completed the top row");
+        assert!(game_board.is_game_over());
     }
 
     #[test]
-    fn test_board_with_a_winning_row_has_a_winner() {
-        let mut game_board = GameBoard::new();
-
-        let result = game_board
-            .play_next_up_at_position(1)
-            .and_then(|_| game_board.play_next_up_at_position(4))
-            .and_then(|_| game_board.play_next_up_at_position(2))
-            .and_then(|_| game_board.play_next_up_at_position(7))
-            .and_then(|_| game_board.play_next_up_at_position(3));
+    fn test_wild_games_let_either_piece_be_placed() {
+        let mut game_board = GameBoard::new_human_vs_human();
+        assert_eq!(
+            game_board.play_next_up(Move::place(1, Piece::O)),
+            Err(GameError::NotYourPiece(Piece::O))
+        );
 
-        assert!(result.is_ok());
+        // X completes a row of Os, which wins for X
+        let mut game_board = game_board.with_wild(true);
+        for position in [1, 2, 3] {
+            assert!(game_board
+                .play_next_up(Move::place(position, Piece::O))
+                .is_ok());
+        }
+        assert!(matches!(
+            game_board.game_state(),
+            GameState::Winner(Player::Human(Piece::X), _)
+        ));
 
-        // row: 1,2,3
-        assert!(game_board.determine_winner().is_some());
+        assert!(game_board.undo().is_some());
+        assert!(game_board.redo().is_some());
+        assert_eq!(
+            game_board.get_cell_at_position(3),
+            Some(&CellState::Occupied(Piece::O))
+        );
     }
 
     #[test]
-    fn test_board_with_a_winning_column_has_a_winner() {
-        let mut game_board = GameBoard::new();
-
-        let result = game_board
-            .play_next_up_at_position(1)
-            .and_then(|_| game_board.play_next_up_at_position(2))
-            .and_then(|_| game_board.play_next_up_at_position(4))
-            .and_then(|_| game_board.play_next_up_at_position(9))
-            .and_then(|_| game_board.play_next_up_at_position(7));
+    fn test_winning_lines_cover_columns_and_diagonals() {
+        for (moves, kind, positions) in [
+            ([2, 1, 5, 3, 8], LineKind::Column(1), [2, 5, 8]),
+            ([3, 1, 5, 2, 7], LineKind::AntiDiagonal, [3, 5, 7]),
+            ([1, 2, 5, 3, 9], LineKind::Diagonal, [1, 5, 9]),
+        ] {
+            let mut game_board = GameBoard::new_human_vs_human();
+            for position in moves {
+                assert!(game_board.play_next_up_at_position(position).is_ok());
+            }
 
-        assert!(result.is_ok());
+            assert_eq!(
+                game_board.winning_line(),
+                Some(WinningLine {
+                    kind,
+                    positions: positions.to_vec(),
+                    board_size: 3,
+                    layer: None,
+                })
+            );
+        }
 
-        // column: 1,4,7
-        assert!(game_board.determine_winner().is_some());
+        assert_eq!(GameBoard::new().winning_line(), None);
     }
 
     #[test]
-    fn test_board_with_a_winning_forward_diagonal_has_a_winner() {
-        let mut game_board = GameBoard::new();
-
-        let result = game_board
-            .play_next_up_at_position(1)
-            .and_then(|_| game_board.play_next_up_at_position(2))
-            .and_then(|_| game_board.play_next_up_at_position(5))
-            .and_then(|_| game_board.play_next_up_at_position(7))
-            .and_then(|_| game_board.play_next_up_at_position(9));
+    fn test_larger_boards_need_a_full_line() {
+        let mut game_board = GameBoard::new_human_vs_human().with_size(4);
+        assert_eq!(game_board.positions(), 1..=16);
 
-        assert!(result.is_ok());
+        // X: 1, 2, 3 is not enough on a 4x4 board
+        for position in [1, 5, 2, 6, 3, 7] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+        assert_eq!(game_board.winning_line(), None);
 
-        // diagonal: 1,5,9
-        assert!(game_board.determine_winner().is_some());
+        assert!(game_board.play_next_up_at_position(4).is_ok());
+        assert_eq!(
+            game_board.winning_line(),
+            Some(WinningLine {
+                kind: LineKind::Row(0),
+                positions: vec![1, 2, 3, 4],
+                board_size: 4,
+                layer: None,
+            })
+        );
+        assert!(game_board.play_next_up_at_position(17).is_err());
     }
 
     #[test]
-    fn test_board_with_a_winning_backward_diagonal_has_a_winner() {
-        let mut game_board = GameBoard::new();
+    fn test_shorter_win_lengths_slide_along_the_board() {
+        let play = |moves: &[usize]| {
+            let mut game_board = GameBoard::new_human_vs_human()
+                .with_size(5)
+                .with_win_length(4);
+            for &position in moves {
+                assert!(game_board.play_next_up_at_position(position).is_ok());
+            }
+            game_board.winning_line()
+        };
 
-        let result = game_board
-            .play_next_up_at_position(3)
-            .and_then(|_| game_board.play_next_up_at_position(2))
-            .and_then(|_| game_board.play_next_up_at_position(5))
-            .and_then(|_| game_board.play_next_up_at_position(1))
-            .and_then(|_| game_board.play_next_up_at_position(7));
+        // X: 2, 3, 4, 5 on the top row, away from the left edge
+        let row = play(&[2, 6, 3, 7, 4, 8, 5]);
+        assert_eq!(row.as_ref().map(|line| line.kind), Some(LineKind::Row(0)));
+        assert_eq!(row.map(|line| line.positions), Some(vec![2, 3, 4, 5]));
 
-        assert!(result.is_ok());
+        // X: 4, 8, 12, 16 running down to the left from the top row
+        let diagonal = play(&[4, 1, 8, 2, 12, 3, 16]);
+        assert_eq!(
+            diagonal.as_ref().map(WinningLine::name),
+            Some("diagonal running down to the left".to_string())
+        );
+        assert_eq!(
+            diagonal.map(|line| line.positions),
+            Some(vec![4, 8, 12, 16])
+        );
 
-        // diagonal: 3,5,7
-        assert!(game_board.determine_winner().is_some());
+        assert_eq!(play(&[1, 6, 2, 7, 3]), None);
     }
 
     #[test]
-    fn test_invalid_cell_positions_return_none() {
-        let game_board = GameBoard::new();
+    fn test_win_lengths_are_checked_against_the_size() {
+        assert_eq!(GameBoard::parse_win_length("4", 5), Ok(4));
+        assert!(GameBoard::parse_win_length("4", 3).is_err());
+        assert!(GameBoard::parse_win_length("2", 5).is_err());
+        assert_eq!(GameBoard::new().with_win_length(5).win_length(), 3);
+    }
 
-        assert_eq!(game_board.get_cell_at_position(0), None);
+    #[test]
+    fn test_inner_lines_of_larger_boards_are_numbered() {
+        let line = |kind, size| WinningLine {
+            kind,
+            positions: vec![0; size],
+            board_size: size,
+            layer: None,
+        };
 
-        assert_eq!(game_board.get_cell_at_position(100), None);
+        assert_eq!(line(LineKind::Row(1), 3).name(), "middle row");
+        assert_eq!(line(LineKind::Row(1), 4).name(), "row 2");
+        assert_eq!(line(LineKind::Column(2), 5).name(), "middle column");
+        assert_eq!(line(LineKind::Column(4), 5).name(), "right column");
     }
 
     #[test]
-    fn test_valid_cell_positions_return_valid_cells() {
-        let game_board = GameBoard::new();
+    fn test_cubes_have_lines_through_the_layers() {
+        let cube = GameBoard::new_human_vs_human().with_cube(true);
+        assert_eq!(cube.positions().count(), 27);
+        assert_eq!(cube.line_starts().count(), 49);
+
+        // X: 5, 14, 23 is a pillar through the middle of each layer
+        let mut game_board = cube.clone();
+        for position in [5, 1, 14, 2, 23] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+        let line = game_board.winning_line();
+        assert_eq!(line.as_ref().map(|line| line.kind), Some(LineKind::Pillar));
+        assert_eq!(
+            line.map(|line| line.to_string()),
+            Some("pillar through the layers".to_string())
+        );
 
+        // X: 1, 14, 27 runs from corner to corner
+        let mut game_board = cube.clone();
+        for position in [1, 2, 14, 3, 27] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
         assert_eq!(
-            game_board.get_cell_at_position(1),
-            Some(get_cell_at_coordinate(&game_board, &Coordinate::TOP_LEFT))
+            game_board.winning_line().map(|line| line.kind),
+            Some(LineKind::SpaceDiagonal)
         );
+
+        // X: 10, 11, 12 is the top row of the middle layer
+        let mut game_board = cube;
+        for position in [10, 1, 11, 2, 12] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
         assert_eq!(
-            game_board.get_cell_at_position(2),
-            Some(get_cell_at_coordinate(&game_board, &Coordinate::TOP_CENTER))
+            game_board.winning_line().map(|line| line.to_string()),
+            Some("top row of layer 2".to_string())
         );
+    }
+
+    #[test]
+    fn test_morris_pieces_slide_once_all_are_down() {
+        let mut game_board = GameBoard::new_human_vs_human().with_morris(true);
         assert_eq!(
-            game_board.get_cell_at_position(3),
-            Some(get_cell_at_coordinate(&game_board, &Coordinate::TOP_RIGHT))
+            game_board.play_next_up(Move::slide(1, 2, Piece::X)),
+            Err(GameError::CannotSlide)
         );
 
+        // X: 1, 2, 6 and O: 4, 5, 9, with every piece down and X to move
+        for position in [1, 5, 2, 9, 6, 4] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+        assert!(game_board.must_slide());
         assert_eq!(
-            game_board.get_cell_at_position(4),
-            Some(get_cell_at_coordinate(
-                &game_board,
-                &Coordinate::MIDDLE_LEFT
-            ))
+            game_board.play_next_up_at_position(3),
+            Err(GameError::MustSlide)
         );
+
+        // Slides go along rows and columns, and diagonally only along the long diagonals
+        assert_eq!(game_board.slide_destinations(6), vec![3]);
+        assert_eq!(game_board.slide_destinations(5), vec![3, 7, 8]);
+        let slide = |from, to| Move::slide(from, to, Piece::X);
         assert_eq!(
-            game_board.get_cell_at_position(5),
-            Some(get_cell_at_coordinate(
-                &game_board,
-                &Coordinate::MIDDLE_CENTER
-            ))
+            game_board.clone().play_next_up(slide(5, 3)),
+            Err(GameError::NothingToSlide(5))
+        );
+        assert_eq!(
+            game_board.clone().play_next_up(slide(1, 3)),
+            Err(GameError::NotAdjacent(1, 3))
         );
+
+        let winning_slide = slide(6, 3);
+        assert!(game_board.play_next_up(winning_slide).is_ok());
+        assert!(matches!(
+            game_board.game_state(),
+            GameState::Winner(Player::Human(Piece::X), _)
+        ));
+
+        let undone = game_board.undo();
+        assert_eq!(undone.map(|record| record.from), Some(Some(6)));
         assert_eq!(
             game_board.get_cell_at_position(6),
-            Some(get_cell_at_coordinate(
-                &game_board,
-                &Coordinate::MIDDLE_RIGHT
-            ))
+            Some(&CellState::Occupied(Piece::X))
         );
+        assert_eq!(game_board.get_cell_at_position(3), Some(&CellState::Empty));
+        assert!(game_board.legal_moves().contains(&winning_slide));
 
+        assert_eq!(game_board.redo(), undone);
+        assert!(game_board.is_game_over());
+    }
+
+    #[test]
+    fn test_gravity_drops_pieces_to_the_bottom_of_their_column() {
+        let mut game_board = GameBoard::new_human_vs_human().with_gravity(true);
+        assert_eq!(game_board.get_available_positions(), vec![7, 8, 9]);
         assert_eq!(
-            game_board.get_cell_at_position(7),
-            Some(get_cell_at_coordinate(
-                &game_board,
-                &Coordinate::BOTTOM_LEFT
-            ))
+            game_board.play_next_up_at_position(2),
+            Err(GameError::NotLowestInColumn(2))
         );
+
+        // Filling the middle column from the bottom up
+        for position in [8, 5, 2] {
+            assert_eq!(game_board.drop_position(2), Ok(position));
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+        assert_eq!(game_board.get_available_positions(), vec![7, 9]);
+
+        assert_eq!(game_board.drop_position(2), Err(GameError::ColumnFull(2)));
         assert_eq!(
-            game_board.get_cell_at_position(8),
-            Some(get_cell_at_coordinate(
-                &game_board,
-                &Coordinate::BOTTOM_CENTER
-            ))
+            game_board.drop_position(0),
+            Err(GameError::InvalidColumn(0))
         );
         assert_eq!(
-            game_board.get_cell_at_position(9),
-            Some(get_cell_at_coordinate(
-                &game_board,
-                &Coordinate::BOTTOM_RIGHT
-            ))
+            game_board.drop_position(4),
+            Err(GameError::InvalidColumn(4))
+        );
+        assert_eq!(
+            game_board.drop_move(3, Piece::O),
+            Ok(Move::place(9, Piece::O))
         );
     }
 
     #[test]
-    fn test_player_id() {
-        let game_board = GameBoard::new();
-
+    fn test_notakto_players_both_place_x_and_the_last_line_loses() {
+        let mut game_board = GameBoard::new_human_vs_human().with_notakto(Some(2));
+        assert_eq!(game_board.get_available_positions().len(), 18);
+
+        // The first player completes the top row of board 1, which only kills that board
+        for position in [1, 2, 3] {
+            assert_eq!(game_board.next_piece(), Piece::X);
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+        assert!(game_board.is_board_dead(0));
+        assert!(!game_board.is_game_over());
         assert_eq!(
-            *game_board.player_for_id(PlayerID::Player1),
-            game_board.player_1
+            game_board.play_next_up_at_position(4),
+            Err(GameError::BoardDead(1))
         );
         assert_eq!(
-            *game_board.player_for_id(PlayerID::Player2),
-            game_board.player_2
+            game_board.get_available_positions(),
+            (10..=18).collect::<Vec<_>>()
+        );
+
+        // The second player completes the top row of the last board and loses
+        for position in [10, 11, 12] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+        let GameState::Winner(player, line) = game_board.game_state() else {
+            panic!("expected a winner");
+        };
+
+        assert_eq!(player.piece(), &Piece::X);
+        assert_eq!(line.positions, [10, 11, 12]);
+        assert_eq!(
+            game_board.how_won(&line),
+            "as Player 2 completed the top row of board 2"
         );
+
+        // Against the computer, whoever completes the line is named rather than given a piece
+        let mut game_board =
+            GameBoard::new_with_first_up(Player::Human(Piece::X)).with_notakto(Some(1));
+        for position in [1, 7, 2, 8, 3] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+        let Some(line) = game_board.winning_line() else {
+            panic!("expected a winning line");
+        };
+        assert_eq!(game_board.how_won(&line), "as you completed the top row");
     }
 
     #[test]
-    fn test_determine_winning_player_resolves_to_x_if_x_wins() {
-        let mut game_board = new_with_first_up(Player::Computer(Piece::X));
-        let result = first_player_top_row_win(&mut game_board);
+    fn test_blocked_cells_take_no_pieces_and_complete_no_lines() {
+        // The fifth empty cell, then the first of those left
+        let mut game_board = GameBoard::new_human_vs_human()
+            .with_blocked_cells_from(2, &mut ReplayedRandom::new(vec![4, 0]));
+        assert_eq!(game_board.blocked_positions(), vec![1, 5]);
+        assert_eq!(
+            game_board.play_next_up_at_position(5),
+            Err(GameError::CellBlocked(5))
+        );
+        assert_eq!(
+            game_board.get_available_positions(),
+            vec![2, 3, 4, 6, 7, 8, 9]
+        );
 
-        assert!(result.is_ok());
+        // X holds the rest of the top row and the middle column, but neither line is complete
+        for position in [2, 4, 3, 6, 8] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+        assert_eq!(game_board.determine_winner(), None);
+        assert_eq!(
+            game_board.to_string(),
+            "[#] [X] [X] \n\
+             [O] [#] [O] \n\
+             [ ] [X] [ ] "
+        );
 
-        assert!(matches!(
-            game_board.determine_winning_player(),
-            Some(&Player::Computer(Piece::X))
-        ));
+        // Pieces dropped under gravity come to rest on a blocked cell
+        let game_board = GameBoard::new_human_vs_human()
+            .with_gravity(true)
+            .with_blocked_cells_from(1, &mut ReplayedRandom::new(vec![7]));
+        assert_eq!(game_board.drop_position(2), Ok(5));
     }
 
     #[test]
-    fn test_determine_winning_player_resolves_to_o_if_o_wins() {
-        let mut game_board = new_with_first_up(Player::Computer(O));
-        let result = first_player_top_row_win(&mut game_board);
+    fn test_cube_layers_are_shown_side_by_side() {
+        let mut game_board = GameBoard::new_human_vs_human().with_cube(true);
+        assert!(game_board.play_next_up_at_position(14).is_ok());
 
-        assert!(result.is_ok());
-
-        assert!(matches!(
-            game_board.determine_winning_player(),
-            Some(&Player::Computer(Piece::O))
-        ));
+        assert_eq!(
+            game_board.to_string(),
+            "Layer 1      Layer 2      Layer 3\n\
+             [ ] [ ] [ ]  [ ] [ ] [ ]  [ ] [ ] [ ] \n\
+             [ ] [ ] [ ]  [ ] [X] [ ]  [ ] [ ] [ ] \n\
+             [ ] [ ] [ ]  [ ] [ ] [ ]  [ ] [ ] [ ] "
+        );
     }
 
     #[test]
-    fn test_determine_winning_player_resolves_to_none_if_there_is_no_winner() {
-        let mut game_board = new_with_first_up(Player::Computer(O));
+    fn test_colored_cells_keep_the_layer_headings_lined_up() {
+        let game_board = GameBoard::new_human_vs_human().with_cube(true);
+        let colored = game_board.layout_cells(|_| "[\x1b[31mX\x1b[0m] ".to_string());
 
-        let result = game_board.play_next_up_at_position(1);
+        assert!(colored.starts_with("Layer 1      Layer 2      Layer 3\n"));
+    }
 
-        assert!(result.is_ok());
-        assert!(matches!(game_board.determine_winning_player(), None));
+    #[test]
+    fn test_board_sizes_are_checked() {
+        assert_eq!(GameBoard::parse_size("4"), Ok(4));
+        assert!(GameBoard::parse_size("2").is_err());
+        assert!(GameBoard::parse_size("big").is_err());
     }
 
     #[test]
-    fn test_winning_player_can_be_player_2() {
-        let first_up = Player::Computer(O);
-        let _second_up = Player::Human(Piece::X);
+    fn test_legal_moves_are_empty_cells_of_unfinished_games() {
+        let mut game_board = GameBoard::new_human_vs_human();
+        assert!(game_board.play_next_up_at_position(5).is_ok());
 
-        let mut game_board = new_with_first_up(first_up);
+        assert!(game_board.is_legal_move(1));
+        assert!(!game_board.is_legal_move(5));
+        assert!(!game_board.is_legal_move(10));
 
-        let result = first_player_top_row_win(&mut game_board);
+        let mut game_board = GameBoard::new_human_vs_human();
+        assert!(first_player_top_row_win(&mut game_board).is_ok());
 
-        assert!(result.is_ok());
-        assert!(matches!(
-            game_board.determine_winning_player(),
-            Some(_second_up)
-        ));
+        assert!(!game_board.is_legal_move(9));
     }
 
     #[test]
-    fn test_board_is_full() {
-        let mut game_board = GameBoard::new();
-        GameBoard::POSITIONS.for_each(|position| _ = game_board.play_next_up_at_position(position));
+    fn test_hints_run_out_and_stay_used_after_undo() {
+        let mut game_board = GameBoard::new_human_vs_human().with_hint_budget(1);
+        assert_eq!(game_board.hints_left(), Some(1));
+        assert!(game_board.play_next_up_at_position(5).is_ok());
+        assert!(game_board.take_hint().is_ok());
 
-        assert!(game_board.is_board_full());
-    }
+        assert!(game_board.undo().is_some());
 
-    #[test]
-    fn test_empty_board_is_not_full() {
-        let game_board = GameBoard::new();
+        assert_eq!(game_board.hints_used(), 1);
+        assert_eq!(game_board.take_hint(), Err(GameError::NoHintsLeft));
 
-        assert!(!game_board.is_board_full());
+        let mut game_board = GameBoard::new_human_vs_human();
+        assert!(game_board.take_hint().is_ok());
+        assert_eq!(game_board.hints_left(), None);
     }
 
     #[test]
-    fn test_board_with_less_than_9_positions_is_not_full() {
-        let mut game_board = GameBoard::new();
+    fn test_new_board_follows_random_source() {
+        // Heads gives the human O, then heads again puts player 1 (the human) first
+        let game_board = GameBoard::new_with_random_source(&mut ReplayedRandom::new(vec![0, 0]));
 
-        let all_positions = GameBoard::POSITIONS;
-        let end_position = all_positions.end();
-        let new_end_position = *end_position - 1;
-        let less_than_all_positions = *all_positions.start()..=new_end_position;
+        assert_eq!(game_board.player_1, Player::Human(O));
+        assert_eq!(game_board.player_2, Player::Computer(Piece::X));
+        assert_eq!(game_board.next_up, PlayerID::Player1);
 
-        less_than_all_positions
-            .for_each(|position| _ = game_board.play_next_up_at_position(position));
+        let game_board = GameBoard::new_with_random_source(&mut ReplayedRandom::new(vec![1, 1]));
 
-        assert!(!game_board.is_board_full());
+        assert_eq!(game_board.player_1, Player::Human(Piece::X));
+        assert_eq!(game_board.next_up, PlayerID::Player2);
     }
 
     #[test]
-    fn test_get_random_available_position_is_a_not_none() {
-        let random_position = GameBoard::new().get_random_available_position();
+    fn test_random_position_follows_random_source() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Human(Piece::X));
+        assert!(game_board.play_next_up_at_position(1).is_ok());
+
+        // Index 2 of the available positions [2, 3, 4, ...]
+        let position = game_board.random_available_position_from(&mut ReplayedRandom::new(vec![2]));
 
-        assert!(matches!(random_position, Some(_)));
+        assert_eq!(position, Some(4));
     }
 
     #[test]
-    fn test_name_of_human_player() {
-        let human_name = "Human";
+    fn test_game_state_outlives_the_board_and_crosses_threads() {
+        let mut game_board = GameBoard::new_with_first_up(Player::Human(Piece::X));
+        assert!(first_player_top_row_win(&mut game_board).is_ok());
+        let state = game_board.game_state();
+        drop(game_board);
 
-        assert_eq!(human_name, Player::Human(Piece::X).name());
-        assert_eq!(human_name, Player::Human(O).name());
+        let sent = std::thread::spawn(move || state).join();
+
+        assert!(matches!(
+            sent,
+            Ok(GameState::Winner(
+                Player::Human(Piece::X),
+                WinningLine {
+                    kind: LineKind::Row(0),
+                    ..
+                }
+            ))
+        ));
     }
 
     #[test]
-    fn test_name_of_computer_player() {
-        let computer_name = "Computer";
+    fn test_rotated_and_mirrored_positions_share_a_canonical_form() {
+        let board_after = |moves: &[usize], gravity: bool| {
+            let mut game_board = GameBoard::new_human_vs_human().with_gravity(gravity);
+            for &position in moves {
+                assert!(game_board.play_next_up_at_position(position).is_ok());
+            }
+            game_board.canonical_form()
+        };
 
-        assert_eq!(computer_name, Player::Computer(Piece::X).name());
-        assert_eq!(computer_name, Player::Computer(O).name());
+        // X in a corner and O on an edge next to it, turned to each corner in turn
+        let form = board_after(&[1, 2], false);
+        assert_eq!(board_after(&[3, 6], false), form);
+        assert_eq!(board_after(&[9, 8], false), form);
+        assert_eq!(board_after(&[7, 4], false), form);
+        assert_ne!(board_after(&[1, 6], false), form);
+        assert_eq!(form.cell_at_position(10), None);
+
+        // Under gravity only the mirror image left to right is the same position
+        assert_eq!(board_after(&[7, 8], true), board_after(&[9, 8], true));
+        assert_eq!(board_after(&[7, 8], false), board_after(&[7, 4], false));
+        assert_ne!(board_after(&[7, 8], true), board_after(&[7, 4], true));
     }
 
     #[test]
-    fn test_piece_retrieval() {
-        assert_eq!(&Piece::X, Player::Computer(Piece::X).piece());
-        assert_eq!(&O, Player::Computer(O).piece());
-        assert_eq!(&Piece::X, Player::Human(Piece::X).piece());
-        assert_eq!(&O, Player::Human(O).piece());
+    fn test_symmetries_are_permutations() {
+        let all = symmetries(GameBoard::CLASSIC_SIZE);
+
+        assert_eq!(all.len(), 8);
+        assert_eq!(all[0], (1..=9).collect::<Vec<_>>());
+        assert!(all.contains(&vec![3, 6, 9, 2, 5, 8, 1, 4, 7]));
+        for symmetry in &all {
+            let mut positions = symmetry.clone();
+            positions.sort_unstable();
+
+            assert_eq!(positions, (1..=9).collect::<Vec<_>>());
+        }
     }
 
     // Private test utility functions
-    fn first_player_top_row_win(game_board: &mut GameBoard) -> Result<(), String> {
+    fn first_player_top_row_win(game_board: &mut GameBoard) -> Result<(), GameError> {
         game_board
             .play_next_up_at_position(1)
             .and_then(|_| game_board.play_next_up_at_position(4))
@@ -739,24 +2934,12 @@ mod tests {
             .and_then(|_| game_board.play_next_up_at_position(3))
     }
 
-    const fn get_cell_at_coordinate<'a>(
+    fn get_cell_at_coordinate<'a>(
         game_board: &'a GameBoard,
         coordinate: &Coordinate,
     ) -> &'a CellState {
-        &game_board.cells[coordinate.row()][coordinate.col()]
-    }
-
-    fn new_with_first_up(first_up_player: Player) -> GameBoard {
-        let other_player = match first_up_player {
-            Player::Computer(piece) => Player::Human(GameBoard::other_piece(piece)),
-            Player::Human(piece) => Player::Computer(GameBoard::other_piece(piece)),
-        };
-
-        GameBoard {
-            player_1: first_up_player,
-            player_2: other_player,
-            next_up: PlayerID::Player1,
-            cells: [[CellState::default(); 3]; 3],
-        }
+        game_board
+            .cells
+            .cell(coordinate.row() * game_board.size() + coordinate.col())
     }
 }
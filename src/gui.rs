@@ -1,18 +1,245 @@
-use crate::game_model;
-use crate::game_model::{execute_computer_turn, CellState, GameBoard, GameState, Player};
+use crate::local_data::{self, Interface, Settings};
+use crate::palette::Palette;
+use crate::registry::{self, OpponentEntry, StrategyEntry, OPPONENTS, STRATEGIES};
+use crate::speedrun::{format_run_time, Speedrun};
+use crate::stats::{self, LifetimeStats};
 use eframe::egui;
 use eframe::egui::Response;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tic_tac_toe::frontend::{play_turn, Frontend};
+use tic_tac_toe::game::Game;
+use tic_tac_toe::game_model;
+use tic_tac_toe::game_model::{
+    choose_computer_move, execute_computer_turn, threats, CellState, Difficulty, GameBoard,
+    GameBoardBuilder, GameError, GameState, Move, Piece, Player, PlayerID, Scoreboard,
+};
+use tic_tac_toe::numbering::Numbering;
+use tic_tac_toe::patterns::detect_patterns;
+#[cfg(feature = "serde")]
+use tic_tac_toe::saved_game::{load_game, save_game, DEFAULT_SAVE_NAME};
+
+// After this long without input on a fresh game, the computer plays a demo game against itself
+const ATTRACT_AFTER: Duration = Duration::from_secs(30);
+const DEMO_MOVE_INTERVAL: Duration = Duration::from_secs(1);
+// Pause between moves when watching the computer play itself
+const WATCH_MOVE_DELAY: Duration = Duration::from_millis(800);
+// How often a running speedrun clock is redrawn, often enough for its tenths
+const SPEEDRUN_CLOCK_INTERVAL: Duration = Duration::from_millis(100);
+// The width of a flat board, and of each layer of a cube or Notakto board, which are stacked top
+// to bottom
+const BOARD_SIZE: f32 = 300.0;
+const CUBE_LAYER_SIZE: f32 = 150.0;
+const LAYER_GAP: f32 = 20.0;
+// How long a piece takes to fall to the bottom of its column under gravity
+const DROP_DURATION: Duration = Duration::from_millis(300);
 
 struct TicTacToeApp {
     game_end_message: String,
+    game: Game,
+    show_threats: bool,
+    explain_moves: bool,
+    explanation: String,
+    hint: String,
+    strategy: &'static StrategyEntry,
+    opponent: Option<&'static OpponentEntry>,
+    numbering: Numbering,
+    palette: Palette,
+    distinct_shapes: bool,
+    reduced_motion: bool,
+    last_interaction: Instant,
+    demo: Option<DemoGame>,
+    matchup: Matchup,
+    // Game start choices for the next human vs computer game, None leaves it to chance
+    setup_piece: Option<Piece>,
+    setup_human_first: Option<bool>,
+    setup_name: String,
+    last_computer_move: Instant,
+    // The piece picked up to slide in Three Men's Morris, until the cell it goes to is clicked
+    slide_from: Option<usize>,
+    thinking: Option<ComputerMove>,
+    // Cells along each side of new boards
+    size: usize,
+    cube: bool,
+    // Pieces in a row new games are won with, None for a full line
+    win_length: Option<usize>,
+    // Hints allowed in new games, None for as many as wanted
+    hint_budget: Option<usize>,
+    // Applied to new games
+    misere: bool,
+    wild: bool,
+    // What a click places in wild games
+    wild_piece: Piece,
+    // Only played on the classic board, see morris_allowed
+    morris: bool,
+    gravity: bool,
+    // Boards for Notakto, None for the usual game
+    notakto: Option<usize>,
+    // Cells blocked at random at the start of each game
+    blocked: usize,
+    // The piece last dropped under gravity and when, while it falls into place
+    dropped: Option<(usize, Instant)>,
+    // Kept in the settings file, switched with Z
+    interface: Interface,
+    speedrun: Option<Speedrun>,
+    // How the last speedrun went, shown until the next one starts
+    speedrun_result: String,
+    scoreboard: Scoreboard,
+    // Shown in the statistics window while it is open
+    statistics: Option<LifetimeStats>,
+    // For the records in the scouting reports, loaded again whenever a game is counted
+    lifetime_stats: LifetimeStats,
+    #[cfg(feature = "serde")]
+    save_status: String,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Matchup {
+    HumanVsComputer,
+    HumanVsHuman,
+    ComputerVsComputer,
+}
+
+impl Matchup {
+    const ALL: [Self; 3] = [
+        Self::HumanVsComputer,
+        Self::HumanVsHuman,
+        Self::ComputerVsComputer,
+    ];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::HumanVsComputer => "You vs computer",
+            Self::HumanVsHuman => "Two players (hotseat)",
+            Self::ComputerVsComputer => "Watch computer vs computer",
+        }
+    }
+
+    // Who is playing a board that was set up elsewhere, such as a loaded game
+    #[cfg(feature = "serde")]
+    fn of(game_board: &GameBoard) -> Self {
+        match (
+            game_board.player_for_id(PlayerID::Player1),
+            game_board.player_for_id(PlayerID::Player2),
+        ) {
+            (Player::Human(_), Player::Human(_)) => Self::HumanVsHuman,
+            (Player::Computer(_), Player::Computer(_)) => Self::ComputerVsComputer,
+            _ => Self::HumanVsComputer,
+        }
+    }
+
+    fn new_game_board(self, setup: GameBoardBuilder, size: usize) -> GameBoard {
+        match self {
+            Self::HumanVsComputer => setup.size(size).build(),
+            Self::HumanVsHuman => GameBoard::new_human_vs_human().with_size(size),
+            Self::ComputerVsComputer => GameBoard::new_computer_vs_computer().with_size(size),
+        }
+    }
+}
+
+// A move being chosen on another thread, so a slow strategy does not freeze the window
+struct ComputerMove {
+    // The board the move was chosen for, it no longer applies once the board has changed
+    game_board: GameBoard,
+    receiver: Receiver<Option<Move>>,
+}
+
+impl ComputerMove {
+    fn start(
+        game_board: &GameBoard,
+        strategy: &'static StrategyEntry,
+        ctx: &egui::Context,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let board = game_board.clone();
+        let ctx = ctx.clone();
+
+        thread::spawn(move || {
+            // Nobody is waiting any more if the game moved on meanwhile
+            if sender.send(choose_computer_move(&board, strategy)).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+
+        Self {
+            game_board: game_board.clone(),
+            receiver,
+        }
+    }
+}
+
+struct DemoGame {
     game_board: GameBoard,
+    last_move: Instant,
 }
 
 impl TicTacToeApp {
     fn new() -> Self {
         Self {
             game_end_message: String::new(),
-            game_board: GameBoard::new(),
+            game: Game::new(GameBoard::new()),
+            show_threats: false,
+            explain_moves: false,
+            explanation: String::new(),
+            hint: String::new(),
+            strategy: registry::default_strategy(),
+            opponent: None,
+            numbering: Numbering::default(),
+            palette: Palette::default(),
+            distinct_shapes: false,
+            reduced_motion: false,
+            last_interaction: Instant::now(),
+            demo: None,
+            matchup: Matchup::HumanVsComputer,
+            setup_piece: None,
+            setup_human_first: None,
+            setup_name: String::new(),
+            last_computer_move: Instant::now(),
+            slide_from: None,
+            thinking: None,
+            size: GameBoard::CLASSIC_SIZE,
+            cube: false,
+            win_length: None,
+            hint_budget: None,
+            misere: false,
+            wild: false,
+            wild_piece: Piece::X,
+            morris: false,
+            gravity: false,
+            notakto: None,
+            blocked: 0,
+            dropped: None,
+            interface: Settings::load().interface,
+            speedrun: None,
+            speedrun_result: String::new(),
+            scoreboard: Scoreboard::new(),
+            statistics: None,
+            lifetime_stats: LifetimeStats::load().unwrap_or_default(),
+            #[cfg(feature = "serde")]
+            save_status: String::new(),
+        }
+    }
+}
+
+impl DemoGame {
+    fn new() -> Self {
+        Self {
+            game_board: GameBoard::new_computer_vs_computer(),
+            last_move: Instant::now(),
+        }
+    }
+
+    fn advance(&mut self, strategy: &StrategyEntry) {
+        if self.last_move.elapsed() < DEMO_MOVE_INTERVAL {
+            return;
+        }
+
+        if self.game_board.is_game_over() {
+            *self = Self::new();
+        } else {
+            _ = execute_computer_turn(&mut self.game_board, strategy);
+            self.last_move = Instant::now();
         }
     }
 }
@@ -28,190 +255,1417 @@ pub fn gui_main() {
 
 impl eframe::App for TicTacToeApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        self.game_end_message = update_ui(self, ctx);
+        if !update_attract_mode(self, ctx) {
+            update_ui(self, ctx);
+        }
     }
 }
 
-fn update_ui(app: &mut TicTacToeApp, ctx: &egui::Context) -> String {
-    let mut turn_result: Result<(), String> = Ok(());
+// Returns whether the demo game took over the window for this frame
+fn update_attract_mode(app: &mut TicTacToeApp, ctx: &egui::Context) -> bool {
+    if ctx.input(|input| !input.events.is_empty() || input.pointer.any_down()) {
+        app.last_interaction = Instant::now();
+        app.demo = None;
+        return false;
+    }
+
+    if app.demo.is_none() {
+        if app.reduced_motion || app.speedrun.is_some() || !is_fresh_game(app.game.board()) {
+            return false;
+        }
+
+        let idle = app.last_interaction.elapsed();
+        if idle < ATTRACT_AFTER {
+            ctx.request_repaint_after(ATTRACT_AFTER - idle);
+            return false;
+        }
+
+        app.demo = Some(DemoGame::new());
+    }
+
+    let Some(demo) = &mut app.demo else {
+        return false;
+    };
+    demo.advance(app.strategy);
 
     egui::CentralPanel::default().show(ctx, |ui| {
-        // Define the size of the board
-        let board_size = 300.0;
-        let cell_size = board_size / 3.0;
+        let cell_size = BOARD_SIZE / 3.0;
+        let (_, painter) = ui.allocate_painter(egui::Vec2::splat(BOARD_SIZE), egui::Sense::hover());
+
+        draw_grid_lines(&painter, cell_size, demo.game_board.size(), 1);
+        draw_board_contents(
+            &demo.game_board,
+            &painter,
+            cell_size,
+            app.palette,
+            app.distinct_shapes,
+            None,
+        );
+
+        ui.label("Demo game. Move the mouse or press a key to play.");
+    });
+
+    ctx.request_repaint_after(DEMO_MOVE_INTERVAL);
+    true
+}
+
+// The human has not made a move yet
+fn is_fresh_game(game_board: &GameBoard) -> bool {
+    let has_human = [PlayerID::Player1, PlayerID::Player2]
+        .into_iter()
+        .any(|player_id| matches!(game_board.player_for_id(player_id), Player::Human(_)));
+
+    has_human
+        && game_board
+            .history()
+            .iter()
+            .all(|record| matches!(game_board.player_for_id(record.player), Player::Computer(_)))
+}
+
+// The window as a frontend, for one frame. The human's turn is whatever they clicked this frame,
+// if anything.
+struct GuiTurn<'a> {
+    app: &'a mut TicTacToeApp,
+    ctx: &'a egui::Context,
+    response: &'a Response,
+    cell_size: f32,
+}
+
+impl Frontend for GuiTurn<'_> {
+    fn game(&mut self) -> &mut Game {
+        &mut self.app.game
+    }
+
+    fn prompt_move(&mut self) -> Result<(), GameError> {
+        let app = &mut *self.app;
+
+        if app.game.premove().is_some() {
+            app.game.play_premove().map(|_| ())
+        } else if self.response.clicked() {
+            update_board_based_on_response(
+                &mut app.game,
+                self.response,
+                self.cell_size,
+                app.wild_piece,
+                &mut app.slide_from,
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    fn play_computer_move(&mut self) -> Result<(), GameError> {
+        let app = &mut *self.app;
+        if app.matchup == Matchup::ComputerVsComputer
+            && app.last_computer_move.elapsed() < WATCH_MOVE_DELAY
+        {
+            return Ok(());
+        }
+
+        let Some(chosen) = play_computer_move(app, self.ctx)? else {
+            return Ok(());
+        };
+
+        let size = app.game.board().size();
+        let label = app.numbering.label(chosen.position, size);
+        match chosen.from {
+            Some(from) => println!(
+                "\nThe computer slid {} from position {} to {label}",
+                chosen.piece,
+                app.numbering.label(from, size)
+            ),
+            None => println!(
+                "\nThe computer played {} in position: {label}",
+                chosen.piece
+            ),
+        }
+        Ok(())
+    }
+
+    // The board itself is painted at the start of the next frame, this catches up on the rest
+    fn render_board(&mut self, board_before_turn: &GameBoard) {
+        let app = &mut *self.app;
+        update_scoreboard(app, board_before_turn);
+
+        if app.game.board() == board_before_turn {
+            return;
+        }
+        app.hint.clear();
+
+        if app.game.board().is_gravity()
+            && !app.reduced_motion
+            && app.game.board().history().len() > board_before_turn.history().len()
+        {
+            app.dropped = app
+                .game
+                .board()
+                .history()
+                .last()
+                .map(|record| (record.position, Instant::now()));
+        }
+        let mover = board_before_turn.player_for_id(board_before_turn.next_up);
+
+        // Keep the computer's reply explained alongside the human move before it
+        if matches!(mover, Player::Human(_)) {
+            app.explanation.clear();
+        }
+
+        for pattern in detect_patterns(board_before_turn, app.game.board()) {
+            app.explanation.push_str(
+                format!(
+                    "{}: {}: {}\n",
+                    mover.name(),
+                    pattern.name,
+                    pattern.explanation
+                )
+                .as_str(),
+            );
+        }
+    }
+
+    fn report_error(&mut self, error: &GameError) {
+        println!("\nError: {error}");
+    }
+
+    // Shown under the board every frame, so this also says whose turn it is while the game goes on
+    fn announce_result(&mut self) {
+        self.app.game_end_message = end_of_game_text(&self.app.game);
+    }
+}
+
+fn update_ui(app: &mut TicTacToeApp, ctx: &egui::Context) {
+    // Z switches zen mode either way, unless it is being typed into a text field
+    if !ctx.wants_keyboard_input() && ctx.input(|input| input.key_pressed(egui::Key::Z)) {
+        toggle_zen(app);
+    }
+
+    if app.interface == Interface::Standard {
+        egui::SidePanel::right("scoreboard_panel").show(ctx, |ui| {
+            draw_scoreboard(ui, app);
+        });
+        draw_statistics_window(ctx, &mut app.statistics);
+    }
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        // Define the size of the board, the same however many cells it has
+        let size = app.game.board().size();
+        let layers = app.game.board().layers();
+        let board_size = if layers > 1 {
+            CUBE_LAYER_SIZE
+        } else {
+            BOARD_SIZE
+        };
+        let cell_size = board_size / usize_to_f32(size).unwrap_or(3.0);
 
         // Create a painter to draw the grid and marks
         let (response, painter) =
-            ui.allocate_painter(egui::Vec2::splat(board_size), egui::Sense::click());
+            ui.allocate_painter(board_extent(cell_size, size, layers), egui::Sense::click());
+
+        let response = match response
+            .hover_pos()
+            .and_then(|pos| position_at(pos, cell_size, size, layers))
+        {
+            Some(position) => {
+                let label = app.numbering.label(position, size);
+                let text = match app.game.board().coordinate_at_position(position) {
+                    Some(coordinate) if app.game.board().is_cube() => {
+                        format!("Position {label} on layer {}", coordinate.layer() + 1)
+                    }
+                    Some(coordinate) if layers > 1 => {
+                        format!("Position {label} on board {}", coordinate.layer() + 1)
+                    }
+                    _ => format!("Position {label}"),
+                };
+                response.on_hover_text_at_pointer(text)
+            }
+            None => response,
+        };
+
+        if app.show_threats && !app.game.board().is_game_over() {
+            draw_threats(app.game.board(), &painter, cell_size, app.palette);
+        }
 
-        draw_grid_lines(&painter, cell_size, board_size);
+        draw_grid_lines(&painter, cell_size, size, layers);
 
-        draw_board_contents(&app.game_board, &painter, cell_size);
+        // Under gravity, where a click in the column under the pointer would land
+        let drop_target = response
+            .hover_pos()
+            .and_then(|pos| position_at(pos, cell_size, size, layers))
+            .filter(|_| app.game.board().is_gravity() && !app.game.board().is_computers_turn())
+            .map(|position| human_move(app.game.board(), position, app.wild_piece).position)
+            .filter(|&position| app.game.board().is_legal_move(position));
+
+        let premove = app.game.premove().map(|chosen| chosen.position);
+        if let Some(position) = premove.or(app.slide_from).or(drop_target) {
+            draw_highlight(position, &painter, cell_size, size, app.palette);
+        }
+
+        let falling = app.dropped.and_then(|(position, dropped_at)| {
+            let fallen = dropped_at.elapsed().as_secs_f32() / DROP_DURATION.as_secs_f32();
+            (fallen < 1.0).then(|| {
+                ctx.request_repaint();
+                // Speeding up as it goes
+                (position, 1.0 - fallen * fallen)
+            })
+        });
+
+        draw_board_contents(
+            app.game.board(),
+            &painter,
+            cell_size,
+            app.palette,
+            app.distinct_shapes,
+            falling,
+        );
+        draw_dead_boards(app.game.board(), &painter, cell_size);
+
+        let zen = app.interface == Interface::Zen;
+        if !zen {
+            draw_new_game_options(ui, app);
+        }
 
-        if app.game_board.is_game_over() {
-            draw_status_message(ui, &app.game_end_message);
+        if app.game.board().is_game_over() {
+            if zen {
+                draw_zen_status(ui, app);
+                // With nothing else on screen, a click on the finished board starts the next game
+                if response.clicked() {
+                    new_game(app);
+                }
+                return;
+            }
+
+            app.game_end_message = end_of_game_text(&app.game);
+            draw_status_message(ui, &app.game_end_message, app.palette);
+            draw_opponent_remark(ui, app);
+
+            // A losing mis-click can be taken back too, except in a speedrun
+            if app.matchup != Matchup::ComputerVsComputer && app.speedrun.is_none() {
+                draw_undo_button(ui, app);
+            }
             return;
         }
 
-        turn_result = if app.game_board.is_computers_turn() {
-            execute_computer_turn(&mut app.game_board)
-        } else if response.clicked() {
-            app.game_board
-                .update_board_based_on_response(&response, cell_size)
+        if !zen {
+            draw_play_options(ui, ctx, app);
+        }
+
+        // A premove cannot say which piece to slide, so Three Men's Morris goes without
+        if app.game.board().is_computers_turn()
+            && app.matchup == Matchup::HumanVsComputer
+            && !app.game.board().is_morris()
+        {
+            if let Some(position) = response
+                .clicked()
+                .then(|| response.hover_pos())
+                .flatten()
+                .and_then(|pos| position_at(pos, cell_size, size, layers))
+            {
+                let chosen = human_move(app.game.board(), position, app.wild_piece);
+                app.game.queue_premove(chosen);
+            }
+        }
+
+        let mut turn = GuiTurn {
+            app,
+            ctx,
+            response: &response,
+            cell_size,
+        };
+        play_turn(&mut turn);
+        turn.announce_result();
+
+        if zen {
+            draw_zen_status(ui, app);
         } else {
-            Ok(())
+            draw_turn_details(ui, app);
+        }
+
+        // Keep the frames coming while the computer has moves to make
+        if app.game.board().is_computers_turn() && !app.game.board().is_game_over() {
+            ctx.request_repaint();
+        }
+    });
+}
+
+// Picking what the next game is like
+fn draw_new_game_options(ui: &mut egui::Ui, app: &mut TicTacToeApp) {
+    // Also offered once a game is over, as the way into the next one
+    if app.matchup == Matchup::HumanVsComputer {
+        draw_game_setup(ui, app);
+    }
+
+    if draw_matchup_selector(ui, &mut app.matchup)
+        | draw_size_selector(ui, &mut app.size, &mut app.win_length)
+        | draw_win_length_selector(ui, app.size, &mut app.win_length)
+        | draw_hint_budget_selector(ui, &mut app.hint_budget)
+        | draw_blocked_cells_selector(ui, &mut app.blocked)
+        | ui.checkbox(&mut app.misere, "Misère: completing a line loses")
+            .changed()
+        | ui.checkbox(&mut app.wild, "Wild: place either piece each turn")
+            .changed()
+        | ui.checkbox(&mut app.cube, "3D: a cube of stacked boards")
+            .changed()
+        | ui.add_enabled(
+            !app.cube,
+            egui::Checkbox::new(&mut app.gravity, "Gravity: pieces drop down their column"),
+        )
+        .on_disabled_hover_text("Played on a flat board")
+        .changed()
+        | ui.add_enabled_ui(notakto_allowed(app), |ui| {
+            draw_notakto_selector(ui, &mut app.notakto)
+        })
+        .inner
+        | ui.add_enabled(
+            morris_allowed(app),
+            egui::Checkbox::new(
+                &mut app.morris,
+                "Three Men's Morris: three pieces each, which then slide",
+            ),
+        )
+        .on_disabled_hover_text("Played on a 3x3 board, without wild rules, gravity, Notakto or 3D")
+        .changed()
+        || ui.button("New game").clicked()
+    {
+        new_game(app);
+    }
+
+    draw_speedrun(ui, app);
+
+    #[cfg(feature = "serde")]
+    draw_save_and_load(ui, app);
+
+    if ui
+        .button("Zen mode")
+        .on_hover_text("Just the board. Press Z to bring everything back.")
+        .clicked()
+    {
+        toggle_zen(app);
+    }
+}
+
+// Settings that can change part way through a game
+fn draw_play_options(ui: &mut egui::Ui, ctx: &egui::Context, app: &mut TicTacToeApp) {
+    ui.checkbox(&mut app.show_threats, "Show threats");
+    ui.checkbox(&mut app.explain_moves, "Explain moves");
+    draw_difficulty_selector(ui, &mut app.strategy);
+    draw_strategy_selector(ui, &mut app.strategy);
+    draw_numbering_selector(ui, &mut app.numbering);
+    draw_palette_selector(ui, &mut app.palette);
+    ui.checkbox(
+        &mut app.distinct_shapes,
+        "Distinct shapes (bold X, outlined O)",
+    );
+
+    // Turns off egui's own transitions, such as collapsing and fading widgets
+    if ui
+        .checkbox(&mut app.reduced_motion, "Reduced motion")
+        .changed()
+    {
+        let animation_time = if app.reduced_motion {
+            0.0
+        } else {
+            egui::Style::default().animation_time
         };
+        ctx.style_mut(|style| style.animation_time = animation_time);
+    }
+}
+
+// Everything shown about the game in progress besides the board
+fn draw_turn_details(ui: &mut egui::Ui, app: &mut TicTacToeApp) {
+    draw_status_message(ui, &app.game_end_message, app.palette);
+    draw_opponent_remark(ui, app);
+
+    if app.thinking.is_some() {
+        draw_thinking_indicator(ui, app.reduced_motion);
+    }
+
+    if app.matchup != Matchup::ComputerVsComputer && app.speedrun.is_none() {
+        draw_swap_player_button(ui, &mut app.game);
+    }
+
+    if app.game.board().is_wild() && !app.game.board().is_computers_turn() {
+        ui.horizontal(|ui| {
+            ui.label("Piece to place:");
+            for piece in [Piece::X, Piece::O] {
+                ui.selectable_value(&mut app.wild_piece, piece, piece.name());
+            }
+        });
+    }
+
+    if app.game.board().must_slide() && !app.game.board().is_computers_turn() {
+        let size = app.game.board().size();
+        ui.label(app.slide_from.map_or_else(
+            || "Click one of your pieces, then an empty cell next to it".to_string(),
+            |from| {
+                format!(
+                    "Sliding from position {}, click where to",
+                    app.numbering.label(from, size)
+                )
+            },
+        ));
+    }
+
+    // Speedruns are played without help
+    if !app.game.board().is_computers_turn() && app.speedrun.is_none() {
+        ui.horizontal(|ui| {
+            let hints_left = app.game.board().hints_left();
+            let label = hints_left.map_or_else(
+                || "Hint".to_string(),
+                |hints_left| format!("Hint ({hints_left} left)"),
+            );
+            if ui
+                .add_enabled(hints_left != Some(0), egui::Button::new(label))
+                .clicked()
+                && app.game.take_hint().is_ok()
+            {
+                app.hint = app.game.board().hint_text(app.numbering);
+            }
+
+            draw_undo_button(ui, app);
+
+            let board_before_redo = app.game.board().clone();
+            if ui.button("Redo").clicked() && !app.game.redo_turn().is_empty() {
+                app.slide_from = None;
+                app.hint.clear();
+                update_scoreboard(app, &board_before_redo);
+            }
+        });
+    }
+
+    if !app.hint.is_empty() {
+        ui.label(&app.hint);
+    }
+
+    if app.explain_moves && !app.explanation.is_empty() {
+        ui.label(&app.explanation);
+    }
+}
+
+// A quiet line under the board in place of everything else
+fn draw_zen_status(ui: &mut egui::Ui, app: &TicTacToeApp) {
+    let text = match app.game.board().game_state() {
+        GameState::Winner(player, _) => format!("{} wins, click for a new game", player.piece()),
+        GameState::Draw => "Draw, click for a new game".to_string(),
+        GameState::InProgress => format!(
+            "{} to play",
+            app.game
+                .board()
+                .player_for_id(app.game.board().next_up)
+                .piece()
+        ),
+    };
+
+    ui.weak(text).on_hover_text("Press Z to leave zen mode");
+}
+
+fn toggle_zen(app: &mut TicTacToeApp) {
+    app.interface = app.interface.toggle_zen();
+
+    if let Err(error) = local_data::set_interface(app.interface) {
+        println!("\nInterface not saved: {error}");
+    }
+}
+
+// Starts choosing the computer's move, or plays it once chosen. Returns the move played, if any
+// this frame.
+fn play_computer_move(
+    app: &mut TicTacToeApp,
+    ctx: &egui::Context,
+) -> Result<Option<Move>, GameError> {
+    // New game, undo and the like leave a move chosen for a board no longer in play
+    if app
+        .thinking
+        .as_ref()
+        .is_some_and(|thinking| thinking.game_board != *app.game.board())
+    {
+        app.thinking = None;
+    }
+
+    let Some(thinking) = &app.thinking else {
+        app.thinking = Some(ComputerMove::start(app.game.board(), app.strategy, ctx));
+        return Ok(None);
+    };
+
+    let chosen = match thinking.receiver.try_recv() {
+        Ok(chosen) => chosen,
+        Err(TryRecvError::Empty) => return Ok(None),
+        // The thread panicked without choosing
+        Err(TryRecvError::Disconnected) => None,
+    };
+
+    app.thinking = None;
+    app.last_computer_move = Instant::now();
+
+    let chosen = chosen.ok_or(GameError::NoAvailablePositions)?;
+    app.game.play(chosen)?;
+    Ok(Some(chosen))
+}
+
+#[cfg(feature = "serde")]
+fn saved_game_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new().add_filter("Saved game", &["json"])
+}
+
+// The file dialogs block the window until they are closed, like a modal dialog would
+#[cfg(feature = "serde")]
+fn draw_save_and_load(ui: &mut egui::Ui, app: &mut TicTacToeApp) {
+    ui.horizontal(|ui| {
+        if ui.button("Save game").clicked() {
+            let chosen = saved_game_dialog()
+                .set_file_name(DEFAULT_SAVE_NAME)
+                .save_file();
+            if let Some(path) = chosen {
+                app.save_status = match save_game(&path, app.game.board()) {
+                    Ok(()) => format!("Saved to {}", path.display()),
+                    Err(error) => error,
+                };
+            }
+        }
+
+        if ui.button("Load game").clicked() {
+            if let Some(path) = saved_game_dialog().pick_file() {
+                app.save_status = match load_game(&path) {
+                    Ok(game_board) => {
+                        app.matchup = Matchup::of(&game_board);
+                        app.game = Game::new(game_board);
+                        app.slide_from = None;
+                        app.explanation.clear();
+                        app.hint.clear();
+                        format!("Loaded {}", path.display())
+                    }
+                    Err(error) => error,
+                };
+            }
+        }
+    });
+
+    if !app.save_status.is_empty() {
+        ui.label(&app.save_status);
+    }
+}
+
+// Three Men's Morris only has its rules for the classic board
+fn morris_allowed(app: &TicTacToeApp) -> bool {
+    app.size == GameBoard::CLASSIC_SIZE
+        && app.win_length.is_none()
+        && !app.cube
+        && !app.wild
+        && !app.gravity
+        && app.notakto.is_none()
+}
+
+// Speedruns only, so that every time on a leaderboard was got the same way
+fn classic_rules(app: &TicTacToeApp) -> bool {
+    app.size == GameBoard::CLASSIC_SIZE
+        && app.win_length.is_none()
+        && !(app.cube || app.misere || app.wild || app.morris || app.gravity)
+        && app.notakto.is_none()
+        && app.blocked == 0
+}
+
+fn notakto_allowed(app: &TicTacToeApp) -> bool {
+    !app.cube && !app.wild && !app.gravity
+}
 
-        draw_status_message(ui, &app.game_end_message);
+fn new_game(app: &mut TicTacToeApp) {
+    let mut game_board = app
+        .matchup
+        .new_game_board(game_setup(app), app.size)
+        .with_cube(app.cube)
+        .with_misere(app.misere)
+        .with_wild(app.wild)
+        .with_morris(app.morris && morris_allowed(app))
+        .with_gravity(app.gravity && !app.cube)
+        .with_notakto(app.notakto.filter(|_| notakto_allowed(app)))
+        .with_blocked_cells(app.blocked);
+    if let Some(win_length) = app.win_length {
+        game_board = game_board.with_win_length(win_length);
+    }
+    if let Some(hint_budget) = app.hint_budget {
+        game_board = game_board.with_hint_budget(hint_budget);
+    }
+
+    app.game = Game::new(game_board);
+    app.slide_from = None;
+    app.explanation.clear();
+    app.hint.clear();
+    app.game_end_message = end_of_game_text(&app.game);
+}
+
+// The clock keeps running between games until the computer is beaten. Picking another opponent
+// or other rules ends the run.
+fn draw_speedrun(ui: &mut egui::Ui, app: &mut TicTacToeApp) {
+    if app.speedrun.as_ref().is_some_and(|run| {
+        app.matchup != Matchup::HumanVsComputer
+            || run.difficulty() != app.strategy.difficulty
+            || !classic_rules(app)
+    }) {
+        app.speedrun = None;
+    }
+
+    if let Some(run) = &app.speedrun {
+        let clock = format!(
+            "⏱ {}, game {} against the {} computer",
+            run.clock(),
+            run.games(),
+            run.difficulty().name()
+        );
+        ui.horizontal(|ui| {
+            ui.label(clock);
+            if ui.button("Give up run").clicked() {
+                app.speedrun = None;
+            }
+        });
+        ui.ctx().request_repaint_after(SPEEDRUN_CLOCK_INTERVAL);
+        return;
+    }
+
+    if !app.speedrun_result.is_empty() {
+        ui.label(&app.speedrun_result);
+    }
+
+    let allowed = app.matchup == Matchup::HumanVsComputer
+        && Speedrun::allowed(app.strategy.difficulty)
+        && classic_rules(app);
+    if ui
+        .add_enabled(allowed, egui::Button::new("Start speedrun"))
+        .on_disabled_hover_text(
+            "Speedruns are against the easy or medium computer, on the classic board and rules",
+        )
+        .clicked()
+    {
+        new_game(app);
+        app.speedrun = Some(Speedrun::new(app.strategy.difficulty));
+        app.speedrun_result.clear();
+    }
+}
+
+fn draw_undo_button(ui: &mut egui::Ui, app: &mut TicTacToeApp) {
+    let board_before_undo = app.game.board().clone();
+
+    if ui.button("Undo").clicked() && !app.game.undo_turn().is_empty() {
+        app.slide_from = None;
+        app.hint.clear();
+        app.explanation.clear();
+        update_scoreboard(app, &board_before_undo);
+    }
+}
+
+// Counts a game when a move finishes it and takes it back off when that move is undone. Games
+// the computer plays against itself are only watched, so they are not counted.
+fn update_scoreboard(app: &mut TicTacToeApp, board_before: &GameBoard) {
+    if app.matchup == Matchup::ComputerVsComputer {
+        return;
+    }
+
+    let strategy = app.strategy;
+    let saved = match (board_before.is_game_over(), app.game.board().is_game_over()) {
+        (false, true) => {
+            app.scoreboard.record(app.game.board());
+            if let Some(run) = &mut app.speedrun {
+                if let Some(time) = run.game_finished(app.game.board()) {
+                    let placing = stats::record_run_time(run.difficulty(), time);
+                    app.speedrun_result = run.result_text(time, &placing);
+                    app.speedrun = None;
+                }
+            }
+            stats::record_game(app.game.board(), strategy)
+        }
+        (true, false) => {
+            app.scoreboard.retract(board_before);
+            stats::retract_game(board_before, strategy)
+        }
+        _ => Ok(()),
+    };
+
+    match saved.and_then(|()| LifetimeStats::load()) {
+        Ok(stats) => app.lifetime_stats = stats,
+        Err(error) => println!("\nStatistics not saved: {error}"),
+    }
+}
+
+fn draw_statistics_window(ctx: &egui::Context, statistics: &mut Option<LifetimeStats>) {
+    let Some(stats) = statistics else {
+        return;
+    };
+
+    let mut open = true;
+    egui::Window::new("Statistics")
+        .open(&mut open)
+        .show(ctx, |ui| {
+            egui::Grid::new("statistics").striped(true).show(ui, |ui| {
+                for heading in ["Difficulty", "Games", "Wins", "Unaided", "Losses", "Draws"] {
+                    ui.strong(heading);
+                }
+                ui.end_row();
+
+                let rows = Difficulty::ALL
+                    .into_iter()
+                    .map(|difficulty| (difficulty.name(), stats.for_difficulty(difficulty)))
+                    .chain([("total", stats.total())]);
+
+                for (name, record) in rows {
+                    ui.label(name);
+                    let counts = [
+                        record.games(),
+                        record.wins,
+                        record.unaided_wins(),
+                        record.losses,
+                        record.draws,
+                    ];
+                    for count in counts {
+                        ui.label(count.to_string());
+                    }
+                    ui.end_row();
+                }
+            });
+
+            for difficulty in Difficulty::ALL {
+                let best_times: Vec<String> = stats
+                    .best_times(difficulty)
+                    .iter()
+                    .map(|&time| format_run_time(time))
+                    .collect();
+                if !best_times.is_empty() {
+                    ui.label(format!(
+                        "Fastest {} speedruns: {}",
+                        difficulty.name(),
+                        best_times.join(", ")
+                    ));
+                }
+            }
+        });
+
+    if !open {
+        *statistics = None;
+    }
+}
+
+fn draw_scoreboard(ui: &mut egui::Ui, app: &mut TicTacToeApp) {
+    // Read from the file each time, so games finished in the CLI meanwhile are included
+    if ui.button("Statistics").clicked() {
+        match LifetimeStats::load() {
+            Ok(stats) => app.statistics = Some(stats),
+            Err(error) => println!("\nCould not load statistics: {error}"),
+        }
+    }
+
+    let scoreboard = &app.scoreboard;
+    ui.heading("Scoreboard");
+
+    if scoreboard.games() == 0 {
+        ui.label("No games finished yet");
+        return;
+    }
+
+    egui::Grid::new("scoreboard").striped(true).show(ui, |ui| {
+        for heading in ["Player", "Wins", "Losses", "Draws"] {
+            ui.strong(heading);
+        }
+        ui.end_row();
+
+        for entry in scoreboard.entries() {
+            ui.label(&entry.player);
+            ui.label(entry.wins.to_string());
+            ui.label(entry.losses.to_string());
+            ui.label(entry.draws.to_string());
+            ui.end_row();
+        }
     });
+}
 
-    handle_turn_result(&turn_result, &app.game_board)
+fn draw_thinking_indicator(ui: &mut egui::Ui, reduced_motion: bool) {
+    ui.horizontal(|ui| {
+        if !reduced_motion {
+            ui.spinner();
+        }
+        ui.label("The computer is thinking…");
+    });
 }
 
-fn draw_status_message(ui: &mut egui::Ui, game_end_message: &str) {
+fn draw_status_message(ui: &mut egui::Ui, game_end_message: &str, palette: Palette) {
     ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
         ui.label(
             egui::RichText::new(game_end_message)
                 .size(24.0)
                 .strong()
-                .color(egui::Color32::from_rgb(100, 100, 255)),
+                .color(color32(palette.status_color())),
         );
     });
 }
 
-fn handle_turn_result(turn_result: &Result<(), String>, game_board: &GameBoard) -> String {
-    match turn_result {
-        Ok(()) => game_board.end_of_game_text(),
+fn draw_difficulty_selector(ui: &mut egui::Ui, strategy: &mut &'static StrategyEntry) {
+    egui::ComboBox::from_label("Difficulty")
+        .selected_text(strategy.difficulty.name())
+        .show_ui(ui, |ui| {
+            for difficulty in Difficulty::ALL {
+                if ui
+                    .selectable_label(strategy.difficulty == difficulty, difficulty.name())
+                    .clicked()
+                {
+                    *strategy = registry::strategy_for(difficulty);
+                }
+            }
+        });
+}
+
+fn draw_strategy_selector(ui: &mut egui::Ui, strategy: &mut &'static StrategyEntry) {
+    egui::ComboBox::from_label("Computer strategy")
+        .selected_text(strategy.name)
+        .show_ui(ui, |ui| {
+            for entry in STRATEGIES {
+                ui.selectable_value(strategy, entry, entry.name)
+                    .on_hover_text(entry.description);
+            }
+        });
+}
+
+fn draw_numbering_selector(ui: &mut egui::Ui, numbering: &mut Numbering) {
+    egui::ComboBox::from_label("Position numbering")
+        .selected_text(numbering.name())
+        .show_ui(ui, |ui| {
+            for option in Numbering::ALL {
+                ui.selectable_value(numbering, option, option.name());
+            }
+        });
+}
+
+fn draw_palette_selector(ui: &mut egui::Ui, palette: &mut Palette) {
+    egui::ComboBox::from_label("Colours")
+        .selected_text(palette.name())
+        .show_ui(ui, |ui| {
+            for option in Palette::ALL {
+                ui.selectable_value(palette, option, option.name());
+            }
+        });
+}
+
+fn draw_game_setup(ui: &mut egui::Ui, app: &mut TicTacToeApp) {
+    let choice_text = |choice: Option<&'static str>| choice.unwrap_or("Random");
+
+    egui::ComboBox::from_label("Your piece")
+        .selected_text(choice_text(app.setup_piece.map(Piece::name)))
+        .show_ui(ui, |ui| {
+            for option in [None, Some(Piece::X), Some(Piece::O)] {
+                ui.selectable_value(
+                    &mut app.setup_piece,
+                    option,
+                    choice_text(option.map(Piece::name)),
+                );
+            }
+        });
+
+    let first_text = |human_first: Option<bool>| {
+        choice_text(human_first.map(|human_first| if human_first { "You" } else { "Computer" }))
+    };
+
+    egui::ComboBox::from_label("First move")
+        .selected_text(first_text(app.setup_human_first))
+        .show_ui(ui, |ui| {
+            for option in [None, Some(true), Some(false)] {
+                ui.selectable_value(&mut app.setup_human_first, option, first_text(option));
+            }
+        });
+
+    ui.horizontal(|ui| {
+        ui.label("Your name");
+        ui.text_edit_singleline(&mut app.setup_name);
+    });
+
+    egui::ComboBox::from_label("Opponent")
+        .selected_text(app.opponent.map_or("Unnamed", OpponentEntry::name))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut app.opponent, None, "Unnamed");
+
+            for opponent in OPPONENTS {
+                if ui
+                    .selectable_label(
+                        app.opponent
+                            .is_some_and(|chosen| chosen.name() == opponent.name()),
+                        opponent.name(),
+                    )
+                    .on_hover_text(
+                        opponent.scouting_report(app.lifetime_stats.against(opponent.name())),
+                    )
+                    .clicked()
+                {
+                    app.opponent = Some(opponent);
+                    app.strategy = &opponent.strategy;
+                }
+            }
+        });
+}
+
+// Only while the chosen opponent is still the one playing
+fn draw_opponent_remark(ui: &mut egui::Ui, app: &TicTacToeApp) {
+    let Some(opponent) = app.opponent else {
+        return;
+    };
+
+    if app.matchup == Matchup::HumanVsComputer && app.strategy == &opponent.strategy {
+        if is_fresh_game(app.game.board()) {
+            ui.label(opponent.scouting_report(app.lifetime_stats.against(opponent.name())));
+        }
+
+        ui.label(format!(
+            "{}: \"{}\"",
+            opponent.name(),
+            opponent.remark(app.game.board())
+        ));
+    }
+}
+
+fn game_setup(app: &TicTacToeApp) -> GameBoardBuilder {
+    let mut setup = GameBoardBuilder::new();
+
+    if let Some(piece) = app.setup_piece {
+        setup = setup.human_piece(piece);
+    }
+
+    if let Some(human_first) = app.setup_human_first {
+        setup = setup.human_first(human_first);
+    }
+
+    if !app.setup_name.trim().is_empty() {
+        setup = setup.human_name(app.setup_name.trim());
+    }
+
+    if let Some(opponent) = app.opponent {
+        setup = setup.computer_name(opponent.name());
+    }
+
+    setup
+}
+
+// Returns whether a different size was picked. A win length that no longer fits is dropped.
+fn draw_size_selector(ui: &mut egui::Ui, size: &mut usize, win_length: &mut Option<usize>) -> bool {
+    let mut changed = false;
+    let size_name = |size: usize| format!("{size} x {size}");
+
+    egui::ComboBox::from_label("Board size")
+        .selected_text(size_name(*size))
+        .show_ui(ui, |ui| {
+            for option in GameBoard::SIZES {
+                changed |= ui
+                    .selectable_value(size, option, size_name(option))
+                    .changed();
+            }
+        });
+
+    if win_length.is_some_and(|win_length| win_length >= *size) {
+        *win_length = None;
+    }
+
+    changed
+}
+
+// Returns whether a different win length was picked
+fn draw_win_length_selector(
+    ui: &mut egui::Ui,
+    size: usize,
+    win_length: &mut Option<usize>,
+) -> bool {
+    let mut changed = false;
+    let win_length_name = |win_length: Option<usize>| {
+        win_length.map_or_else(|| "Full line".to_string(), |k| format!("{k} in a row"))
+    };
 
-        Err(error) => {
-            if error == "exit" {
-                "\nExiting the game".to_string()
-            } else {
-                // Output the error condition and continue looping
-                println!("\nError: {error:?}");
-                game_board.end_of_game_text()
+    egui::ComboBox::from_label("To win")
+        .selected_text(win_length_name(*win_length))
+        .show_ui(ui, |ui| {
+            let options = [None]
+                .into_iter()
+                .chain((GameBoard::CLASSIC_SIZE..size).map(Some));
+
+            for option in options {
+                changed |= ui
+                    .selectable_value(win_length, option, win_length_name(option))
+                    .changed();
+            }
+        });
+
+    changed
+}
+
+// Returns whether a different number of hints was picked
+fn draw_hint_budget_selector(ui: &mut egui::Ui, hint_budget: &mut Option<usize>) -> bool {
+    let mut changed = false;
+    let hint_budget_name = |hint_budget: Option<usize>| {
+        hint_budget.map_or_else(|| "Unlimited".to_string(), |n| n.to_string())
+    };
+
+    egui::ComboBox::from_label("Hints per game")
+        .selected_text(hint_budget_name(*hint_budget))
+        .show_ui(ui, |ui| {
+            for option in [None, Some(0), Some(1), Some(2), Some(3)] {
+                changed |= ui
+                    .selectable_value(hint_budget, option, hint_budget_name(option))
+                    .changed();
+            }
+        });
+
+    changed
+}
+
+// Returns whether a different number of blocked cells was picked
+fn draw_blocked_cells_selector(ui: &mut egui::Ui, blocked: &mut usize) -> bool {
+    let mut changed = false;
+    let blocked_name = |blocked: usize| match blocked {
+        0 => "None".to_string(),
+        1 => "1 cell".to_string(),
+        blocked => format!("{blocked} cells"),
+    };
+
+    egui::ComboBox::from_label("Blocked at random")
+        .selected_text(blocked_name(*blocked))
+        .show_ui(ui, |ui| {
+            for option in [0].into_iter().chain(GameBoard::BLOCKED_CELLS) {
+                changed |= ui
+                    .selectable_value(blocked, option, blocked_name(option))
+                    .changed();
             }
+        });
+
+    changed
+}
+
+// Returns whether Notakto was switched on or off or given a different number of boards
+fn draw_notakto_selector(ui: &mut egui::Ui, notakto: &mut Option<usize>) -> bool {
+    let mut changed = false;
+    let notakto_name = |notakto: Option<usize>| match notakto {
+        None => "Off".to_string(),
+        Some(1) => "1 board".to_string(),
+        Some(boards) => format!("{boards} boards"),
+    };
+
+    egui::ComboBox::from_label("Notakto: both place X, the last line loses")
+        .selected_text(notakto_name(*notakto))
+        .show_ui(ui, |ui| {
+            let options = [None]
+                .into_iter()
+                .chain(GameBoard::NOTAKTO_BOARDS.map(Some));
+
+            for option in options {
+                changed |= ui
+                    .selectable_value(notakto, option, notakto_name(option))
+                    .changed();
+            }
+        });
+
+    changed
+}
+
+// Returns whether a different matchup was picked
+fn draw_matchup_selector(ui: &mut egui::Ui, matchup: &mut Matchup) -> bool {
+    let mut changed = false;
+
+    egui::ComboBox::from_label("Players")
+        .selected_text(matchup.name())
+        .show_ui(ui, |ui| {
+            for option in Matchup::ALL {
+                changed |= ui
+                    .selectable_value(matchup, option, option.name())
+                    .changed();
+            }
+        });
+
+    changed
+}
+
+// For the player whose turn it is, so in a hotseat game either human can hand over their seat
+fn draw_swap_player_button(ui: &mut egui::Ui, game: &mut Game) {
+    let next_up = game.board().next_up;
+    let button_text = match game.board().player_for_id(next_up) {
+        Player::Human(_) => "Let the computer finish",
+        Player::Computer(_) => "Take back control",
+    };
+
+    ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+        if ui.button(button_text).clicked() {
+            game.swap_player(next_up);
+        }
+    });
+}
+
+fn draw_grid_lines(painter: &egui::Painter, cell_size: f32, size: usize, layers: usize) {
+    let board_size = usize_to_f32(size).unwrap_or(3.0) * cell_size;
+
+    for layer in 0..layers {
+        let Some(top) = layer_top(layer, cell_size, size) else {
+            continue;
+        };
+
+        for i in 1..size {
+            let Some(offset) = usize_to_f32(i).map(|i| i * cell_size) else {
+                continue;
+            };
+            // Vertical lines
+            painter.line_segment(
+                [
+                    egui::pos2(offset, top),
+                    egui::pos2(offset, top + board_size),
+                ],
+                (2.0, egui::Color32::BLACK),
+            );
+            // Horizontal lines
+            painter.line_segment(
+                [
+                    egui::pos2(0.0, top + offset),
+                    egui::pos2(board_size, top + offset),
+                ],
+                (2.0, egui::Color32::BLACK),
+            );
+        }
+
+        // Stacked layers need their outline too, to tell them apart
+        if layers > 1 {
+            painter.rect_stroke(
+                egui::Rect::from_min_size(egui::pos2(0.0, top), egui::Vec2::splat(board_size)),
+                0.0,
+                (1.0, egui::Color32::GRAY),
+            );
         }
     }
 }
 
-fn draw_grid_lines(painter: &egui::Painter, cell_size: f32, board_size: f32) {
-    for i in 1i16..3 {
-        let offset = f32::from(i) * cell_size;
-        // Vertical lines
-        painter.line_segment(
-            [egui::pos2(offset, 0.0), egui::pos2(offset, board_size)],
-            (2.0, egui::Color32::BLACK),
+// Where a layer of a cube starts, each one below the last
+fn layer_top(layer: usize, cell_size: f32, size: usize) -> Option<f32> {
+    let layer = usize_to_f32(layer)?;
+    let layer_size = usize_to_f32(size)? * cell_size;
+
+    Some(layer * (layer_size + LAYER_GAP))
+}
+
+// Greys out Notakto boards that already have a line
+fn draw_dead_boards(game_board: &GameBoard, painter: &egui::Painter, cell_size: f32) {
+    let size = game_board.size();
+    let width = usize_to_f32(size).unwrap_or(3.0) * cell_size;
+
+    for layer in (0..game_board.layers()).filter(|&layer| game_board.is_board_dead(layer)) {
+        let Some(top) = layer_top(layer, cell_size, size) else {
+            continue;
+        };
+
+        painter.rect_filled(
+            egui::Rect::from_min_size(egui::pos2(0.0, top), egui::Vec2::splat(width)),
+            0.0,
+            egui::Color32::from_black_alpha(64),
         );
-        // Horizontal lines
-        painter.line_segment(
-            [egui::pos2(0.0, offset), egui::pos2(board_size, offset)],
-            (2.0, egui::Color32::BLACK),
+    }
+}
+
+fn board_extent(cell_size: f32, size: usize, layers: usize) -> egui::Vec2 {
+    let width = usize_to_f32(size).unwrap_or(3.0) * cell_size;
+    let height = layer_top(layers - 1, cell_size, size).unwrap_or(0.0) + width;
+
+    egui::vec2(width, height)
+}
+
+fn cell_rect(position: usize, cell_size: f32, size: usize) -> Option<egui::Rect> {
+    let index = position - 1;
+    let column_position = usize_to_f32(index % size)?;
+    let row_position = usize_to_f32(index / size % size)?;
+    let top = layer_top(index / (size * size), cell_size, size)?;
+
+    Some(egui::Rect::from_min_size(
+        egui::pos2(column_position * cell_size, top + row_position * cell_size),
+        egui::Vec2::splat(cell_size),
+    ))
+}
+
+// A premove, the piece picked up to slide, or where a dropped piece would land
+fn draw_highlight(
+    position: usize,
+    painter: &egui::Painter,
+    cell_size: f32,
+    size: usize,
+    palette: Palette,
+) {
+    if let Some(cell) = cell_rect(position, cell_size, size) {
+        painter.rect_stroke(
+            cell.shrink(4.0),
+            4.0,
+            egui::Stroke::new(3.0, color32(palette.status_color())),
         );
     }
 }
 
-fn draw_x(painter: &egui::Painter, center: egui::Pos2) {
+fn draw_threats(game_board: &GameBoard, painter: &egui::Painter, cell_size: f32, palette: Palette) {
+    let threats = threats(game_board);
+
+    for position in game_board.positions() {
+        let x_threat = threats.contains(&(position, game_model::Piece::X));
+        let o_threat = threats.contains(&(position, game_model::Piece::O));
+
+        // Tint in the colour of the piece that would complete a line, a blend if both would
+        let tint = match (x_threat, o_threat) {
+            (true, true) => color32(palette.shared_tint()),
+            (true, false) => color32(palette.piece_tint(game_model::Piece::X)),
+            (false, true) => color32(palette.piece_tint(game_model::Piece::O)),
+            (false, false) => continue,
+        };
+
+        let Some(cell) = cell_rect(position, cell_size, game_board.size()) else {
+            continue;
+        };
+
+        painter.rect_filled(cell, 0.0, tint);
+    }
+}
+
+fn draw_x(painter: &egui::Painter, center: egui::Pos2, radius: f32, stroke: egui::Stroke) {
     painter.line_segment(
         [
-            egui::pos2(center.x - 20.0, center.y - 20.0),
-            egui::pos2(center.x + 20.0, center.y + 20.0),
+            egui::pos2(center.x - radius, center.y - radius),
+            egui::pos2(center.x + radius, center.y + radius),
         ],
-        (2.0, egui::Color32::RED),
+        stroke,
     );
     painter.line_segment(
         [
-            egui::pos2(center.x + 20.0, center.y - 20.0),
-            egui::pos2(center.x - 20.0, center.y + 20.0),
+            egui::pos2(center.x + radius, center.y - radius),
+            egui::pos2(center.x - radius, center.y + radius),
         ],
-        (2.0, egui::Color32::RED),
+        stroke,
     );
 }
 
-fn draw_o(painter: &egui::Painter, center: egui::Pos2) {
-    painter.circle_stroke(center, 20.0, (2.0, egui::Color32::BLUE));
+fn draw_o(painter: &egui::Painter, center: egui::Pos2, radius: f32, stroke: egui::Stroke) {
+    painter.circle_stroke(center, radius, stroke);
 }
 
-fn draw_board_contents(game_board: &GameBoard, painter: &egui::Painter, cell_size: f32) {
-    // Draw X and O marks on the board
-    for row in 0..3 {
-        for col in 0..3 {
-            let row_id: usize = match row {
-                0 => 1,
-                1 => 4,
-                2 => 7,
-                _ => 0,
-            };
-            let position: usize = row_id + col;
-            let Some(mark) = game_board.get_cell_at_position(position) else {
-                break;
-            };
+fn draw_board_contents(
+    game_board: &GameBoard,
+    painter: &egui::Painter,
+    cell_size: f32,
+    palette: Palette,
+    distinct_shapes: bool,
+    // A piece still falling into place, with the share of the way down it has left to go
+    falling: Option<(usize, f32)>,
+) {
+    // With distinct shapes the pieces differ by weight as well as colour
+    let (x_width, o_width) = if distinct_shapes {
+        (8.0, 2.0)
+    } else {
+        (2.0, 2.0)
+    };
 
-            match mark {
-                CellState::Empty => (),
-                CellState::Occupied(piece) => {
-                    let Some(column_position) = usize_to_f32(col) else {
-                        break;
-                    };
-
-                    let Some(row_position) = usize_to_f32(row) else {
-                        break;
-                    };
-
-                    let center = egui::pos2(
-                        column_position.mul_add(cell_size, cell_size / 2.0),
-                        row_position.mul_add(cell_size, cell_size / 2.0),
-                    );
-
-                    match piece {
-                        game_model::Piece::X => {
-                            draw_x(painter, center);
-                        }
-                        game_model::Piece::O => {
-                            draw_o(painter, center);
-                        }
-                    }
-                }
+    // Pieces are sized to the cells, so they shrink on larger boards
+    let radius = cell_size / 5.0;
+
+    // Draw X and O marks on the board, and shade blocked cells
+    for position in game_board.positions() {
+        let Some(cell) = cell_rect(position, cell_size, game_board.size()) else {
+            continue;
+        };
+        let piece = match game_board.get_cell_at_position(position) {
+            Some(CellState::Occupied(piece)) => piece,
+            Some(CellState::Blocked) => {
+                painter.rect_filled(
+                    cell.shrink(cell_size / 10.0),
+                    cell_size / 20.0,
+                    egui::Color32::from_black_alpha(128),
+                );
+                continue;
+            }
+            _ => continue,
+        };
+        // Falling from the top of the board
+        let center = match falling {
+            Some((falling_position, left_to_fall)) if falling_position == position => {
+                cell.center() - egui::vec2(0.0, cell.top() * left_to_fall)
+            }
+            _ => cell.center(),
+        };
+
+        match piece {
+            game_model::Piece::X => {
+                draw_x(
+                    painter,
+                    center,
+                    radius,
+                    egui::Stroke::new(x_width, color32(palette.piece_color(game_model::Piece::X))),
+                );
+            }
+            game_model::Piece::O => {
+                draw_o(
+                    painter,
+                    center,
+                    radius,
+                    egui::Stroke::new(o_width, color32(palette.piece_color(game_model::Piece::O))),
+                );
             }
         }
     }
 }
 
-impl GameBoard {
-    fn update_board_based_on_response(
-        &mut self,
-        response: &Response,
-        cell_size: f32,
-    ) -> Result<(), String> {
-        let Some(pos) = response.hover_pos() else {
-            return Ok(());
-        };
+fn update_board_based_on_response(
+    game: &mut Game,
+    response: &Response,
+    cell_size: f32,
+    wild_piece: Piece,
+    slide_from: &mut Option<usize>,
+) -> Result<(), GameError> {
+    let game_board = game.board();
+    let Some(position) = response
+        .hover_pos()
+        .and_then(|pos| position_at(pos, cell_size, game_board.size(), game_board.layers()))
+    else {
+        return Ok(());
+    };
 
-        let Some(col) = f32_to_usize((pos.x / cell_size).floor()) else {
+    // The first click picks up one of the mover's pieces, the next slides it there
+    if game_board.must_slide() {
+        let own_piece = CellState::Occupied(*game_board.player_for_id(game_board.next_up).piece());
+        if game_board.get_cell_at_position(position) == Some(&own_piece) {
+            *slide_from = Some(position);
             return Ok(());
-        };
+        }
 
-        let Some(row) = f32_to_usize((pos.y / cell_size).floor()) else {
-            return Ok(());
-        };
+        let piece = game_board.own_move(position).piece;
+        return slide_from
+            .take()
+            .map_or(Ok(()), |from| game.play(Move::slide(from, position, piece)));
+    }
 
-        let row_id: usize = match row {
-            0 => 1,
-            1 => 4,
-            2 => 7,
-            _ => 0,
-        };
+    let chosen = human_move(game_board, position, wild_piece);
+    if game_board.is_legal_move(chosen.position) {
+        game.play(chosen)
+    } else {
+        Ok(())
+    }
+}
 
-        let position = row_id + col;
+// A click places the selected piece in wild games, otherwise the player's own. Under gravity
+// it drops down the clicked column.
+fn human_move(game_board: &GameBoard, position: usize, wild_piece: Piece) -> Move {
+    let piece = if game_board.is_wild() {
+        wild_piece
+    } else {
+        *game_board.player_for_id(game_board.next_up).piece()
+    };
 
-        if position <= Self::POSITIONS.count()
-            && self.get_cell_at_position(position) == Some(&CellState::Empty)
-        {
-            self.play_next_up_at_position(position)
-        } else {
-            Ok(())
-        }
+    game_board
+        .coordinate_at_position(position)
+        .filter(|_| game_board.is_gravity())
+        .and_then(|coordinate| game_board.drop_move(coordinate.col() + 1, piece).ok())
+        .unwrap_or_else(|| Move::place(position, piece))
+}
+
+fn position_at(pos: egui::Pos2, cell_size: f32, size: usize, layers: usize) -> Option<usize> {
+    // Each layer's rows, then the gap before the next layer
+    let layer_height = usize_to_f32(size)? * cell_size + LAYER_GAP;
+    let layer = f32_to_usize((pos.y / layer_height).floor())?;
+    let layer_y = pos.y - layer_top(layer, cell_size, size)?;
+
+    let col = f32_to_usize((pos.x / cell_size).floor())?;
+    let row = f32_to_usize((layer_y / cell_size).floor())?;
+
+    if layer >= layers || row >= size || col >= size {
+        return None;
     }
+
+    Some((layer * size + row) * size + col + 1)
 }
 
 #[allow(clippy::cast_precision_loss)]
@@ -226,6 +1680,10 @@ const fn usize_to_f32(usize_value: usize) -> Option<f32> {
     }
 }
 
+const fn color32((red, green, blue): (u8, u8, u8)) -> egui::Color32 {
+    egui::Color32::from_rgb(red, green, blue)
+}
+
 #[allow(clippy::cast_precision_loss)]
 #[allow(clippy::cast_possible_truncation)]
 #[allow(clippy::cast_sign_loss)]
@@ -239,30 +1697,27 @@ const fn f32_to_usize(f32_value: f32) -> Option<usize> {
     }
 }
 
-impl GameBoard {
-    fn end_of_game_text(&self) -> String {
-        match self.game_state() {
-            GameState::Winner(player) => match player {
-                Player::Human(piece) => format!("You won!\n{piece}"),
-                Player::Computer(piece) => format!("The computer won!\n{piece}"),
-            },
-            GameState::Draw => "\nThis game results in a draw.".to_string(),
-            GameState::InProgress => {
-                let mut text = String::new();
-                text.push_str("The game is still in progress.\n");
-                text.push_str("It's ");
-                let next_player = self.player_for_id(self.next_up);
-                match next_player {
-                    Player::Human(piece) => {
-                        text.push_str(format!("your turn to play {piece}").as_str());
-                    }
-                    Player::Computer(piece) => {
-                        text.push_str(format!("the computer's turn to play {piece}").as_str());
-                    }
-                };
-                text.push('\n');
-                text
-            }
+fn end_of_game_text(game: &Game) -> String {
+    let game_board = game.board();
+    match (game_board.game_state(), game.result_text()) {
+        (GameState::Winner(..), Some(result)) if game_board.is_notakto() => result,
+        (GameState::Winner(player, _), Some(result)) => format!("{result}\n{}", player.piece()),
+        (GameState::Draw, Some(result)) => format!("\n{result}"),
+        _ => {
+            let mut text = String::new();
+            text.push_str("The game is still in progress.\n");
+            text.push_str("It's ");
+            let next_player = game_board.player_for_id(game_board.next_up);
+            match next_player {
+                Player::Human(piece) => {
+                    text.push_str(format!("your turn to play {piece}").as_str());
+                }
+                Player::Computer(piece) => {
+                    text.push_str(format!("the computer's turn to play {piece}").as_str());
+                }
+            };
+            text.push('\n');
+            text
         }
     }
 }
@@ -0,0 +1,216 @@
+use crate::arbiter::EngineArgs;
+use crate::bench::BenchArgs;
+use crate::cli::CliArgs;
+use crate::dataset::DatasetArgs;
+use crate::exit_code::ExitReason;
+use crate::local_data::DataArgs;
+use crate::registry::{OPPONENTS, STRATEGIES, VARIANTS};
+use crate::simulate::SimulateArgs;
+use crate::stats::{stats_path, StatsArgs};
+#[cfg(feature = "tui")]
+use crate::tui::TuiArgs;
+use crate::tuner::TuneArgs;
+use crate::watch::WatchArgs;
+use clap::{Arg, Command};
+
+pub fn version_string() -> String {
+    format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+}
+
+pub fn info_main() {
+    let profile = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+
+    println!("{} ({profile} build)", version_string());
+
+    let optional = [
+        ("tui", cfg!(feature = "tui")),
+        ("gui", cfg!(feature = "gui")),
+    ];
+    let mut frontends = vec!["cli"];
+    frontends.extend(
+        optional
+            .iter()
+            .filter_map(|&(name, enabled)| enabled.then_some(name)),
+    );
+    frontends.extend(["engine", "watch", "ambient"]);
+    let missing: Vec<&str> = optional
+        .iter()
+        .filter_map(|&(name, enabled)| (!enabled).then_some(name))
+        .collect();
+
+    print!("\nFrontends: {}", frontends.join(", "));
+    if missing.is_empty() {
+        println!();
+    } else {
+        println!(" (built without {})", missing.join(" or "));
+    }
+
+    let onnx = if cfg!(feature = "onnx") {
+        "enabled, use --strategy onnx:<model file>"
+    } else {
+        "disabled"
+    };
+    let serde = if cfg!(feature = "serde") {
+        "enabled, the GUI can save and load games"
+    } else {
+        "disabled"
+    };
+    println!("Optional features: onnx ({onnx}), serde ({serde})");
+
+    let strategies: Vec<_> = STRATEGIES
+        .iter()
+        .map(|entry| {
+            (
+                entry.name,
+                format!("{} ({})", entry.description, entry.difficulty.name()),
+            )
+        })
+        .collect();
+    let strategies: Vec<_> = strategies
+        .iter()
+        .map(|(name, description)| (*name, description.as_str()))
+        .collect();
+    let opponents: Vec<_> = OPPONENTS
+        .iter()
+        .map(|opponent| {
+            (
+                opponent.name(),
+                format!(
+                    "{} ({})",
+                    opponent.strategy.description,
+                    opponent.strategy.difficulty.name()
+                ),
+            )
+        })
+        .collect();
+    let opponents: Vec<_> = opponents
+        .iter()
+        .map(|(name, description)| (*name, description.as_str()))
+        .collect();
+    let variants: Vec<_> = VARIANTS
+        .iter()
+        .map(|entry| match entry.option {
+            Some(option) => (entry.name, format!("{} (--{option})", entry.description)),
+            None => (entry.name, entry.description.to_string()),
+        })
+        .collect();
+    let variants: Vec<_> = variants
+        .iter()
+        .map(|(name, description)| (*name, description.as_str()))
+        .collect();
+
+    print_section("Strategies", &strategies);
+    print_section("Opponents", &opponents);
+    print_section("Variants", &variants);
+    print!("{}", mode_options());
+
+    println!("\nGame settings are command line options. The interface preset is a stored setting.");
+    match stats_path() {
+        Ok(path) => println!("Statistics file: {}", path.display()),
+        Err(error) => println!("Statistics file: {error}"),
+    }
+    println!("Manage stored data with the data command, see --help");
+}
+
+// The options of every mode, listed by info and --help
+fn mode_options() -> String {
+    [
+        clap_section::<CliArgs>("CLI options"),
+        #[cfg(feature = "tui")]
+        clap_section::<TuiArgs>("TUI options"),
+        clap_section::<SimulateArgs>("Simulate options"),
+        clap_section::<BenchArgs>("Bench options"),
+        clap_section::<EngineArgs>("Engine options"),
+        clap_section::<WatchArgs>("Watch options"),
+        clap_section::<DatasetArgs>("Export-dataset options"),
+        clap_section::<TuneArgs>("Tune options"),
+        clap_section::<StatsArgs>("Stats commands"),
+        clap_section::<DataArgs>("Data commands"),
+    ]
+    .concat()
+}
+
+// Adds every mode's options and the exit codes to --help, after the list of modes
+pub fn with_option_help(command: Command) -> Command {
+    let mut text = mode_options();
+    text.push_str("\nExit codes:\n");
+
+    for reason in ExitReason::ALL {
+        text.push_str(&format!("  {:<3} {}\n", reason as u8, reason.description()));
+    }
+
+    command.after_help(text)
+}
+
+// A section for the options of a mode, or its commands such as `stats merge <file>`
+fn clap_section<T: clap::Args>(title: &str) -> String {
+    let command = T::augment_args(Command::new(""));
+    let mut options: Vec<(String, String)> = command
+        .get_arguments()
+        .filter_map(|arg| {
+            let long = arg.get_long()?;
+            let name = match arg.get_value_names() {
+                Some([value, ..]) if arg.get_action().takes_values() => {
+                    format!("--{long} <{value}>")
+                }
+                _ => format!("--{long}"),
+            };
+
+            Some((name, arg.get_help()?.to_string()))
+        })
+        .collect();
+    options.extend(command.get_subcommands().filter_map(|subcommand| {
+        let values = subcommand
+            .get_positionals()
+            .map(|arg| format!(" <{}>", value_text(arg)));
+        let name = subcommand.get_name().to_string() + &values.collect::<String>();
+
+        Some((name, subcommand.get_about()?.to_string()))
+    }));
+    let options: Vec<_> = options
+        .iter()
+        .map(|(name, description)| (name.as_str(), description.as_str()))
+        .collect();
+
+    section(title, &options)
+}
+
+// The values a command takes, such as stats|settings|all, or else what it is called
+fn value_text(arg: &Arg) -> String {
+    let possible: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .map(|value| value.get_name().to_string())
+        .collect();
+
+    if !possible.is_empty() {
+        return possible.join("|");
+    }
+    arg.get_value_names()
+        .and_then(<[_]>::first)
+        .map_or_else(|| arg.get_id().to_string(), ToString::to_string)
+}
+
+fn print_section(title: &str, entries: &[(&str, &str)]) {
+    print!("{}", section(title, entries));
+}
+
+fn section(title: &str, entries: &[(&str, &str)]) -> String {
+    let mut text = format!("\n{title}:\n");
+
+    let name_width = entries
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+
+    for (name, description) in entries {
+        text.push_str(&format!("  {name:name_width$}  {description}\n"));
+    }
+
+    text
+}
@@ -0,0 +1,210 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A recording starts with a header line, "seed=" and the seed of every random draw, then a tab
+// and the cli options it was recorded with, each after a tab. The rest of the lines hold how long
+// the user took to answer a prompt in milliseconds, a tab, then the raw input exactly as typed.
+// Recordings made before the header was added start straight with the inputs.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct SessionHeader {
+    pub seed: u64,
+    pub options: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RecordedSession {
+    pub header: Option<SessionHeader>,
+    pub inputs: Vec<(Duration, String)>,
+}
+
+pub struct InputSession {
+    replay: Option<std::vec::IntoIter<(Duration, String)>>,
+    record: Option<File>,
+}
+
+impl InputSession {
+    pub fn new(
+        record: Option<(&str, &SessionHeader)>,
+        replay: Option<Vec<(Duration, String)>>,
+    ) -> Result<Self, String> {
+        let record = match record {
+            Some((path, header)) => {
+                let mut file =
+                    File::create(path).map_err(|error| format!("Cannot create {path}: {error}"))?;
+                writeln!(file, "{}", header_line(header))
+                    .map_err(|error| format!("Failed to record input: {error}"))?;
+                Some(file)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            replay: replay.map(Vec::into_iter),
+            record,
+        })
+    }
+
+    // None means the input has run out for good
+    pub fn read_line(&mut self) -> Result<Option<String>, String> {
+        let prompted_at = Instant::now();
+
+        let line = if let Some(replay) = &mut self.replay {
+            let Some((delay, line)) = replay.next() else {
+                return Ok(None);
+            };
+
+            thread::sleep(delay);
+
+            // Echo it, so the replayed session reads like the original one
+            println!("{line}");
+            line
+        } else {
+            let mut input = String::new();
+
+            match io::stdin().read_line(&mut input) {
+                Ok(0) => return Ok(None),
+                Ok(_) => input.trim_end_matches(['\r', '\n']).to_string(),
+                Err(_) => return Err(String::from("Failed to read line")),
+            }
+        };
+
+        if let Some(record) = &mut self.record {
+            writeln!(record, "{}\t{line}", prompted_at.elapsed().as_millis())
+                .map_err(|error| format!("Failed to record input: {error}"))?;
+        }
+
+        Ok(Some(line))
+    }
+}
+
+pub fn read_session(path: &str) -> Result<RecordedSession, String> {
+    let file = File::open(path).map_err(|error| format!("Cannot read {path}: {error}"))?;
+    parse_log(BufReader::new(file))
+}
+
+fn header_line(header: &SessionHeader) -> String {
+    let mut line = format!("seed={}", header.seed);
+    for option in &header.options {
+        line.push('\t');
+        line.push_str(option);
+    }
+    line
+}
+
+fn parse_log(log: impl BufRead) -> Result<RecordedSession, String> {
+    let mut header = None;
+    let mut inputs = Vec::new();
+
+    for (index, line) in log.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.map_err(|error| format!("Line {line_number}: {error}"))?;
+
+        if let Some(rest) = line.strip_prefix("seed=").filter(|_| index == 0) {
+            let mut fields = rest.split('\t');
+            let seed = fields.next().unwrap_or_default();
+            let seed = seed
+                .parse()
+                .map_err(|_| format!("Line {line_number}: '{seed}' is not a seed"))?;
+
+            header = Some(SessionHeader {
+                seed,
+                options: fields.map(str::to_string).collect(),
+            });
+            continue;
+        }
+
+        let Some((millis, input)) = line.split_once('\t') else {
+            return Err(format!("Line {line_number}: expected '<millis>\\t<input>'"));
+        };
+
+        let millis = millis
+            .parse()
+            .map_err(|_| format!("Line {line_number}: '{millis}' is not a duration"))?;
+
+        inputs.push((Duration::from_millis(millis), input.to_string()));
+    }
+
+    Ok(RecordedSession { header, inputs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tic_tac_toe::game_model::{GameBoard, GameBoardBuilder};
+    use tic_tac_toe::random_source::{RandomSource, SeededRandom};
+
+    #[test]
+    fn test_parse_log_keeps_raw_input() {
+        let log = "1500\t5\n20\t q \n0\t\n";
+
+        assert_eq!(
+            parse_log(log.as_bytes()),
+            Ok(RecordedSession {
+                header: None,
+                inputs: vec![
+                    (Duration::from_millis(1500), "5".to_string()),
+                    (Duration::from_millis(20), " q ".to_string()),
+                    (Duration::ZERO, String::new()),
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_log_reports_bad_lines() {
+        assert!(parse_log("5\n".as_bytes()).is_err());
+        assert!(parse_log("soon\t5\n".as_bytes()).is_err());
+        assert!(parse_log("seed=many\n".as_bytes()).is_err());
+        assert!(parse_log("10\t5\nseed=1\n".as_bytes()).is_err());
+    }
+
+    // Against a computer playing random moves, with the human playing the lowest free cell
+    fn play_session(
+        random: &mut dyn RandomSource,
+        mut next_input: impl FnMut(&GameBoard) -> Option<String>,
+    ) -> GameBoard {
+        let mut game_board = GameBoardBuilder::new().build_with_random_source(random);
+
+        while !game_board.is_game_over() {
+            let position = if game_board.is_computers_turn() {
+                game_board.random_available_position_from(random)
+            } else {
+                next_input(&game_board).and_then(|input| input.parse().ok())
+            };
+            let Some(position) = position else {
+                break;
+            };
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+
+        game_board
+    }
+
+    #[test]
+    fn test_seeded_sessions_replay_to_the_same_board() {
+        let header = SessionHeader {
+            seed: 1740,
+            options: vec!["--strategy".to_string(), "random".to_string()],
+        };
+
+        let mut log = format!("{}\n", header_line(&header));
+        let recorded = play_session(&mut SeededRandom::new(header.seed), |game_board| {
+            let input = game_board.get_available_positions().first()?.to_string();
+            log.push_str(&format!("0\t{input}\n"));
+            Some(input)
+        });
+
+        let Ok(session) = parse_log(log.as_bytes()) else {
+            panic!("The recording did not parse:\n{log}");
+        };
+        assert_eq!(session.header.as_ref(), Some(&header));
+
+        let mut inputs = session.inputs.into_iter().map(|(_, input)| input);
+        let replayed = play_session(&mut SeededRandom::new(header.seed), |_| inputs.next());
+
+        assert!(recorded.is_game_over());
+        assert_eq!(replayed, recorded);
+    }
+}
@@ -0,0 +1,22 @@
+// The game itself, with no frontend: the board and its rules, the computer players and the
+// pieces they share. The binary's CLI, GUI and tools are built on this, and other programs can
+// embed it the same way.
+mod bitboard;
+pub mod engine;
+pub mod frontend;
+pub mod game;
+pub mod game_model;
+pub mod numbering;
+pub mod patterns;
+pub mod random_source;
+#[cfg(feature = "serde")]
+pub mod saved_game;
+pub mod strategy;
+pub mod table;
+
+pub use game::{Game, GameObserver};
+pub use game_model::{
+    execute_computer_turn, CanonicalForm, CellState, Difficulty, GameBoard, GameBoardBuilder,
+    GameError, GameState, Move, Piece, Player, PlayerID,
+};
+pub use strategy::MoveStrategy;
@@ -0,0 +1,264 @@
+use crate::exit_code::ExitReason;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+pub const STATS_FILE: &str = "stats.txt";
+const SETTINGS_FILE: &str = "settings.txt";
+// Everything the game keeps on this machine, by the name used with wipe
+const FILES: [(&str, &str); 2] = [("stats", STATS_FILE), ("settings", SETTINGS_FILE)];
+
+// Without a command everything stored is exported
+#[derive(clap::Args)]
+pub struct DataArgs {
+    #[command(subcommand)]
+    command: Option<DataCommand>,
+}
+
+#[derive(clap::Subcommand)]
+pub enum DataCommand {
+    #[command(about = "Print everything stored on this machine")]
+    Export,
+    #[command(about = "Delete stored statistics, settings or both")]
+    Wipe {
+        #[arg(value_name = "data", value_parser = ["stats", "settings", "all"])]
+        data: String,
+    },
+    #[command(about = "Choose whether finished games are added to the statistics")]
+    RecordStats {
+        #[arg(value_name = "state", value_parser = ["on", "off"])]
+        state: String,
+    },
+    #[command(about = "Choose the interface preset. Zen shows just the board and a prompt")]
+    Interface {
+        #[arg(value_name = "standard|zen", value_parser = Interface::from_name)]
+        interface: Interface,
+    },
+}
+
+// How much the frontends show around the board
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Interface {
+    #[default]
+    Standard,
+    // Just the board, the turn and the prompt, nothing else
+    Zen,
+}
+
+impl Interface {
+    pub const ALL: [Self; 2] = [Self::Standard, Self::Zen];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Zen => "zen",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|interface| interface.name() == name)
+            .ok_or_else(|| format!("Unknown interface '{name}', expected standard or zen"))
+    }
+
+    pub const fn toggle_zen(self) -> Self {
+        match self {
+            Self::Standard => Self::Zen,
+            Self::Zen => Self::Standard,
+        }
+    }
+}
+
+// Everything kept in the settings file
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Settings {
+    // On unless turned off with `data record-stats off`
+    pub record_stats: bool,
+    pub interface: Interface,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            record_stats: true,
+            interface: Interface::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        data_path(SETTINGS_FILE).map_or_else(|_| Self::default(), |path| Self::load_from(&path))
+    }
+
+    // A missing or unreadable file leaves everything at its default
+    pub fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path).map_or_else(|_| Self::default(), |text| Self::parse(&text))
+    }
+
+    // Unknown names and values are skipped, so older and newer files still load
+    fn parse(text: &str) -> Self {
+        let mut settings = Self::default();
+
+        for (name, value) in text.lines().filter_map(|line| line.split_once('=')) {
+            match name.trim() {
+                "record_stats" => settings.record_stats = value.trim() != "false",
+                "interface" => {
+                    if let Ok(interface) = Interface::from_name(value.trim()) {
+                        settings.interface = interface;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        settings
+    }
+
+    fn format(&self) -> String {
+        format!(
+            "# Set with the data command\nrecord_stats={}\ninterface={}\n",
+            self.record_stats,
+            self.interface.name()
+        )
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&data_path(SETTINGS_FILE)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|error| format!("Could not create '{}': {error}", dir.display()))?;
+        }
+
+        fs::write(path, self.format())
+            .map_err(|error| format!("Could not write '{}': {error}", path.display()))
+    }
+}
+
+// Under the platform's per-user data directory
+pub fn data_path(file_name: &str) -> Result<PathBuf, String> {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+
+    let data_dir = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".local").join("share")))
+    };
+
+    data_dir
+        .map(|dir| dir.join(env!("CARGO_PKG_NAME")).join(file_name))
+        .ok_or_else(|| "Could not find a data directory to keep local data in".to_string())
+}
+
+pub fn recording_stats() -> bool {
+    Settings::load().record_stats
+}
+
+fn set_recording_stats(enabled: bool) -> Result<(), String> {
+    Settings {
+        record_stats: enabled,
+        ..Settings::load()
+    }
+    .save()
+}
+
+// Kept for the next session too, whichever frontend changed it
+pub fn set_interface(interface: Interface) -> Result<(), String> {
+    Settings {
+        interface,
+        ..Settings::load()
+    }
+    .save()
+}
+
+pub fn local_data_main(args: &DataArgs) -> ExitReason {
+    let result = match &args.command {
+        None | Some(DataCommand::Export) => export(),
+        Some(DataCommand::Wipe { data }) => FILES
+            .iter()
+            .filter(|(name, _)| data == "all" || name == data)
+            .try_for_each(|(_, file_name)| wipe(file_name)),
+        Some(DataCommand::RecordStats { state }) => set_recording_stats(state == "on"),
+        Some(DataCommand::Interface { interface }) => set_interface(*interface),
+    };
+
+    match result {
+        Ok(()) => ExitReason::Completed,
+        Err(error) => {
+            println!("{error}");
+            ExitReason::InvalidInput
+        }
+    }
+}
+
+fn export() -> Result<(), String> {
+    let settings = Settings::load();
+    println!(
+        "Recording statistics: {}",
+        if settings.record_stats { "on" } else { "off" }
+    );
+    println!("Interface: {}", settings.interface.name());
+
+    for (_, file_name) in FILES {
+        let path = data_path(file_name)?;
+
+        match fs::read_to_string(&path) {
+            Ok(text) => println!("\n{}:\n{}", path.display(), text.trim_end()),
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                println!("\n{}: not stored", path.display());
+            }
+            Err(error) => return Err(format!("Could not read '{}': {error}", path.display())),
+        }
+    }
+
+    Ok(())
+}
+
+fn wipe(file_name: &str) -> Result<(), String> {
+    let path = data_path(file_name)?;
+
+    match fs::remove_file(&path) {
+        Ok(()) => {
+            println!("Deleted {}", path.display());
+            Ok(())
+        }
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(format!("Could not delete '{}': {error}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_are_recorded_unless_turned_off() {
+        assert!(Settings::parse("").record_stats);
+        assert!(Settings::parse("record_stats=true\n").record_stats);
+        assert!(!Settings::parse("# Set with the data command\nrecord_stats=false\n").record_stats);
+    }
+
+    #[test]
+    fn test_settings_parse_back() {
+        let settings = Settings {
+            record_stats: false,
+            interface: Interface::Zen,
+        };
+
+        assert_eq!(Settings::parse(&settings.format()), settings);
+        assert_eq!(
+            Settings::parse("interface=fancy\n").interface,
+            Interface::Standard
+        );
+    }
+}
@@ -1,29 +1,183 @@
-mod game_model;
+mod ambient;
+mod arbiter;
+mod bench;
 mod cli;
+mod dataset;
+mod event_log;
+mod exit_code;
+#[cfg(feature = "gui")]
 mod gui;
+mod info;
+mod input_session;
+mod local_data;
+#[cfg(feature = "onnx")]
+mod onnx_strategy;
+mod palette;
+mod registry;
+mod selftest;
+mod simulate;
+mod speedrun;
+mod stats;
+#[cfg(feature = "tui")]
+mod tui;
+mod tuner;
+mod watch;
 
-use crate::cli::cli_main;
+use crate::ambient::ambient_main;
+use crate::arbiter::{arbiter_main, EngineArgs};
+use crate::bench::{bench_main, BenchArgs};
+use crate::cli::{cli_main, typed_options, CliArgs, CliOptions};
+use crate::dataset::{export_dataset_main, DatasetArgs};
+use crate::exit_code::ExitReason;
+#[cfg(feature = "gui")]
 use crate::gui::gui_main;
+use crate::info::{info_main, with_option_help};
+use crate::local_data::{local_data_main, DataArgs};
+use crate::selftest::selftest_main;
+use crate::simulate::{simulate_main, SimulateArgs};
+use crate::stats::{stats_main, StatsArgs};
+#[cfg(feature = "tui")]
+use crate::tui::{tui_main, TuiArgs};
+use crate::tuner::{tune_main, TuneArgs};
+use crate::watch::{watch_main, WatchArgs};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::process::ExitCode;
+use tic_tac_toe::random_source::seed_thread_random;
 
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Tic-tac-toe in the terminal or a window, against the computer or a friend"
+)]
+struct Args {
+    #[command(subcommand)]
+    mode: Mode,
+    #[arg(
+        long,
+        global = true,
+        value_name = "n",
+        help = "Seed every random choice, such as who goes first and the computer's moves, to repeat a run"
+    )]
+    seed: Option<u64>,
+}
+
+#[derive(Subcommand)]
+enum Mode {
+    #[command(visible_alias = "text", about = "Play in the terminal")]
+    Cli(Box<CliArgs>),
+    #[cfg(feature = "tui")]
+    #[command(about = "Play full screen in the terminal, moving a cursor over the board")]
+    Tui(TuiArgs),
+    #[cfg(not(feature = "tui"))]
+    #[command(about = "Play full screen in the terminal, moving a cursor over the board")]
+    Tui,
+    #[command(about = "Play in a window")]
+    Gui,
+    #[command(about = "Play another program one position per line on standard input and output")]
+    Engine(EngineArgs),
+    #[command(about = "Watch two computer strategies play a game")]
+    Watch(WatchArgs),
+    #[command(about = "Play many games between two strategies and sum up the results")]
+    Simulate(SimulateArgs),
+    #[command(about = "Time the engine's search and count the positions it looks at per second")]
+    Bench(BenchArgs),
+    #[command(about = "Computer against computer until interrupted")]
+    Ambient,
+    #[command(about = "Write every position with its perfect move as CSV")]
+    ExportDataset(DatasetArgs),
+    #[command(about = "Evolve the weighted strategy's weights by self-play")]
+    Tune(TuneArgs),
+    #[command(about = "Lifetime statistics of finished games")]
+    Stats(StatsArgs),
+    #[command(about = "Manage the statistics and settings stored on this machine")]
+    Data(DataArgs),
+    #[command(about = "Version, features, strategies and options")]
+    Info,
+    #[command(about = "Check the engine and game rules")]
+    Selftest,
+}
 
-    let usage_string = "Usage: cargo run -- {cli|gui}";
+// For cli options that clash in ways clap does not check, in the same style as the ones it does
+fn print_cli_error(message: &str) {
+    let mut command = Args::command();
+    command.build();
 
-    // The first argument (args[0]) is always the program name
-    // Actual arguments start from args[1]
-    if args.len() <= 1 {
-        println!("{usage_string}");
-        return;
+    if let Some(cli) = command.find_subcommand_mut("cli") {
+        _ = cli.error(ErrorKind::ArgumentConflict, message).print();
     }
+}
 
-    let mode = args[1].as_str();
-    match mode {
-        "cli" => cli_main(),
-        "gui" => gui_main(),
-        _ => {
-            println!("Wrong mode. Mode must be 'text' or 'gui', but got '{mode}` instead.");
-            println!("{usage_string}");
+fn main() -> ExitCode {
+    let matches = with_option_help(Args::command()).try_get_matches_from(std::env::args());
+    let args = match matches.and_then(|matches| Args::from_arg_matches(&matches)) {
+        Ok(args) => args,
+        Err(error) => {
+            // Help and the version are printed as errors too, with a successful exit code
+            _ = error.print();
+            return if error.exit_code() == 0 {
+                ExitReason::Completed
+            } else {
+                ExitReason::InvalidUsage
+            }
+            .into();
         }
+    };
+
+    if let Some(seed) = args.seed {
+        seed_thread_random(seed);
     }
+
+    let exit_reason = match args.mode {
+        Mode::Cli(cli_args) => match CliOptions::from_command_line(
+            *cli_args,
+            args.seed,
+            typed_options(std::env::args()),
+        ) {
+            Ok(mut options) => cli_main(&mut options),
+            Err(error) => {
+                print_cli_error(&error);
+                ExitReason::InvalidUsage
+            }
+        },
+        #[cfg(feature = "tui")]
+        Mode::Tui(tui_args) => tui_main(&tui_args),
+        #[cfg(not(feature = "tui"))]
+        Mode::Tui => {
+            println!("This build has no TUI, it was built without the 'tui' feature.");
+            println!(
+                "Rebuild with 'cargo run --features tui -- tui', or play in the terminal with 'cli'."
+            );
+            ExitReason::InvalidUsage
+        }
+        Mode::Engine(engine_args) => arbiter_main(&engine_args),
+        Mode::Simulate(simulate_args) => simulate_main(&simulate_args),
+        Mode::Bench(bench_args) => bench_main(&bench_args),
+        Mode::Ambient => ambient_main(),
+        Mode::Watch(watch_args) => watch_main(&watch_args),
+        #[cfg(feature = "gui")]
+        Mode::Gui => {
+            gui_main();
+            ExitReason::Completed
+        }
+        #[cfg(not(feature = "gui"))]
+        Mode::Gui => {
+            println!("This build has no GUI, it was built without the 'gui' feature.");
+            println!(
+                "Rebuild with 'cargo run --features gui -- gui', or play in the terminal with 'cli'."
+            );
+            ExitReason::InvalidUsage
+        }
+        Mode::Selftest => selftest_main(),
+        Mode::ExportDataset(dataset_args) => export_dataset_main(&dataset_args),
+        Mode::Tune(tune_args) => tune_main(&tune_args),
+        Mode::Stats(stats_args) => stats_main(&stats_args),
+        Mode::Data(data_args) => local_data_main(&data_args),
+        Mode::Info => {
+            info_main();
+            ExitReason::Completed
+        }
+    };
+
+    exit_reason.into()
 }
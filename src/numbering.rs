@@ -0,0 +1,98 @@
+// How positions are labelled to the player. Internally positions count from 1 at the top left,
+// along each row in turn, and on through each layer of a cube.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Numbering {
+    #[default]
+    Standard,
+    // Laid out like a numeric keypad, 7-8-9 on the top row. Larger boards also number the bottom
+    // row first, and each layer of a cube is numbered on from the one before.
+    Numpad,
+    ZeroIndexed,
+}
+
+impl Numbering {
+    pub const ALL: [Self; 3] = [Self::Standard, Self::Numpad, Self::ZeroIndexed];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Numpad => "numpad",
+            Self::ZeroIndexed => "zero",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|numbering| numbering.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                format!("Unknown numbering '{name}', expected one of: standard, numpad, zero")
+            })
+    }
+
+    // The label of a position on a board `size` cells wide
+    pub const fn label(self, position: usize, size: usize) -> usize {
+        match self {
+            Self::Standard => position,
+            Self::Numpad => {
+                let layer_cells = size * size;
+                let layer = (position - 1) / layer_cells;
+                let row = (position - 1) % layer_cells / size;
+                let col = (position - 1) % size;
+
+                layer * layer_cells + (size - 1 - row) * size + col + 1
+            }
+            Self::ZeroIndexed => position - 1,
+        }
+    }
+
+    // The position labelled so on a board with this many layers of `size` by `size` cells
+    pub fn position(self, label: usize, size: usize, layers: usize) -> Option<usize> {
+        (1..=layers * size * size).find(|&position| self.label(position, size) == label)
+    }
+
+    pub fn labels(self, positions: &[usize], size: usize) -> Vec<usize> {
+        let mut labels: Vec<usize> = positions
+            .iter()
+            .map(|&position| self.label(position, size))
+            .collect();
+        labels.sort_unstable();
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_round_trip() {
+        for numbering in Numbering::ALL {
+            for (size, layers) in [(3, 1), (4, 1), (3, 3)] {
+                for position in 1..=layers * size * size {
+                    assert_eq!(
+                        numbering.position(numbering.label(position, size), size, layers),
+                        Some(position)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_numpad_puts_seven_top_left() {
+        assert_eq!(Numbering::Numpad.label(1, 3), 7);
+        assert_eq!(Numbering::Numpad.label(5, 3), 5);
+        assert_eq!(Numbering::Numpad.label(9, 3), 3);
+        assert_eq!(Numbering::Numpad.position(1, 3, 1), Some(7));
+        assert_eq!(Numbering::Numpad.label(1, 4), 13);
+        assert_eq!(Numbering::Numpad.label(10, 3), 16);
+    }
+
+    #[test]
+    fn test_zero_indexed_labels() {
+        assert_eq!(Numbering::ZeroIndexed.label(1, 3), 0);
+        assert_eq!(Numbering::ZeroIndexed.position(8, 3, 1), Some(9));
+        assert_eq!(Numbering::ZeroIndexed.position(9, 3, 1), None);
+    }
+}
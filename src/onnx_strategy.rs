@@ -0,0 +1,90 @@
+use crate::registry::StrategyEntry;
+use tic_tac_toe::game_model::{CellState, Difficulty, GameBoard};
+use tic_tac_toe::strategy::MoveStrategy;
+use tract_onnx::prelude::*;
+
+// The model takes a 1x9 f32 tensor of the cells, top-left first, seen by the player to move:
+// 1 for their pieces, -1 for the opponent's and 0 for empty. It returns a score per cell, and the
+// best scoring empty cell is played.
+pub struct OnnxStrategy {
+    model: TypedRunnableModel<TypedModel>,
+}
+
+impl OnnxStrategy {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let model = tract_onnx::onnx()
+            .model_for_path(path)
+            .and_then(|model| model.with_input_fact(0, f32::fact([1, 9]).into()))
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(|error| format!("Could not load ONNX model '{path}': {error}"))?;
+
+        Ok(Self { model })
+    }
+
+    fn scores(&self, board: &GameBoard) -> TractResult<Vec<f32>> {
+        let mover = *board.player_for_id(board.next_up).piece();
+        let cells: Vec<f32> = board
+            .positions()
+            .map(|position| match board.get_cell_at_position(position) {
+                Some(CellState::Occupied(piece)) if *piece == mover => 1.0,
+                Some(CellState::Occupied(_)) => -1.0,
+                Some(CellState::Empty | CellState::Blocked) | None => 0.0,
+            })
+            .collect();
+
+        let input: Tensor = tract_ndarray::Array2::from_shape_vec((1, 9), cells)?.into();
+        let outputs = self.model.run(tvec!(input.into()))?;
+
+        Ok(outputs[0].to_array_view::<f32>()?.iter().copied().collect())
+    }
+}
+
+impl MoveStrategy for OnnxStrategy {
+    fn choose_move(&self, board: &GameBoard) -> Option<usize> {
+        if board.size() != GameBoard::CLASSIC_SIZE || board.layers() > 1 {
+            eprintln!("The ONNX model only plays on a 3x3 board");
+            return None;
+        }
+        if board.is_misere()
+            || board.is_wild()
+            || board.is_morris()
+            || board.is_gravity()
+            || !board.blocked_positions().is_empty()
+        {
+            eprintln!(
+                "The ONNX model only plays by the normal rules, not misère, wild, Three Men's Morris, gravity or blocked cells"
+            );
+            return None;
+        }
+
+        let scores = match self.scores(board) {
+            Ok(scores) => scores,
+            Err(error) => {
+                eprintln!("ONNX model failed: {error}");
+                return None;
+            }
+        };
+
+        board
+            .get_available_positions()
+            .into_iter()
+            .filter_map(|position| Some((position, *scores.get(position - 1)?)))
+            .max_by(|(_, first), (_, second)| first.total_cmp(second))
+            .map(|(position, _)| position)
+    }
+}
+
+// Models are picked at runtime, so their entries live for the rest of the program like the
+// registered ones
+pub fn load_entry(path: &str) -> Result<&'static StrategyEntry, String> {
+    let strategy: &'static OnnxStrategy = Box::leak(Box::new(OnnxStrategy::load(path)?));
+
+    Ok(Box::leak(Box::new(StrategyEntry {
+        name: "onnx",
+        description: "Moves picked by an ONNX model",
+        // Its strength depends entirely on the model
+        difficulty: Difficulty::Medium,
+        implementation: strategy,
+    })))
+}
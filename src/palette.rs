@@ -0,0 +1,136 @@
+use tic_tac_toe::game_model::Piece;
+
+// Piece colours as plain RGB so any frontend can use them
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Palette {
+    #[default]
+    Standard,
+    // Red and green are hard to tell apart, so lean on blue against orange
+    Deuteranopia,
+    Protanopia,
+    // Blue and yellow are hard to tell apart, so lean on red against teal
+    Tritanopia,
+}
+
+impl Palette {
+    pub const ALL: [Self; 4] = [
+        Self::Standard,
+        Self::Deuteranopia,
+        Self::Protanopia,
+        Self::Tritanopia,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Protanopia => "protanopia",
+            Self::Tritanopia => "tritanopia",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|palette| palette.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|palette| palette.name()).collect();
+                format!(
+                    "Unknown palette '{name}', expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+
+    pub const fn piece_color(self, piece: Piece) -> (u8, u8, u8) {
+        match (self, piece) {
+            (Self::Standard, Piece::X) => (255, 0, 0),
+            (Self::Standard, Piece::O) => (0, 0, 255),
+            (Self::Deuteranopia | Self::Protanopia, Piece::X) => (230, 159, 0),
+            (Self::Deuteranopia | Self::Protanopia, Piece::O) => (0, 114, 178),
+            (Self::Tritanopia, Piece::X) => (220, 50, 32),
+            (Self::Tritanopia, Piece::O) => (0, 130, 130),
+        }
+    }
+
+    // A light shade of the piece colour, for backgrounds
+    #[cfg(any(feature = "gui", feature = "tui", test))]
+    pub const fn piece_tint(self, piece: Piece) -> (u8, u8, u8) {
+        lighten(self.piece_color(piece))
+    }
+
+    // Used where a cell matters to both pieces at once
+    #[cfg(feature = "gui")]
+    pub const fn shared_tint(self) -> (u8, u8, u8) {
+        let (x_red, x_green, x_blue) = self.piece_color(Piece::X);
+        let (o_red, o_green, o_blue) = self.piece_color(Piece::O);
+
+        lighten((
+            average(x_red, o_red),
+            average(x_green, o_green),
+            average(x_blue, o_blue),
+        ))
+    }
+
+    #[cfg(feature = "gui")]
+    pub const fn status_color(self) -> (u8, u8, u8) {
+        match self {
+            Self::Standard => (100, 100, 255),
+            _ => (40, 40, 40),
+        }
+    }
+}
+
+// Three quarters of the way towards white
+#[cfg(any(feature = "gui", feature = "tui", test))]
+const fn lighten((red, green, blue): (u8, u8, u8)) -> (u8, u8, u8) {
+    (
+        lighten_channel(red),
+        lighten_channel(green),
+        lighten_channel(blue),
+    )
+}
+
+#[cfg(any(feature = "gui", feature = "tui", test))]
+const fn lighten_channel(channel: u8) -> u8 {
+    channel + (u8::MAX - channel) / 4 * 3
+}
+
+#[cfg(feature = "gui")]
+const fn average(first: u8, second: u8) -> u8 {
+    first / 2 + second / 2 + (first % 2 + second % 2) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pieces_have_distinct_colors() {
+        for palette in Palette::ALL {
+            assert_ne!(palette.piece_color(Piece::X), palette.piece_color(Piece::O));
+            assert_ne!(palette.piece_tint(Piece::X), palette.piece_tint(Piece::O));
+        }
+    }
+
+    #[test]
+    fn test_palettes_are_found_by_name() {
+        for palette in Palette::ALL {
+            assert_eq!(Palette::from_name(palette.name()), Ok(palette));
+        }
+        assert_eq!(Palette::from_name("Tritanopia"), Ok(Palette::Tritanopia));
+        assert!(Palette::from_name("sepia").is_err());
+    }
+
+    #[test]
+    fn test_tints_are_lighter_than_colors() {
+        for palette in Palette::ALL {
+            for piece in [Piece::X, Piece::O] {
+                let (red, green, blue) = palette.piece_color(piece);
+                let (tint_red, tint_green, tint_blue) = palette.piece_tint(piece);
+
+                assert!(tint_red >= red && tint_green >= green && tint_blue >= blue);
+            }
+        }
+    }
+}
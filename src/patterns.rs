@@ -0,0 +1,225 @@
+use crate::game_model::{symmetries, threats, CellState, GameBoard, Piece};
+
+pub struct Pattern {
+    pub name: &'static str,
+    pub explanation: &'static str,
+    rule: Rule,
+}
+
+enum Rule {
+    // After these alternating moves (up to symmetry), replying with any of these positions loses
+    // against perfect play
+    OpeningTrap {
+        moves: &'static [usize],
+        losing_replies: &'static [usize],
+    },
+    // The move leaves the mover with two or more ways to complete a line
+    DoubleThreat,
+    // The opponent could complete a line next move and the move did nothing about it
+    MissedBlock,
+}
+
+// New patterns only need an entry here
+pub const PATTERNS: &[Pattern] = &[
+    Pattern {
+        name: "Corner opening trap",
+        explanation: "Against a corner opening, only the center reply holds the draw. \
+                      Any other reply lets the first player set up a fork.",
+        rule: Rule::OpeningTrap {
+            moves: &[1],
+            losing_replies: &[2, 3, 4, 6, 7, 8, 9],
+        },
+    },
+    Pattern {
+        name: "Center opening, edge reply",
+        explanation: "Against a center opening, an edge reply loses. \
+                      A corner reply is needed to hold the draw.",
+        rule: Rule::OpeningTrap {
+            moves: &[5],
+            losing_replies: &[2, 4, 6, 8],
+        },
+    },
+    Pattern {
+        name: "Edge opening trap",
+        explanation: "Against an edge opening, the far corners and the adjacent edges lose. \
+                      Reply in the center, a near corner or the opposite edge.",
+        rule: Rule::OpeningTrap {
+            moves: &[2],
+            losing_replies: &[4, 6, 7, 9],
+        },
+    },
+    Pattern {
+        name: "Opposite corners trap",
+        explanation: "With corners taken on both ends of a diagonal around your center, \
+                      taking another corner walks into a fork. An edge holds the draw.",
+        rule: Rule::OpeningTrap {
+            moves: &[1, 5, 9],
+            losing_replies: &[3, 7],
+        },
+    },
+    Pattern {
+        name: "Double threat",
+        explanation: "This move creates two ways to complete a line at once. \
+                      The opponent can only block one of them.",
+        rule: Rule::DoubleThreat,
+    },
+    Pattern {
+        name: "Missed block",
+        explanation: "The opponent could already complete a line, and this move did not block it.",
+        rule: Rule::MissedBlock,
+    },
+];
+
+pub fn detect_patterns(before: &GameBoard, after: &GameBoard) -> Vec<&'static Pattern> {
+    // Every pattern is about lines being worth completing with your own pieces, placed anywhere
+    if before.is_misere()
+        || before.is_wild()
+        || before.is_morris()
+        || before.is_gravity()
+        || !before.blocked_positions().is_empty()
+    {
+        return Vec::new();
+    }
+
+    let Some(position) = before
+        .get_available_positions()
+        .into_iter()
+        .find(|&position| after.get_cell_at_position(position) != Some(&CellState::Empty))
+    else {
+        return Vec::new();
+    };
+
+    let mover = *before.player_for_id(before.next_up).piece();
+
+    PATTERNS
+        .iter()
+        .filter(|pattern| pattern.rule.matches(before, after, position, mover))
+        .collect()
+}
+
+impl Rule {
+    fn matches(
+        &self,
+        before: &GameBoard,
+        after: &GameBoard,
+        position: usize,
+        mover: Piece,
+    ) -> bool {
+        match self {
+            Self::OpeningTrap {
+                moves,
+                losing_replies,
+            } => {
+                let occupied = before.positions().count() - before.get_available_positions().len();
+
+                // The traps and their symmetries are for the classic board
+                before.size() == GameBoard::CLASSIC_SIZE
+                    && !before.is_cube()
+                    && occupied == moves.len()
+                    && symmetries(GameBoard::CLASSIC_SIZE).iter().any(|symmetry| {
+                        let maps_to = |position: usize| symmetry[position - 1];
+
+                        let moves_match = moves.iter().enumerate().all(|(index, &played)| {
+                            // The last listed move was the opponent's, and turns alternate before it
+                            let piece = if (moves.len() - 1 - index) % 2 == 0 {
+                                GameBoard::other_piece(mover)
+                            } else {
+                                mover
+                            };
+
+                            before.get_cell_at_position(maps_to(played))
+                                == Some(&CellState::Occupied(piece))
+                        });
+
+                        moves_match
+                            && losing_replies
+                                .iter()
+                                .any(|&reply| maps_to(reply) == position)
+                    })
+            }
+
+            Self::DoubleThreat => {
+                let mut threatened_positions: Vec<usize> = threats(after)
+                    .into_iter()
+                    .filter(|&(_, piece)| piece == mover)
+                    .map(|(position, _)| position)
+                    .collect();
+                threatened_positions.dedup();
+
+                after.determine_winning_player().is_none() && threatened_positions.len() >= 2
+            }
+
+            Self::MissedBlock => {
+                let opponent_threats: Vec<usize> = threats(before)
+                    .into_iter()
+                    .filter(|&(_, piece)| piece != mover)
+                    .map(|(position, _)| position)
+                    .collect();
+
+                after.determine_winning_player().is_none()
+                    && !opponent_threats.is_empty()
+                    && !opponent_threats.contains(&position)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edge_reply_to_rotated_corner_opening_is_detected() {
+        let (before, after) = play(&[9], 8);
+
+        assert_eq!(names(&before, &after), vec!["Corner opening trap"]);
+    }
+
+    #[test]
+    fn test_center_reply_to_corner_opening_is_not_flagged() {
+        let (before, after) = play(&[3], 5);
+
+        assert!(names(&before, &after).is_empty());
+    }
+
+    #[test]
+    fn test_opposite_corners_trap_is_detected() {
+        let (before, after) = play(&[3, 5, 7], 9);
+
+        assert_eq!(names(&before, &after), vec!["Opposite corners trap"]);
+    }
+
+    #[test]
+    fn test_double_threat_is_detected() {
+        // First player: 1, 9 then 3 blocks the diagonal and threatens both 2 and 6
+        let (before, after) = play(&[1, 5, 9, 7], 3);
+
+        assert_eq!(names(&before, &after), vec!["Double threat"]);
+    }
+
+    #[test]
+    fn test_missed_block_is_detected() {
+        let (before, after) = play(&[1, 5, 2], 9);
+
+        assert_eq!(names(&before, &after), vec!["Missed block"]);
+    }
+
+    fn play(moves: &[usize], reply: usize) -> (GameBoard, GameBoard) {
+        let mut before = GameBoard::new();
+        for &position in moves {
+            assert!(before.play_next_up_at_position(position).is_ok());
+        }
+
+        let mut after = before.clone();
+        assert!(after.play_next_up_at_position(reply).is_ok());
+
+        (before, after)
+    }
+
+    fn names(before: &GameBoard, after: &GameBoard) -> Vec<&'static str> {
+        detect_patterns(before, after)
+            .iter()
+            .map(|pattern| pattern.name)
+            .collect()
+    }
+}
@@ -0,0 +1,138 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+// Every random decision in the game goes through this, so it can be seeded, recorded or replayed
+pub trait RandomSource {
+    // A uniformly distributed index in 0..upper, `upper` must not be 0
+    fn next_index(&mut self, upper: usize) -> usize;
+
+    // A uniformly distributed fraction in 0.0..1.0, in steps of one in a million
+    fn next_fraction(&mut self) -> f64 {
+        f64::from(u32::try_from(self.next_index(1_000_000)).unwrap_or(0)) / 1_000_000.0
+    }
+}
+
+// The source used when none is passed in: the thread's generator, unless the whole program was
+// seeded with seed_thread_random
+pub struct ThreadRandom;
+
+static PROGRAM_SEEDED: Mutex<Option<ChaCha8Rng>> = Mutex::new(None);
+// Checked first so that unseeded draws from games running in parallel do not wait on the lock
+static IS_PROGRAM_SEEDED: AtomicBool = AtomicBool::new(false);
+
+// From now on every ThreadRandom draw, on any thread, comes from one generator with this seed, so
+// a run of the program can be repeated
+pub fn seed_thread_random(seed: u64) {
+    *PROGRAM_SEEDED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some(ChaCha8Rng::seed_from_u64(seed));
+    IS_PROGRAM_SEEDED.store(true, Ordering::Release);
+}
+
+impl RandomSource for ThreadRandom {
+    fn next_index(&mut self, upper: usize) -> usize {
+        if !IS_PROGRAM_SEEDED.load(Ordering::Acquire) {
+            return rand::thread_rng().gen_range(0..upper);
+        }
+
+        match PROGRAM_SEEDED
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+        {
+            Some(seeded) => seeded.gen_range(0..upper),
+            None => rand::thread_rng().gen_range(0..upper),
+        }
+    }
+}
+
+pub struct SeededRandom(ChaCha8Rng);
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> Self {
+        Self(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+impl RandomSource for SeededRandom {
+    fn next_index(&mut self, upper: usize) -> usize {
+        self.0.gen_range(0..upper)
+    }
+}
+
+// Passes draws through from another source and keeps them for a later ReplayedRandom
+pub struct RecordingRandom<S: RandomSource> {
+    source: S,
+    draws: Vec<usize>,
+}
+
+impl<S: RandomSource> RecordingRandom<S> {
+    pub const fn new(source: S) -> Self {
+        Self {
+            source,
+            draws: Vec::new(),
+        }
+    }
+
+    pub fn into_draws(self) -> Vec<usize> {
+        self.draws
+    }
+}
+
+impl<S: RandomSource> RandomSource for RecordingRandom<S> {
+    fn next_index(&mut self, upper: usize) -> usize {
+        let draw = self.source.next_index(upper);
+        self.draws.push(draw);
+        draw
+    }
+}
+
+pub struct ReplayedRandom {
+    draws: std::vec::IntoIter<usize>,
+}
+
+impl ReplayedRandom {
+    pub fn new(draws: Vec<usize>) -> Self {
+        Self {
+            draws: draws.into_iter(),
+        }
+    }
+}
+
+impl RandomSource for ReplayedRandom {
+    fn next_index(&mut self, upper: usize) -> usize {
+        // Draws are replayed as recorded. Once the recording runs out, or if a draw doesn't fit
+        // (the replay has diverged), it wraps around rather than panicking mid-game.
+        self.draws.next().unwrap_or(0) % upper
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_gives_same_draws() {
+        let mut first = SeededRandom::new(42);
+        let mut second = SeededRandom::new(42);
+
+        let first_draws: Vec<_> = (0..20).map(|_| first.next_index(9)).collect();
+        let second_draws: Vec<_> = (0..20).map(|_| second.next_index(9)).collect();
+
+        assert_eq!(first_draws, second_draws);
+    }
+
+    #[test]
+    fn test_recorded_draws_replay_in_order() {
+        let mut recording = RecordingRandom::new(SeededRandom::new(7));
+        let recorded: Vec<_> = (1..=9).map(|upper| recording.next_index(upper)).collect();
+
+        let mut replay = ReplayedRandom::new(recording.into_draws());
+        let replayed: Vec<_> = (1..=9).map(|upper| replay.next_index(upper)).collect();
+
+        assert_eq!(recorded, replayed);
+        assert_eq!(replay.next_index(3), 0);
+    }
+}
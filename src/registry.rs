@@ -0,0 +1,396 @@
+use crate::stats::Record;
+use tic_tac_toe::engine::{EngineConfig, HeuristicWeights, MctsConfig};
+use tic_tac_toe::game_model::{Difficulty, GameBoard, GameState, Move, Player};
+use tic_tac_toe::strategy::{
+    MctsStrategy, MinimaxStrategy, MoveStrategy, RandomStrategy, WeightedStrategy,
+    WinOrBlockStrategy,
+};
+
+pub struct StrategyEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub difficulty: Difficulty,
+    pub implementation: &'static dyn MoveStrategy,
+}
+
+// Registered names are unique, so they are enough to tell entries apart
+impl PartialEq for StrategyEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl MoveStrategy for StrategyEntry {
+    fn choose_move(&self, board: &GameBoard) -> Option<usize> {
+        self.implementation.choose_move(board)
+    }
+
+    fn choose_full_move(&self, board: &GameBoard) -> Option<Move> {
+        self.implementation.choose_full_move(board)
+    }
+}
+
+// A named computer opponent, a strategy with something to say about the game
+#[derive(PartialEq)]
+pub struct OpponentEntry {
+    pub strategy: StrategyEntry,
+    pub greeting: &'static str,
+    // How to beat them, shown in the scouting report
+    pub tip: &'static str,
+    pub win_remark: &'static str,
+    pub loss_remark: &'static str,
+    pub draw_remark: &'static str,
+}
+
+impl OpponentEntry {
+    pub const fn name(&self) -> &'static str {
+        self.strategy.name
+    }
+
+    // With the human's record against them, from the lifetime statistics
+    pub fn scouting_report(&self, head_to_head: Record) -> String {
+        let record = if head_to_head.games() == 0 {
+            "not played yet".to_string()
+        } else {
+            format!(
+                "{} won, {} lost, {} drawn",
+                head_to_head.wins, head_to_head.losses, head_to_head.draws
+            )
+        };
+
+        format!(
+            "Scouting report: {}\nStyle: {} ({})\nYour record: {record}\nTip: {}",
+            self.name(),
+            self.strategy.description,
+            self.strategy.difficulty.name(),
+            self.tip
+        )
+    }
+
+    // What the opponent says about the game so far, the greeting until it is over
+    pub fn remark(&self, game_board: &GameBoard) -> &'static str {
+        match game_board.game_state() {
+            GameState::Winner(Player::Computer(_), _) => self.win_remark,
+            GameState::Winner(Player::Human(_), _) => self.loss_remark,
+            GameState::Draw => self.draw_remark,
+            GameState::InProgress => self.greeting,
+        }
+    }
+}
+
+pub struct VariantEntry {
+    pub name: &'static str,
+    // The cli option that plays it, without the dashes. None for the rules every game starts from.
+    pub option: Option<&'static str>,
+    pub description: &'static str,
+}
+
+// Where the cli lists the options that change the rules, each one registered in VARIANTS
+pub const VARIANT_HEADING: &str = "Variants";
+
+// Adding a strategy or variant only takes an entry here; the first entry is the default
+pub const STRATEGIES: &[StrategyEntry] = &[
+    StrategyEntry {
+        name: "random",
+        description: "Plays a random available position",
+        difficulty: Difficulty::Easy,
+        implementation: &RandomStrategy,
+    },
+    StrategyEntry {
+        name: "win-or-block",
+        description:
+            "Wins when it can, blocks your wins, otherwise prefers center, corners, then edges",
+        difficulty: Difficulty::Medium,
+        implementation: &WinOrBlockStrategy,
+    },
+    StrategyEntry {
+        name: "minimax",
+        description: "Perfect play, it never loses",
+        difficulty: Difficulty::Hard,
+        implementation: &MinimaxStrategy::new(EngineConfig::FULL_DEPTH),
+    },
+    StrategyEntry {
+        name: "mcts",
+        description: "Monte Carlo tree search over random playouts, strong but not perfect",
+        difficulty: Difficulty::Medium,
+        implementation: &MctsStrategy::new(MctsConfig::DEFAULT),
+    },
+];
+
+// Ordered from the gentlest to the strongest, for players working their way up
+pub const OPPONENTS: &[OpponentEntry] = &[
+    OpponentEntry {
+        strategy: StrategyEntry {
+            name: "Randy",
+            description: "Plays wherever takes his fancy",
+            difficulty: Difficulty::Easy,
+            implementation: &RandomStrategy,
+        },
+        greeting: "Hi! I just put my pieces wherever. Is that allowed?",
+        tip: "Line up two in a row, Randy will rarely notice the threat.",
+        win_remark: "Wait, I won? I wasn't even trying!",
+        loss_remark: "Well played! I should probably have a plan next time.",
+        draw_remark: "A draw! I'll take it.",
+    },
+    OpponentEntry {
+        strategy: StrategyEntry {
+            name: "Blocky",
+            description: "Defends first, blocking every line you start",
+            difficulty: Difficulty::Medium,
+            implementation: &WinOrBlockStrategy,
+        },
+        greeting: "You won't get three in a row past me.",
+        tip: "A single threat is always blocked, so set up two at once with a fork.",
+        win_remark: "Defence wins games.",
+        loss_remark: "How did that get through?",
+        draw_remark: "Nothing got through. Just how I like it.",
+    },
+    OpponentEntry {
+        strategy: StrategyEntry {
+            name: "Forky",
+            description: "Attacks first, setting up forks and rarely bothering to block",
+            difficulty: Difficulty::Medium,
+            implementation: &WeightedStrategy {
+                weights: HeuristicWeights {
+                    win: 100.0,
+                    block: 2.0,
+                    fork: 10.0,
+                    center: 3.0,
+                    corner: 2.5,
+                    edge: 0.0,
+                    blunder_rate: 0.0,
+                },
+            },
+        },
+        greeting: "Two threats at once, see if you can stop both.",
+        tip: "Forky would rather attack than defend, so make a threat early and keep the initiative.",
+        win_remark: "Forked you!",
+        loss_remark: "I was too busy attacking to notice that.",
+        draw_remark: "You kept me from forking. Respect.",
+    },
+    OpponentEntry {
+        strategy: StrategyEntry {
+            name: "Minnie Max",
+            description: "Searches every game to the end, she never loses",
+            difficulty: Difficulty::Hard,
+            implementation: &MinimaxStrategy::new(EngineConfig::FULL_DEPTH),
+        },
+        greeting: "I have already seen how this ends.",
+        tip: "She cannot be beaten, so a draw is a perfect result. Take the center or a corner early.",
+        win_remark: "As calculated.",
+        loss_remark: "That should not be possible. Please report a bug.",
+        draw_remark: "A perfect game from both of us.",
+    },
+];
+
+pub const VARIANTS: &[VariantEntry] = &[
+    VariantEntry {
+        name: "classic",
+        option: None,
+        description: "3x3 board, three in a row wins",
+    },
+    VariantEntry {
+        name: "misère",
+        option: Some("misere"),
+        description: "Completing a line loses",
+    },
+    VariantEntry {
+        name: "wild",
+        option: Some("wild"),
+        description: "Either player may place an X or an O",
+    },
+    VariantEntry {
+        name: "3d",
+        option: Some("3d"),
+        description: "A cube of stacked boards, with lines through the layers too",
+    },
+    VariantEntry {
+        name: "morris",
+        option: Some("morris"),
+        description: "Three Men's Morris, each player's three pieces slide once they are down",
+    },
+    VariantEntry {
+        name: "notakto",
+        option: Some("notakto"),
+        description: "Both players place X on up to three boards, and the last line loses",
+    },
+    VariantEntry {
+        name: "blocked",
+        option: Some("blocked"),
+        description: "One or two random cells start blocked for both players",
+    },
+    VariantEntry {
+        name: "gravity",
+        option: Some("gravity"),
+        description: "Pieces drop to the lowest empty cell of a column, as in Connect Four",
+    },
+];
+
+pub const fn default_strategy() -> &'static StrategyEntry {
+    &STRATEGIES[0]
+}
+
+// The first registered strategy of that difficulty
+pub fn strategy_for(difficulty: Difficulty) -> &'static StrategyEntry {
+    STRATEGIES
+        .iter()
+        .find(|entry| entry.difficulty == difficulty)
+        .unwrap_or_else(default_strategy)
+}
+
+pub fn strategy(name: &str) -> Result<&'static StrategyEntry, String> {
+    if let Some(path) = name.strip_prefix("tuned:") {
+        return crate::tuner::load_entry(path);
+    }
+
+    #[cfg(feature = "onnx")]
+    if let Some(path) = name.strip_prefix("onnx:") {
+        return crate::onnx_strategy::load_entry(path);
+    }
+
+    STRATEGIES
+        .iter()
+        .chain(OPPONENTS.iter().map(|opponent| &opponent.strategy))
+        .find(|entry| entry.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<_> = STRATEGIES.iter().map(|entry| entry.name).collect();
+            format!(
+                "Unknown strategy '{name}', expected one of: {} or an opponent",
+                names.join(", ")
+            )
+        })
+}
+
+pub fn opponent(name: &str) -> Result<&'static OpponentEntry, String> {
+    OPPONENTS
+        .iter()
+        .find(|opponent| opponent.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<_> = OPPONENTS.iter().map(OpponentEntry::name).collect();
+            format!(
+                "Unknown opponent '{name}', expected one of: {}",
+                names.join(", ")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CliArgs;
+    use clap::{Args, Command};
+    use tic_tac_toe::game_model::Piece;
+
+    #[test]
+    fn test_registered_names_are_unique() {
+        let mut strategy_names: Vec<_> = STRATEGIES
+            .iter()
+            .chain(OPPONENTS.iter().map(|opponent| &opponent.strategy))
+            .map(|entry| entry.name.to_ascii_lowercase())
+            .collect();
+        strategy_names.sort_unstable();
+        strategy_names.dedup();
+
+        let mut variant_names: Vec<_> = VARIANTS.iter().map(|entry| entry.name).collect();
+        variant_names.sort_unstable();
+        variant_names.dedup();
+
+        assert_eq!(strategy_names.len(), STRATEGIES.len() + OPPONENTS.len());
+        assert_eq!(variant_names.len(), VARIANTS.len());
+    }
+
+    #[test]
+    fn test_every_variant_option_is_registered() {
+        let command = CliArgs::augment_args(Command::new(""));
+        let variant_options: Vec<_> = command
+            .get_arguments()
+            .filter(|arg| arg.get_help_heading() == Some(VARIANT_HEADING))
+            .filter_map(|arg| arg.get_long())
+            .collect();
+        let registered: Vec<_> = VARIANTS.iter().filter_map(|entry| entry.option).collect();
+
+        assert!(!variant_options.is_empty());
+        for option in &variant_options {
+            assert!(registered.contains(option), "--{option} is not in VARIANTS");
+        }
+        for option in &registered {
+            assert!(
+                variant_options.contains(option),
+                "VARIANTS has no --{option}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_difficulty_has_a_strategy() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(strategy_for(difficulty).difficulty, difficulty);
+        }
+    }
+
+    #[test]
+    fn test_strategy_lookup_ignores_case() {
+        assert!(strategy("Random").is_ok());
+        assert!(strategy("no-such-strategy").is_err());
+    }
+
+    #[test]
+    fn test_opponents_are_found_by_name_and_as_strategies() {
+        assert_eq!(
+            opponent("minnie max").map(OpponentEntry::name),
+            Ok("Minnie Max")
+        );
+        assert_eq!(strategy("forky").map(|entry| entry.name), Ok("Forky"));
+        assert!(opponent("minimax").is_err());
+    }
+
+    #[test]
+    fn test_scouting_report_describes_the_opponent() {
+        let blocky = &OPPONENTS[1];
+        let report = blocky.scouting_report(Record::default());
+
+        assert!(report.contains(blocky.name()));
+        assert!(report.contains(blocky.strategy.description));
+        assert!(report.contains(blocky.tip));
+        assert!(report.contains("Your record: not played yet"));
+
+        let head_to_head = Record {
+            wins: 2,
+            losses: 1,
+            draws: 0,
+            hinted_wins: 1,
+        };
+        assert!(blocky
+            .scouting_report(head_to_head)
+            .contains("Your record: 2 won, 1 lost, 0 drawn"));
+    }
+
+    #[test]
+    fn test_opponent_remarks_follow_the_game() {
+        let randy = &OPPONENTS[0];
+        let mut game_board = GameBoard::new_with_first_up(Player::Computer(Piece::X));
+
+        assert_eq!(randy.remark(&game_board), randy.greeting);
+
+        for position in [1, 4, 2, 5, 3] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+
+        assert_eq!(randy.remark(&game_board), randy.win_remark);
+    }
+
+    #[test]
+    fn test_every_strategy_picks_an_available_position() {
+        let game_board = GameBoard::new();
+
+        for entry in STRATEGIES
+            .iter()
+            .chain(OPPONENTS.iter().map(|opponent| &opponent.strategy))
+        {
+            let position = entry.choose_move(&game_board);
+
+            assert!(position
+                .is_some_and(|position| game_board.get_available_positions().contains(&position)));
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::game_model::GameBoard;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+// Offered by the save dialog
+pub const DEFAULT_SAVE_NAME: &str = "saved-game.json";
+
+pub fn save_game(path: &Path, game_board: &GameBoard) -> Result<(), String> {
+    File::create(path)
+        .map_err(|error| format!("Could not create '{}': {error}", path.display()))
+        .and_then(|file| write_game(BufWriter::new(file), game_board))
+}
+
+pub fn load_game(path: &Path) -> Result<GameBoard, String> {
+    File::open(path)
+        .map_err(|error| format!("Could not open '{}': {error}", path.display()))
+        .and_then(|file| read_game(BufReader::new(file)))
+}
+
+fn write_game(mut output: impl Write, game_board: &GameBoard) -> Result<(), String> {
+    serde_json::to_writer_pretty(&mut output, game_board).map_err(|error| error.to_string())?;
+    output.flush().map_err(|error| error.to_string())
+}
+
+fn read_game(input: impl Read) -> Result<GameBoard, String> {
+    let game_board: GameBoard =
+        serde_json::from_reader(input).map_err(|error| format!("Not a saved game: {error}"))?;
+
+    if !game_board.is_well_formed() || !GameBoard::SIZES.contains(&game_board.size()) {
+        return Err("Not a saved game: the board is not a playable size".to_string());
+    }
+
+    Ok(game_board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_model::GameBoardBuilder;
+
+    #[test]
+    fn test_saved_game_loads_back_unchanged() {
+        let mut game_board = GameBoardBuilder::new()
+            .human_first(true)
+            .human_name("Ada")
+            .build();
+        assert!(game_board.play_next_up_at_position(5).is_ok());
+        assert!(game_board.play_next_up_at_position(1).is_ok());
+        assert!(game_board.undo().is_some());
+
+        let mut saved = Vec::new();
+        assert!(write_game(&mut saved, &game_board).is_ok());
+
+        assert_eq!(read_game(saved.as_slice()), Ok(game_board));
+    }
+
+    #[test]
+    fn test_other_json_is_rejected() {
+        assert!(read_game(r#"{"cells": []}"#.as_bytes()).is_err());
+
+        let mut saved = Vec::new();
+        assert!(write_game(&mut saved, &GameBoard::new()).is_ok());
+        let ragged = String::from_utf8_lossy(&saved).replacen("\"Empty\",", "", 1);
+        assert!(read_game(ragged.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_impossible_players_and_moves_are_rejected() {
+        let mut game_board = GameBoard::new();
+        assert!(game_board.play_next_up_at_position(5).is_ok());
+        let mut saved = Vec::new();
+        assert!(write_game(&mut saved, &game_board).is_ok());
+        let saved = String::from_utf8_lossy(&saved);
+
+        let same_pieces = saved.replace("\"O\"", "\"X\"");
+        assert_ne!(same_pieces, saved);
+        assert!(read_game(same_pieces.as_bytes()).is_err());
+
+        let off_the_board = saved.replace("\"position\": 5", "\"position\": 10");
+        assert_ne!(off_the_board, saved);
+        assert!(read_game(off_the_board.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+use crate::exit_code::ExitReason;
+use crate::local_data::{Interface, Settings};
+use crate::registry::{StrategyEntry, STRATEGIES};
+use tic_tac_toe::engine::{minimax_move, solve, EngineConfig, SolvedResult};
+use tic_tac_toe::game_model::{Difficulty, GameBoard, GameState, Piece, Player};
+use tic_tac_toe::random_source::{
+    RandomSource, RecordingRandom, ReplayedRandom, SeededRandom, ThreadRandom,
+};
+#[cfg(feature = "serde")]
+use tic_tac_toe::saved_game::{load_game, save_game};
+use tic_tac_toe::strategy::MoveStrategy;
+
+const GAMES: usize = 1000;
+// Search-based strategies are much slower than the rules checks
+const STRATEGY_GAMES: usize = 200;
+const SOLVER_GAMES: u64 = 20;
+
+struct Check {
+    name: &'static str,
+    run: fn() -> Result<String, String>,
+}
+
+const CHECKS: &[Check] = &[
+    Check {
+        name: "Rules invariants",
+        run: check_rules_invariants,
+    },
+    Check {
+        name: "Reproducible randomness",
+        run: check_reproducible_randomness,
+    },
+    Check {
+        name: "Strategies vs random",
+        run: check_strategies_against_random,
+    },
+    Check {
+        name: "Solver vs minimax",
+        run: check_solver_agrees_with_minimax,
+    },
+    Check {
+        name: "Config parse",
+        run: check_settings_load_back,
+    },
+    #[cfg(feature = "serde")]
+    Check {
+        name: "Save/load round-trip",
+        run: check_saved_games_load_back,
+    },
+];
+
+// Listed so the report is honest about what it does not cover
+const SKIPPED: &[(&str, &str)] = &[
+    #[cfg(not(feature = "serde"))]
+    ("Save/load round-trip", "built without the serde feature"),
+];
+
+pub fn selftest_main() -> ExitReason {
+    let mut failures = 0;
+
+    for check in CHECKS {
+        match (check.run)() {
+            Ok(details) => println!("PASS  {}: {details}", check.name),
+            Err(error) => {
+                failures += 1;
+                println!("FAIL  {}: {error}", check.name);
+            }
+        }
+    }
+
+    for (name, reason) in SKIPPED {
+        println!("SKIP  {name}: {reason}");
+    }
+
+    if failures == 0 {
+        println!("\nAll checks passed");
+        ExitReason::Completed
+    } else {
+        println!("\n{failures} check(s) failed");
+        ExitReason::SelfTestFailed
+    }
+}
+
+fn check_rules_invariants() -> Result<String, String> {
+    for _ in 0..GAMES {
+        let mut game_board = GameBoard::new();
+        let mut moves = 0;
+
+        while !game_board.is_game_over() {
+            let available_before = game_board.get_available_positions().len();
+            let mover = game_board.next_up;
+
+            let Some(position) = game_board.get_random_available_position() else {
+                return Err(format!(
+                    "No available position on an unfinished board:\n{game_board}"
+                ));
+            };
+
+            game_board.play_next_up_at_position(position)?;
+            moves += 1;
+
+            if game_board.get_available_positions().len() + 1 != available_before {
+                return Err(format!("Playing {position} did not fill exactly one cell"));
+            }
+
+            if game_board.next_up == mover {
+                return Err(format!("Turn did not pass after playing {position}"));
+            }
+
+            if game_board.play_next_up_at_position(position).is_ok() {
+                return Err(format!("Occupied position {position} was accepted"));
+            }
+        }
+
+        // The first possible win is on the fifth move
+        if !(5..=9).contains(&moves) {
+            return Err(format!("Game ended after {moves} moves:\n{game_board}"));
+        }
+
+        match game_board.game_state() {
+            GameState::Draw if !game_board.is_board_full() => {
+                return Err(format!("Draw on a board that is not full:\n{game_board}"));
+            }
+            GameState::InProgress => {
+                return Err(format!("Finished game is still in progress:\n{game_board}"));
+            }
+            GameState::Winner(..) | GameState::Draw => (),
+        }
+    }
+
+    Ok(format!("{GAMES} random games"))
+}
+
+fn check_reproducible_randomness() -> Result<String, String> {
+    for seed in 0..100 {
+        if play_random_game(&mut SeededRandom::new(seed))
+            != play_random_game(&mut SeededRandom::new(seed))
+        {
+            return Err(format!("Seed {seed} gave two different games"));
+        }
+    }
+
+    for _ in 0..100 {
+        let mut recording = RecordingRandom::new(ThreadRandom);
+        let recorded_game = play_random_game(&mut recording);
+
+        if play_random_game(&mut ReplayedRandom::new(recording.into_draws())) != recorded_game {
+            return Err(format!(
+                "Replaying the draws of this game diverged:\n{recorded_game}"
+            ));
+        }
+    }
+
+    Ok("100 seeded and 100 recorded games replayed identically".to_string())
+}
+
+fn play_random_game(random: &mut dyn RandomSource) -> GameBoard {
+    let mut game_board = GameBoard::new_with_random_source(random);
+
+    while !game_board.is_game_over() {
+        let Some(position) = game_board.random_available_position_from(random) else {
+            break;
+        };
+
+        if game_board.play_next_up_at_position(position).is_err() {
+            break;
+        }
+    }
+
+    game_board
+}
+
+fn check_strategies_against_random() -> Result<String, String> {
+    let mut summaries = Vec::new();
+
+    for strategy in STRATEGIES {
+        let (wins, draws, losses) = play_against_random(strategy)?;
+
+        // Hard strategies promise perfect play
+        if strategy.difficulty == Difficulty::Hard && losses > 0 {
+            return Err(format!(
+                "{} lost {losses} games to random moves",
+                strategy.name
+            ));
+        }
+
+        summaries.push(format!("{} +{wins} ={draws} -{losses}", strategy.name));
+    }
+
+    Ok(format!(
+        "{STRATEGY_GAMES} games each, {}",
+        summaries.join(", ")
+    ))
+}
+
+fn play_against_random(strategy: &StrategyEntry) -> Result<(usize, usize, usize), String> {
+    let (mut wins, mut draws, mut losses) = (0, 0, 0);
+
+    for game in 0..STRATEGY_GAMES {
+        // Alternate who moves first
+        let first_up = if game % 2 == 0 {
+            Player::Computer(Piece::X)
+        } else {
+            Player::Human(Piece::X)
+        };
+        let mut game_board = GameBoard::new_with_first_up(first_up);
+
+        while !game_board.is_game_over() {
+            let position = if game_board.is_computers_turn() {
+                strategy.choose_move(&game_board)
+            } else {
+                game_board.get_random_available_position()
+            };
+
+            let Some(position) = position else {
+                return Err(format!("{} found no move on:\n{game_board}", strategy.name));
+            };
+
+            game_board
+                .play_next_up_at_position(position)
+                .map_err(|error| format!("{} played an illegal move: {error}", strategy.name))?;
+        }
+
+        match game_board.game_state() {
+            GameState::Winner(Player::Computer(_), _) => wins += 1,
+            GameState::Winner(Player::Human(_), _) => losses += 1,
+            GameState::Draw | GameState::InProgress => draws += 1,
+        }
+    }
+
+    Ok((wins, draws, losses))
+}
+
+// A perfect move never changes the proven result, and the empty board is a draw
+fn check_solver_agrees_with_minimax() -> Result<String, String> {
+    let empty_board_result = solve(&GameBoard::new()).result;
+    if empty_board_result != SolvedResult::Draw {
+        return Err(format!("The empty board solved to: {empty_board_result}"));
+    }
+
+    let mut positions = 0;
+
+    for seed in 0..SOLVER_GAMES {
+        let mut random = SeededRandom::new(seed);
+        let mut game_board = GameBoard::new_with_random_source(&mut random);
+
+        while !game_board.is_game_over() {
+            let solution = solve(&game_board);
+
+            let mut best_reply = game_board.clone();
+            let Some(position) = minimax_move(&game_board, EngineConfig::FULL_DEPTH, &mut random)
+            else {
+                return Err(format!("minimax found no move on:\n{game_board}"));
+            };
+            best_reply.play_next_up_at_position(position)?;
+
+            let result_after = solve(&best_reply).result;
+            if result_after != solution.result {
+                return Err(format!(
+                    "minimax played {position} and turned {} into {result_after} on:\n{game_board}",
+                    solution.result
+                ));
+            }
+            positions += 1;
+
+            let Some(position) = game_board.random_available_position_from(&mut random) else {
+                break;
+            };
+            game_board.play_next_up_at_position(position)?;
+        }
+    }
+
+    Ok(format!(
+        "{positions} positions from {SOLVER_GAMES} random games kept their result"
+    ))
+}
+
+// Every combination of settings, through a file rather than the player's own settings
+fn check_settings_load_back() -> Result<String, String> {
+    let path = std::env::temp_dir().join(format!(
+        "tic-tac-toe-selftest-{}-settings.txt",
+        std::process::id()
+    ));
+
+    let round_trip = || {
+        let mut combinations = 0;
+        for record_stats in [true, false] {
+            for interface in Interface::ALL {
+                let settings = Settings {
+                    record_stats,
+                    interface,
+                };
+                settings.save_to(&path)?;
+                if Settings::load_from(&path) != settings {
+                    return Err(format!("{settings:?} did not load back the same"));
+                }
+                combinations += 1;
+            }
+        }
+        Ok(format!("{combinations} combinations of settings"))
+    };
+    let result = round_trip();
+
+    _ = std::fs::remove_file(&path);
+    result
+}
+
+// Through a real file, as the GUI saves them
+#[cfg(feature = "serde")]
+fn check_saved_games_load_back() -> Result<String, String> {
+    let path =
+        std::env::temp_dir().join(format!("tic-tac-toe-selftest-{}.json", std::process::id()));
+    let mut result = Ok(format!("{SOLVER_GAMES} random games"));
+
+    for seed in 0..SOLVER_GAMES {
+        let mut random = SeededRandom::new(seed);
+        let mut game_board = play_random_game(&mut random);
+        // Half finished games too, with something to redo
+        if seed % 2 == 0 {
+            game_board.undo();
+        }
+
+        let loaded = save_game(&path, &game_board).and_then(|()| load_game(&path));
+        if loaded.as_ref() != Ok(&game_board) {
+            result = Err(format!(
+                "This game did not load back the same:\n{game_board}"
+            ));
+            break;
+        }
+    }
+
+    _ = std::fs::remove_file(&path);
+    result
+}
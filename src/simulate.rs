@@ -0,0 +1,202 @@
+use crate::exit_code::ExitReason;
+use crate::registry::{self, StrategyEntry};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use tic_tac_toe::game_model::{execute_computer_turn, GameBoard, GameError, GameState, Piece};
+use tic_tac_toe::table::Table;
+
+#[derive(clap::Args)]
+pub struct SimulateArgs {
+    #[arg(
+        long,
+        value_name = "n",
+        default_value_t = 1000,
+        help = "How many games to play"
+    )]
+    games: usize,
+    #[arg(
+        long,
+        value_name = "name",
+        value_parser = registry::strategy,
+        default_value = registry::default_strategy().name,
+        help = "Strategy playing X, who moves first"
+    )]
+    p1: &'static StrategyEntry,
+    #[arg(
+        long,
+        value_name = "name",
+        value_parser = registry::strategy,
+        default_value = registry::default_strategy().name,
+        help = "Strategy playing O"
+    )]
+    p2: &'static StrategyEntry,
+    #[arg(
+        long,
+        help = "Play the games on all cores, so the order of random moves differs even with --seed"
+    )]
+    parallel: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Tally {
+    x_wins: usize,
+    o_wins: usize,
+    draws: usize,
+    moves: usize,
+}
+
+impl Tally {
+    fn games(&self) -> usize {
+        self.x_wins + self.o_wins + self.draws
+    }
+}
+
+// Shared by the games running at once, so each finished game is added without a lock
+#[derive(Default)]
+struct Counters {
+    x_wins: AtomicUsize,
+    o_wins: AtomicUsize,
+    draws: AtomicUsize,
+    moves: AtomicUsize,
+}
+
+impl Counters {
+    fn record(&self, game_board: &GameBoard) {
+        let result = match game_board.game_state() {
+            GameState::Winner(player, _) if *player.piece() == Piece::X => &self.x_wins,
+            GameState::Winner(..) => &self.o_wins,
+            GameState::Draw | GameState::InProgress => &self.draws,
+        };
+        result.fetch_add(1, Ordering::Relaxed);
+        self.moves
+            .fetch_add(game_board.history().len(), Ordering::Relaxed);
+    }
+
+    fn into_tally(self) -> Tally {
+        Tally {
+            x_wins: self.x_wins.into_inner(),
+            o_wins: self.o_wins.into_inner(),
+            draws: self.draws.into_inner(),
+            moves: self.moves.into_inner(),
+        }
+    }
+}
+
+// Plays many games between two strategies without showing them, and sums up how they went. For
+// checking a strategy against the others, or how fast they play.
+pub fn simulate_main(args: &SimulateArgs) -> ExitReason {
+    let started = Instant::now();
+    let tally = match simulate(args.games, args.p1, args.p2, args.parallel) {
+        Ok(tally) => tally,
+        Err(error) => {
+            println!("Error: {error}");
+            return ExitReason::InvalidInput;
+        }
+    };
+    let elapsed = started.elapsed();
+
+    let percent = |count: usize| format!("{:.1}", 100.0 * ratio(count, tally.games()));
+    let mut table = Table::new(&["Result", "Games", "Percent"]);
+    for (result, count) in [
+        (format!("{} (X) wins", args.p1.name), tally.x_wins),
+        (format!("{} (O) wins", args.p2.name), tally.o_wins),
+        ("Draws".to_string(), tally.draws),
+    ] {
+        table.add_row(vec![result, count.to_string(), percent(count)]);
+    }
+
+    println!(
+        "{} games of {} (X) vs {} (O) in {:.2}s:\n{table}",
+        tally.games(),
+        args.p1.name,
+        args.p2.name,
+        elapsed.as_secs_f64()
+    );
+    println!(
+        "Average game length: {:.2} moves",
+        ratio(tally.moves, tally.games())
+    );
+
+    ExitReason::Completed
+}
+
+fn simulate(
+    games: usize,
+    x_strategy: &StrategyEntry,
+    o_strategy: &StrategyEntry,
+    parallel: bool,
+) -> Result<Tally, GameError> {
+    let counters = Counters::default();
+    let play_one = |_| play(x_strategy, o_strategy).map(|game_board| counters.record(&game_board));
+
+    if parallel {
+        (0..games).into_par_iter().try_for_each(play_one)?;
+    } else {
+        (0..games).try_for_each(play_one)?;
+    }
+
+    Ok(counters.into_tally())
+}
+
+fn play(x_strategy: &StrategyEntry, o_strategy: &StrategyEntry) -> Result<GameBoard, GameError> {
+    let mut game_board = GameBoard::new_computer_vs_computer();
+
+    while !game_board.is_game_over() {
+        let strategy = match game_board.player_for_id(game_board.next_up).piece() {
+            Piece::X => x_strategy,
+            Piece::O => o_strategy,
+        };
+        execute_computer_turn(&mut game_board, strategy)?;
+    }
+
+    Ok(game_board)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn ratio(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tic_tac_toe::game_model::Difficulty;
+
+    #[test]
+    fn test_every_game_is_counted_once() {
+        let random = registry::strategy_for(Difficulty::Easy);
+        let Ok(tally) = simulate(50, random, random, false) else {
+            panic!("expected every game to finish");
+        };
+
+        assert_eq!(tally.games(), 50);
+        // A game takes from 5 to 9 moves
+        assert!((250..=450).contains(&tally.moves));
+    }
+
+    #[test]
+    fn test_perfect_play_is_always_a_draw() {
+        let minimax = registry::strategy_for(Difficulty::Hard);
+
+        assert_eq!(
+            simulate(3, minimax, minimax, false).map(|tally| tally.draws),
+            Ok(3)
+        );
+    }
+
+    #[test]
+    fn test_parallel_games_are_all_counted() {
+        let random = registry::strategy_for(Difficulty::Easy);
+        let Ok(tally) = simulate(500, random, random, true) else {
+            panic!("expected every game to finish");
+        };
+
+        assert_eq!(tally.games(), 500);
+        assert!((2500..=4500).contains(&tally.moves));
+    }
+}
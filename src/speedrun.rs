@@ -0,0 +1,133 @@
+use std::time::{Duration, Instant};
+use tic_tac_toe::game_model::{Difficulty, GameBoard, GameState, Player};
+
+// Real time from the start of the first game until the computer is beaten. Losing or drawing
+// does not stop the clock, the run carries on into the next game.
+pub struct Speedrun {
+    difficulty: Difficulty,
+    started: Instant,
+    // The game being played, counted from 1
+    games: usize,
+}
+
+impl Speedrun {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            started: Instant::now(),
+            games: 1,
+        }
+    }
+
+    // Beating the hard computer is not always possible, so it has no leaderboard
+    pub const fn allowed(difficulty: Difficulty) -> bool {
+        matches!(difficulty, Difficulty::Easy | Difficulty::Medium)
+    }
+
+    pub const fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub const fn games(&self) -> usize {
+        self.games
+    }
+
+    pub fn clock(&self) -> String {
+        format_run_time(self.started.elapsed())
+    }
+
+    pub fn restart(&mut self) {
+        *self = Self::new(self.difficulty);
+    }
+
+    // The time the run took once the computer is beaten. Any other result moves on a game.
+    pub fn game_finished(&mut self, game_board: &GameBoard) -> Option<Duration> {
+        if matches!(
+            game_board.game_state(),
+            GameState::Winner(Player::Human(_), _)
+        ) {
+            return Some(self.started.elapsed());
+        }
+
+        self.games += 1;
+        None
+    }
+
+    // Describes how the run went, with where stats::record_run_time placed its time
+    pub fn result_text(&self, time: Duration, placing: &Result<Option<usize>, String>) -> String {
+        let placing = match placing {
+            Ok(Some(1)) => " A new best time!".to_string(),
+            Ok(Some(rank)) => format!(
+                " Number {rank} on the {} leaderboard.",
+                self.difficulty.name()
+            ),
+            Ok(None) => String::new(),
+            Err(error) => format!(" Time not saved: {error}"),
+        };
+        let games = if self.games == 1 { "game" } else { "games" };
+
+        format!(
+            "Beat the {} computer in {} over {} {games}.{placing}",
+            self.difficulty.name(),
+            format_run_time(time),
+            self.games
+        )
+    }
+}
+
+// Minutes, seconds and tenths, such as 1:05.3
+pub fn format_run_time(time: Duration) -> String {
+    let tenths = time.as_millis() / 100;
+
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tic_tac_toe::game_model::Piece;
+
+    #[test]
+    fn test_run_times_are_minutes_seconds_and_tenths() {
+        assert_eq!(format_run_time(Duration::from_millis(0)), "0:00.0");
+        assert_eq!(format_run_time(Duration::from_millis(65_349)), "1:05.3");
+        assert_eq!(format_run_time(Duration::from_secs(600)), "10:00.0");
+    }
+
+    #[test]
+    fn test_runs_carry_on_until_the_computer_is_beaten() {
+        let mut run = Speedrun::new(Difficulty::Easy);
+
+        let mut lost = GameBoard::new_with_first_up(Player::Computer(Piece::X));
+        for position in [1, 4, 2, 5, 3] {
+            assert!(lost.play_next_up_at_position(position).is_ok());
+        }
+        assert_eq!(run.game_finished(&lost), None);
+        assert_eq!(run.games(), 2);
+
+        let mut won = GameBoard::new_with_first_up(Player::Human(Piece::X));
+        for position in [1, 4, 2, 5, 3] {
+            assert!(won.play_next_up_at_position(position).is_ok());
+        }
+        assert!(run.game_finished(&won).is_some());
+        assert!(!Speedrun::allowed(Difficulty::Hard));
+    }
+
+    #[test]
+    fn test_result_text_gives_the_leaderboard_place() {
+        let run = Speedrun::new(Difficulty::Medium);
+        let time = Duration::from_millis(65_349);
+
+        assert_eq!(
+            run.result_text(time, &Ok(Some(1))),
+            "Beat the medium computer in 1:05.3 over 1 game. A new best time!"
+        );
+        assert!(run
+            .result_text(time, &Ok(Some(3)))
+            .ends_with(" Number 3 on the medium leaderboard."));
+        assert!(run.result_text(time, &Ok(None)).ends_with("game."));
+        assert!(run
+            .result_text(time, &Err("disk full".to_string()))
+            .ends_with(" Time not saved: disk full"));
+    }
+}
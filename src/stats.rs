@@ -0,0 +1,774 @@
+use crate::exit_code::ExitReason;
+use crate::local_data::{data_path, recording_stats, STATS_FILE};
+use crate::registry::{OpponentEntry, StrategyEntry, OPPONENTS};
+use crate::speedrun::format_run_time;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tic_tac_toe::game_model::{Difficulty, GameBoard, GameState, Player, PlayerID};
+use tic_tac_toe::table::Table;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Record {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    // Wins where the human asked for a hint, also counted in wins
+    pub hinted_wins: usize,
+}
+
+impl Record {
+    pub const fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    // Wins nobody helped with
+    pub const fn unaided_wins(&self) -> usize {
+        self.wins.saturating_sub(self.hinted_wins)
+    }
+
+    const fn add(self, other: Self) -> Self {
+        Self {
+            wins: self.wins + other.wins,
+            losses: self.losses + other.losses,
+            draws: self.draws + other.draws,
+            hinted_wins: self.hinted_wins + other.hinted_wins,
+        }
+    }
+
+    const fn counts(&self) -> [usize; 4] {
+        [self.wins, self.losses, self.draws, self.hinted_wins]
+    }
+
+    fn count_mut(&mut self, result: &str) -> Option<&mut usize> {
+        match result {
+            "wins" => Some(&mut self.wins),
+            "losses" => Some(&mut self.losses),
+            "draws" => Some(&mut self.draws),
+            "hinted_wins" => Some(&mut self.hinted_wins),
+            _ => None,
+        }
+    }
+}
+
+const RESULTS: [&str; 4] = ["wins", "losses", "draws", "hinted_wins"];
+
+// Speedrun times kept for each difficulty
+const LEADERBOARD_SIZE: usize = 5;
+
+// The games finished on one machine
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+struct Totals {
+    // Indexed like Difficulty::ALL
+    per_difficulty: [Record; 3],
+    // Fastest speedruns first, indexed like Difficulty::ALL
+    best_times: [Vec<Duration>; 3],
+    // Games against each named opponent, also counted in their difficulty
+    per_opponent: BTreeMap<String, Record>,
+}
+
+impl Totals {
+    fn games(&self) -> usize {
+        self.per_difficulty.iter().map(Record::games).sum()
+    }
+
+    fn format(&self, prefix: &str) -> String {
+        let mut text = String::new();
+
+        for (index, difficulty) in Difficulty::ALL.into_iter().enumerate() {
+            let record = self.per_difficulty[index];
+            for (result, count) in RESULTS.iter().zip(record.counts()) {
+                text.push_str(format!("{prefix}{}.{result}={count}\n", difficulty.name()).as_str());
+            }
+
+            let best_times = &self.best_times[index];
+            if !best_times.is_empty() {
+                let millis: Vec<String> = best_times
+                    .iter()
+                    .map(|time| time.as_millis().to_string())
+                    .collect();
+                text.push_str(
+                    format!(
+                        "{prefix}{}.best_ms={}\n",
+                        difficulty.name(),
+                        millis.join(",")
+                    )
+                    .as_str(),
+                );
+            }
+        }
+
+        for (name, record) in &self.per_opponent {
+            for (result, count) in RESULTS.iter().zip(record.counts()) {
+                text.push_str(format!("{prefix}opponent.{name}.{result}={count}\n").as_str());
+            }
+        }
+
+        text
+    }
+
+    // A <difficulty>.<result> or opponent.<name>.<result> line
+    fn parse_line(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let (record, result) = if let Some(key) = key.strip_prefix("opponent.") {
+            let Some((name, result)) = key.rsplit_once('.') else {
+                return Err(format!("Expected opponent.<name>.<result>, got '{key}'"));
+            };
+            (
+                self.per_opponent.entry(name.to_string()).or_default(),
+                result,
+            )
+        } else {
+            let Some((difficulty, result)) = key.split_once('.') else {
+                return Err(format!("Expected <difficulty>.<result>, got '{key}'"));
+            };
+            let index = difficulty_index(Difficulty::from_name(difficulty)?);
+
+            if result == "best_ms" {
+                let mut best_times = parse_times(value)?;
+                best_times.sort();
+                best_times.truncate(LEADERBOARD_SIZE);
+                self.best_times[index] = best_times;
+                return Ok(());
+            }
+
+            (&mut self.per_difficulty[index], result)
+        };
+
+        let Some(count) = record.count_mut(result) else {
+            return Err(format!("Unknown result '{result}'"));
+        };
+        let Ok(value) = value.parse() else {
+            return Err(format!("'{value}' is not a count"));
+        };
+
+        *count = value;
+        Ok(())
+    }
+}
+
+// Every human vs computer game ever finished on this machine, from the human's side, and the
+// games merged in from other machines
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct LifetimeStats {
+    // Tells this machine's games apart from the same games merged back in from another file.
+    // Made up when the file is first loaded.
+    machine_id: String,
+    own: Totals,
+    // The latest totals merged in from each other machine, by its id
+    merged: BTreeMap<String, Totals>,
+}
+
+impl LifetimeStats {
+    fn all_totals(&self) -> impl Iterator<Item = &Totals> {
+        [&self.own].into_iter().chain(self.merged.values())
+    }
+
+    pub fn for_difficulty(&self, difficulty: Difficulty) -> Record {
+        let index = difficulty_index(difficulty);
+        self.all_totals().fold(Record::default(), |total, totals| {
+            total.add(totals.per_difficulty[index])
+        })
+    }
+
+    // The fastest runs from every machine, fastest first
+    pub fn best_times(&self, difficulty: Difficulty) -> Vec<Duration> {
+        let index = difficulty_index(difficulty);
+        let mut best_times: Vec<Duration> = self
+            .all_totals()
+            .flat_map(|totals| totals.best_times[index].iter().copied())
+            .collect();
+        best_times.sort();
+        best_times.truncate(LEADERBOARD_SIZE);
+        best_times
+    }
+
+    // The time's place on the leaderboard from 1, or None if it is not fast enough to be kept
+    pub fn record_run(&mut self, difficulty: Difficulty, time: Duration) -> Option<usize> {
+        let rank = self
+            .best_times(difficulty)
+            .partition_point(|&best| best <= time);
+
+        let best_times = &mut self.own.best_times[difficulty_index(difficulty)];
+        let own_rank = best_times.partition_point(|&best| best <= time);
+        best_times.insert(own_rank, time);
+        best_times.truncate(LEADERBOARD_SIZE);
+
+        (rank < LEADERBOARD_SIZE).then_some(rank + 1)
+    }
+
+    pub fn total(&self) -> Record {
+        Difficulty::ALL
+            .into_iter()
+            .fold(Record::default(), |total, difficulty| {
+                total.add(self.for_difficulty(difficulty))
+            })
+    }
+
+    // The human's games against a named opponent, on every machine
+    pub fn against(&self, opponent: &str) -> Record {
+        self.all_totals()
+            .filter_map(|totals| totals.per_opponent.get(opponent))
+            .fold(Record::default(), |total, &record| total.add(record))
+    }
+
+    // Games without a human, or not finished, are not counted. Those against a named opponent
+    // are counted against them too.
+    pub fn record(
+        &mut self,
+        game_board: &GameBoard,
+        difficulty: Difficulty,
+        opponent: Option<&str>,
+    ) {
+        self.tally(game_board, difficulty, opponent, |count| *count += 1);
+    }
+
+    // Takes a game back off, such as when its last move is undone in the GUI
+    #[cfg(any(feature = "gui", test))]
+    pub fn retract(
+        &mut self,
+        game_board: &GameBoard,
+        difficulty: Difficulty,
+        opponent: Option<&str>,
+    ) {
+        self.tally(game_board, difficulty, opponent, |count| {
+            *count = count.saturating_sub(1);
+        });
+    }
+
+    fn tally(
+        &mut self,
+        game_board: &GameBoard,
+        difficulty: Difficulty,
+        opponent: Option<&str>,
+        change: impl Fn(&mut usize),
+    ) {
+        let has_human = [PlayerID::Player1, PlayerID::Player2]
+            .into_iter()
+            .any(|player_id| matches!(game_board.player_for_id(player_id), Player::Human(_)));
+        if !has_human {
+            return;
+        }
+
+        tally_game(
+            game_board,
+            &mut self.own.per_difficulty[difficulty_index(difficulty)],
+            &change,
+        );
+        if let Some(opponent) = opponent {
+            let record = self
+                .own
+                .per_opponent
+                .entry(opponent.to_string())
+                .or_default();
+            tally_game(game_board, record, &change);
+        }
+    }
+
+    fn format(&self) -> String {
+        let mut text = String::from("# Lifetime statistics, shown with the stats command\n");
+
+        if !self.machine_id.is_empty() {
+            text.push_str(format!("machine={}\n", self.machine_id).as_str());
+        }
+        text.push_str(&self.own.format(""));
+
+        for (machine_id, totals) in &self.merged {
+            text.push_str(&totals.format(&format!("from.{machine_id}.")));
+        }
+
+        text
+    }
+
+    // Missing counts are taken as zero
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut stats = Self::default();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("Expected name=value, got '{line}'"));
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            if key == "machine" {
+                value.clone_into(&mut stats.machine_id);
+            } else if let Some(key) = key.strip_prefix("from.") {
+                let Some((machine_id, key)) = key.split_once('.') else {
+                    return Err(format!(
+                        "Expected from.<machine>.<difficulty>.<result>, got '{key}'"
+                    ));
+                };
+                stats
+                    .merged
+                    .entry(machine_id.to_string())
+                    .or_default()
+                    .parse_line(key, value)?;
+            } else {
+                stats.own.parse_line(key, value)?;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    // A missing file is a fresh start with no games played
+    pub fn load() -> Result<Self, String> {
+        let mut stats = Self::load_from(&stats_path()?)?;
+        if stats.machine_id.is_empty() {
+            stats.machine_id = format!("{:016x}", rand::random::<u64>());
+        }
+        Ok(stats)
+    }
+
+    fn load_from(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(format!("Could not read '{}': {error}", path.display())),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = stats_path()?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|error| format!("Could not create '{}': {error}", dir.display()))?;
+        }
+
+        fs::write(&path, self.format())
+            .map_err(|error| format!("Could not write '{}': {error}", path.display()))
+    }
+
+    // Keeps the latest totals from each machine in the other file, so merging a file again only
+    // adds the games played since. A file saved before machines had ids is known by its
+    // contents instead. Our own games, merged back from another machine, are left out.
+    pub fn merge(&mut self, other: &Self) {
+        let other_id = if other.machine_id.is_empty() {
+            let mut hasher = DefaultHasher::new();
+            other.format().hash(&mut hasher);
+            format!("file-{:016x}", hasher.finish())
+        } else {
+            other.machine_id.clone()
+        };
+
+        for (machine_id, totals) in other.merged.iter().chain([(&other_id, &other.own)]) {
+            if *machine_id == self.machine_id {
+                continue;
+            }
+            // Totals only grow, so an older copy passed on through a third machine is left out
+            let merged = self.merged.entry(machine_id.clone()).or_default();
+            if totals.games() >= merged.games() {
+                merged.clone_from(totals);
+            }
+        }
+    }
+
+    pub fn to_table(&self) -> Table {
+        let mut table = Table::new(&["Difficulty", "Games", "Wins", "Unaided", "Losses", "Draws"]);
+
+        let rows = Difficulty::ALL
+            .into_iter()
+            .map(|difficulty| (difficulty.name(), self.for_difficulty(difficulty)))
+            .chain([("total", self.total())]);
+
+        for (name, record) in rows {
+            table.add_row(vec![
+                name.to_string(),
+                record.games().to_string(),
+                record.wins.to_string(),
+                record.unaided_wins().to_string(),
+                record.losses.to_string(),
+                record.draws.to_string(),
+            ]);
+        }
+
+        table
+    }
+
+    // None until a speedrun has been finished
+    pub fn leaderboard_table(&self) -> Option<Table> {
+        let mut table = Table::new(&["Difficulty", "Rank", "Time"]);
+        let mut any_times = false;
+
+        for difficulty in Difficulty::ALL {
+            for (rank, &time) in self.best_times(difficulty).iter().enumerate() {
+                any_times = true;
+                table.add_row(vec![
+                    difficulty.name().to_string(),
+                    (rank + 1).to_string(),
+                    format_run_time(time),
+                ]);
+            }
+        }
+
+        any_times.then_some(table)
+    }
+}
+
+fn tally_game(game_board: &GameBoard, record: &mut Record, change: impl Fn(&mut usize)) {
+    match game_board.game_state() {
+        GameState::Winner(Player::Human(_), _) => {
+            change(&mut record.wins);
+            if game_board.hints_used() > 0 {
+                change(&mut record.hinted_wins);
+            }
+        }
+        GameState::Winner(Player::Computer(_), _) => change(&mut record.losses),
+        GameState::Draw => change(&mut record.draws),
+        GameState::InProgress => (),
+    }
+}
+
+// Comma separated milliseconds
+fn parse_times(text: &str) -> Result<Vec<Duration>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|millis| !millis.is_empty())
+        .map(|millis| {
+            millis
+                .parse()
+                .map(Duration::from_millis)
+                .map_err(|_| format!("'{millis}' is not a time in milliseconds"))
+        })
+        .collect()
+}
+
+fn difficulty_index(difficulty: Difficulty) -> usize {
+    Difficulty::ALL
+        .iter()
+        .position(|&known| known == difficulty)
+        .unwrap_or(0)
+}
+
+pub fn stats_path() -> Result<PathBuf, String> {
+    data_path(STATS_FILE)
+}
+
+// The name to count games under, when the strategy is a named opponent
+fn opponent_name(strategy: &StrategyEntry) -> Option<&'static str> {
+    OPPONENTS
+        .iter()
+        .find(|opponent| opponent.strategy == *strategy)
+        .map(OpponentEntry::name)
+}
+
+// Adds a finished game against the strategy to the statistics file, unless recording is turned off
+pub fn record_game(game_board: &GameBoard, strategy: &StrategyEntry) -> Result<(), String> {
+    if !recording_stats() {
+        return Ok(());
+    }
+
+    let mut stats = LifetimeStats::load()?;
+    stats.record(game_board, strategy.difficulty, opponent_name(strategy));
+    stats.save()
+}
+
+#[cfg(feature = "gui")]
+pub fn retract_game(game_board: &GameBoard, strategy: &StrategyEntry) -> Result<(), String> {
+    if !recording_stats() {
+        return Ok(());
+    }
+
+    let mut stats = LifetimeStats::load()?;
+    stats.retract(game_board, strategy.difficulty, opponent_name(strategy));
+    stats.save()
+}
+
+// Adds a speedrun time to the leaderboard, giving its place if it was fast enough to be kept
+pub fn record_run_time(difficulty: Difficulty, time: Duration) -> Result<Option<usize>, String> {
+    if !recording_stats() {
+        return Ok(None);
+    }
+
+    let mut stats = LifetimeStats::load()?;
+    let rank = stats.record_run(difficulty, time);
+    stats.save()?;
+    Ok(rank)
+}
+
+// Without a command the statistics are shown
+#[derive(clap::Args)]
+pub struct StatsArgs {
+    #[command(subcommand)]
+    command: Option<StatsCommand>,
+}
+
+#[derive(clap::Subcommand)]
+pub enum StatsCommand {
+    #[command(
+        about = "Add the games in another statistics file, such as one copied from another machine. \
+                 Merging a newer copy of the same file replaces the games it added before"
+    )]
+    Merge {
+        #[arg(value_name = "file", help = "The statistics file to add")]
+        path: PathBuf,
+    },
+}
+
+pub fn stats_main(args: &StatsArgs) -> ExitReason {
+    let result = match &args.command {
+        None => LifetimeStats::load(),
+        Some(StatsCommand::Merge { path }) => merge_file(path),
+    };
+
+    match result {
+        Ok(stats) => {
+            print!("{}", stats.to_table());
+            if let Some(leaderboard) = stats.leaderboard_table() {
+                print!("\nFastest speedruns:\n{leaderboard}");
+            }
+            if let Ok(path) = stats_path() {
+                println!("\nKept in {}", path.display());
+            }
+            ExitReason::Completed
+        }
+        Err(error) => {
+            println!("{error}");
+            ExitReason::InvalidInput
+        }
+    }
+}
+
+fn merge_file(path: &Path) -> Result<LifetimeStats, String> {
+    // Unlike our own file, a missing file to merge is a mistake
+    if !path.is_file() {
+        return Err(format!("'{}' is not a statistics file", path.display()));
+    }
+
+    let mut stats = LifetimeStats::load()?;
+    stats.merge(&LifetimeStats::load_from(path)?);
+    stats.save()?;
+
+    println!("Merged the games in '{}'", path.display());
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tic_tac_toe::game_model::Piece;
+
+    fn finished_game(first_up: Player) -> GameBoard {
+        let mut game_board = GameBoard::new_with_first_up(first_up);
+        for position in [1, 4, 2, 5, 3] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+        game_board
+    }
+
+    #[test]
+    fn test_results_are_counted_per_difficulty() {
+        let mut stats = LifetimeStats::default();
+        stats.record(
+            &finished_game(Player::Human(Piece::X)),
+            Difficulty::Hard,
+            None,
+        );
+        stats.record(
+            &finished_game(Player::Computer(Piece::X)),
+            Difficulty::Hard,
+            None,
+        );
+        stats.record(
+            &finished_game(Player::Human(Piece::X)),
+            Difficulty::Easy,
+            None,
+        );
+        stats.record(&GameBoard::new(), Difficulty::Easy, None);
+
+        assert_eq!(
+            stats.for_difficulty(Difficulty::Hard),
+            Record {
+                wins: 1,
+                losses: 1,
+                draws: 0,
+                hinted_wins: 0,
+            }
+        );
+        assert_eq!(stats.total().games(), 3);
+
+        stats.retract(
+            &finished_game(Player::Human(Piece::X)),
+            Difficulty::Easy,
+            None,
+        );
+
+        assert_eq!(stats.for_difficulty(Difficulty::Easy).games(), 0);
+    }
+
+    #[test]
+    fn test_wins_with_hints_are_not_unaided() {
+        let mut game_board =
+            GameBoard::new_with_first_up(Player::Human(Piece::X)).with_hint_budget(1);
+        assert!(game_board.take_hint().is_ok());
+        for position in [1, 4, 2, 5, 3] {
+            assert!(game_board.play_next_up_at_position(position).is_ok());
+        }
+
+        let mut stats = LifetimeStats::default();
+        stats.record(&game_board, Difficulty::Hard, None);
+        stats.record(
+            &finished_game(Player::Human(Piece::X)),
+            Difficulty::Hard,
+            None,
+        );
+
+        let record = stats.for_difficulty(Difficulty::Hard);
+        assert_eq!(
+            (record.wins, record.hinted_wins, record.unaided_wins()),
+            (2, 1, 1)
+        );
+        assert_eq!(LifetimeStats::parse(&stats.format()), Ok(stats.clone()));
+
+        stats.retract(&game_board, Difficulty::Hard, None);
+
+        assert_eq!(stats.for_difficulty(Difficulty::Hard).hinted_wins, 0);
+    }
+
+    #[test]
+    fn test_saved_text_parses_back() {
+        let mut stats = LifetimeStats::default();
+        stats.record(
+            &finished_game(Player::Human(Piece::O)),
+            Difficulty::Medium,
+            None,
+        );
+
+        assert_eq!(LifetimeStats::parse(&stats.format()), Ok(stats.clone()));
+        assert!(LifetimeStats::parse("medium.wins=lots").is_err());
+        assert!(LifetimeStats::parse("medium.forfeits=1").is_err());
+    }
+
+    #[test]
+    fn test_results_are_counted_against_each_opponent() {
+        let mut stats = LifetimeStats::default();
+        stats.record(
+            &finished_game(Player::Human(Piece::X)),
+            Difficulty::Medium,
+            Some("Blocky"),
+        );
+        stats.record(
+            &finished_game(Player::Computer(Piece::X)),
+            Difficulty::Medium,
+            None,
+        );
+
+        assert_eq!(stats.against("Blocky").wins, 1);
+        assert_eq!(stats.against("Blocky").games(), 1);
+        assert_eq!(stats.against("Rando").games(), 0);
+        assert_eq!(LifetimeStats::parse(&stats.format()), Ok(stats.clone()));
+    }
+
+    #[test]
+    fn test_leaderboard_keeps_the_fastest_runs() {
+        let mut stats = LifetimeStats::default();
+        for seconds in [30, 10, 20, 40, 50] {
+            stats.record_run(Difficulty::Easy, Duration::from_secs(seconds));
+        }
+
+        assert_eq!(
+            stats.record_run(Difficulty::Easy, Duration::from_secs(15)),
+            Some(2)
+        );
+        assert_eq!(
+            stats.record_run(Difficulty::Easy, Duration::from_secs(60)),
+            None
+        );
+        assert_eq!(
+            stats.best_times(Difficulty::Easy),
+            [10, 15, 20, 30, 40].map(Duration::from_secs)
+        );
+        assert!(stats.best_times(Difficulty::Medium).is_empty());
+
+        assert_eq!(LifetimeStats::parse(&stats.format()), Ok(stats.clone()));
+        assert!(LifetimeStats::parse("easy.best_ms=fast").is_err());
+    }
+
+    #[test]
+    fn test_merge_adds_counts() {
+        let mut stats = LifetimeStats::default();
+        stats.record(
+            &finished_game(Player::Human(Piece::X)),
+            Difficulty::Easy,
+            None,
+        );
+        let mut other = LifetimeStats::default();
+        other.record(
+            &finished_game(Player::Computer(Piece::X)),
+            Difficulty::Easy,
+            None,
+        );
+        other.record(
+            &finished_game(Player::Human(Piece::X)),
+            Difficulty::Hard,
+            None,
+        );
+
+        stats.merge(&other);
+
+        assert_eq!(
+            stats.for_difficulty(Difficulty::Easy),
+            Record {
+                wins: 1,
+                losses: 1,
+                draws: 0,
+                hinted_wins: 0,
+            }
+        );
+        assert_eq!(stats.total().games(), 3);
+        assert_eq!(LifetimeStats::parse(&stats.format()), Ok(stats.clone()));
+    }
+
+    #[test]
+    fn test_merging_a_file_again_does_not_count_its_games_twice() {
+        let mut stats = LifetimeStats {
+            machine_id: "ours".to_string(),
+            ..LifetimeStats::default()
+        };
+        stats.record(
+            &finished_game(Player::Human(Piece::X)),
+            Difficulty::Easy,
+            None,
+        );
+        let mut other = LifetimeStats {
+            machine_id: "theirs".to_string(),
+            ..LifetimeStats::default()
+        };
+        other.record(
+            &finished_game(Player::Computer(Piece::X)),
+            Difficulty::Easy,
+            None,
+        );
+        other.record_run(Difficulty::Easy, Duration::from_secs(20));
+
+        let mut merged_once = stats.clone();
+        merged_once.merge(&other);
+        let mut merged_twice = merged_once.clone();
+        merged_twice.merge(&other);
+
+        assert_eq!(merged_twice, merged_once);
+        assert_eq!(merged_twice.total().games(), 2);
+        assert_eq!(
+            merged_twice.best_times(Difficulty::Easy),
+            [Duration::from_secs(20)]
+        );
+
+        // A newer copy replaces the old one, and our own games coming back are left out
+        other.record(
+            &finished_game(Player::Human(Piece::X)),
+            Difficulty::Hard,
+            None,
+        );
+        other.merge(&merged_twice);
+        merged_twice.merge(&other);
+
+        assert_eq!(merged_twice.total().games(), 3);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::engine::{
+    complete_move, mcts_full_move, mcts_move, minimax_full_move, minimax_move, weighted_move,
+    win_or_block_move, EngineConfig, HeuristicWeights, MctsConfig,
+};
+use crate::game_model::{GameBoard, Move};
+use crate::random_source::ThreadRandom;
+
+// Anything that can pick a move can play the computer's side. Shared with the thread the GUI
+// chooses the computer's moves on.
+pub trait MoveStrategy: Sync {
+    fn choose_move(&self, board: &GameBoard) -> Option<usize>;
+
+    // Wild games also need the piece to place, and Three Men's Morris the piece to slide.
+    // Strategies that only pick a position get the move there that does them the most good.
+    fn choose_full_move(&self, board: &GameBoard) -> Option<Move> {
+        self.choose_move(board)
+            .and_then(|position| complete_move(board, position))
+    }
+}
+
+pub struct RandomStrategy;
+
+impl MoveStrategy for RandomStrategy {
+    fn choose_move(&self, board: &GameBoard) -> Option<usize> {
+        board.get_random_available_position()
+    }
+}
+
+pub struct WinOrBlockStrategy;
+
+impl MoveStrategy for WinOrBlockStrategy {
+    fn choose_move(&self, board: &GameBoard) -> Option<usize> {
+        win_or_block_move(board, &mut ThreadRandom)
+    }
+}
+
+pub struct MinimaxStrategy {
+    pub config: EngineConfig,
+}
+
+impl MinimaxStrategy {
+    pub const fn new(config: EngineConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl MoveStrategy for MinimaxStrategy {
+    fn choose_move(&self, board: &GameBoard) -> Option<usize> {
+        minimax_move(board, self.config, &mut ThreadRandom)
+    }
+
+    fn choose_full_move(&self, board: &GameBoard) -> Option<Move> {
+        minimax_full_move(board, self.config, &mut ThreadRandom)
+    }
+}
+
+pub struct MctsStrategy {
+    pub config: MctsConfig,
+}
+
+impl MctsStrategy {
+    pub const fn new(config: MctsConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl MoveStrategy for MctsStrategy {
+    fn choose_move(&self, board: &GameBoard) -> Option<usize> {
+        mcts_move(board, self.config, &mut ThreadRandom)
+    }
+
+    fn choose_full_move(&self, board: &GameBoard) -> Option<Move> {
+        mcts_full_move(board, self.config, &mut ThreadRandom)
+    }
+}
+
+pub struct WeightedStrategy {
+    pub weights: HeuristicWeights,
+}
+
+impl MoveStrategy for WeightedStrategy {
+    fn choose_move(&self, board: &GameBoard) -> Option<usize> {
+        weighted_move(board, &self.weights, &mut ThreadRandom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_model::execute_computer_turn;
+
+    // Stands in for a bot written outside this crate
+    struct FirstAvailableStrategy;
+
+    impl MoveStrategy for FirstAvailableStrategy {
+        fn choose_move(&self, board: &GameBoard) -> Option<usize> {
+            board.get_available_positions().first().copied()
+        }
+    }
+
+    #[test]
+    fn test_custom_strategy_drives_computer_turn() {
+        let mut game_board = GameBoard::new();
+
+        assert_eq!(
+            execute_computer_turn(&mut game_board, &FirstAvailableStrategy)
+                .map(|chosen| chosen.position),
+            Ok(1)
+        );
+        assert_eq!(
+            execute_computer_turn(&mut game_board, &FirstAvailableStrategy)
+                .map(|chosen| chosen.position),
+            Ok(2)
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt;
+
+// Rows of text under a header line, with each column padded to its widest cell. Columns holding
+// only numbers are right aligned so their digits line up.
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(ToString::to_string).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn add_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = markdown_row(&self.headers);
+
+        let alignments: Vec<&str> = (0..self.headers.len())
+            .map(|column| {
+                if self.is_numeric_column(column) {
+                    "---:"
+                } else {
+                    "---"
+                }
+            })
+            .collect();
+        markdown.push_str(&format!("| {} |\n", alignments.join(" | ")));
+
+        for row in &self.rows {
+            markdown.push_str(&markdown_row(row));
+        }
+
+        markdown
+    }
+
+    fn cell(row: &[String], column: usize) -> &str {
+        row.get(column).map_or("", String::as_str)
+    }
+
+    fn is_numeric_column(&self, column: usize) -> bool {
+        !self.rows.is_empty()
+            && self
+                .rows
+                .iter()
+                .all(|row| Self::cell(row, column).parse::<f64>().is_ok())
+    }
+
+    fn column_widths(&self) -> Vec<usize> {
+        (0..self.headers.len())
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .map(|row| Self::cell(row, column).chars().count())
+                    .chain([self.headers[column].chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    fn write_row(&self, f: &mut fmt::Formatter, row: &[String], widths: &[usize]) -> fmt::Result {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(column, &width)| {
+                let cell = Self::cell(row, column);
+                if self.is_numeric_column(column) {
+                    format!("{cell:>width$}")
+                } else {
+                    format!("{cell:<width$}")
+                }
+            })
+            .collect();
+
+        writeln!(f, "{}", cells.join("  ").trim_end())
+    }
+}
+
+fn markdown_row(row: &[String]) -> String {
+    format!("| {} |\n", row.join(" | "))
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let widths = self.column_widths();
+
+        self.write_row(f, &self.headers, &widths)?;
+
+        let rules: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+        writeln!(f, "{}", rules.join("  "))?;
+
+        for row in &self.rows {
+            self.write_row(f, row, &widths)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results_table() -> Table {
+        let mut table = Table::new(&["Opponent", "Wins"]);
+        table.add_row(vec!["random".to_string(), "38".to_string()]);
+        table.add_row(vec!["win-or-block".to_string(), "7".to_string()]);
+        table
+    }
+
+    #[test]
+    fn test_columns_line_up() {
+        assert_eq!(
+            results_table().to_string(),
+            "Opponent      Wins\n\
+             ------------  ----\n\
+             random          38\n\
+             win-or-block     7\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_marks_numeric_columns() {
+        assert_eq!(
+            results_table().to_markdown(),
+            "| Opponent | Wins |\n\
+             | --- | ---: |\n\
+             | random | 38 |\n\
+             | win-or-block | 7 |\n"
+        );
+    }
+}
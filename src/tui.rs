@@ -0,0 +1,359 @@
+use crate::exit_code::ExitReason;
+use crate::palette::Palette;
+use crate::registry::{self, StrategyEntry};
+use crate::stats;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use tic_tac_toe::frontend::{run_game, Frontend};
+use tic_tac_toe::game::{Game, Turn};
+use tic_tac_toe::game_model::{CellState, GameBoard, GameBoardBuilder, GameError, Scoreboard};
+
+// The tui mode's options, the few that make sense with a cursor on a flat board
+#[derive(clap::Args)]
+pub struct TuiArgs {
+    #[arg(
+        long,
+        value_name = "name",
+        value_parser = registry::strategy,
+        help = "Computer strategy to play against"
+    )]
+    strategy: Option<&'static StrategyEntry>,
+    #[arg(long, help = "Two people take turns at the same keyboard, no computer")]
+    hotseat: bool,
+    #[arg(
+        long,
+        value_name = "n",
+        value_parser = GameBoard::parse_size,
+        default_value_t = GameBoard::CLASSIC_SIZE,
+        help = "Play on an n by n board, from 3 to 5"
+    )]
+    size: usize,
+    #[arg(
+        long,
+        value_name = "name",
+        value_parser = Palette::from_name,
+        help = "Piece colors: standard, deuteranopia, protanopia or tritanopia"
+    )]
+    palette: Option<Palette>,
+}
+
+impl TuiArgs {
+    fn new_game_board(&self) -> GameBoard {
+        if self.hotseat {
+            GameBoard::new_human_vs_human().with_size(self.size)
+        } else {
+            GameBoardBuilder::new().size(self.size).build()
+        }
+    }
+}
+
+const HELP: &[(&str, &str)] = &[
+    ("Arrow keys", "Move the cursor"),
+    ("Enter, space", "Play in the cell under the cursor"),
+    ("u, r", "Undo or redo a turn"),
+    ("n", "Next game, once this one is over"),
+    ("?", "Show or hide this help"),
+    ("q, Esc", "Quit"),
+];
+
+// The full screen terminal as a frontend, kept over every game of the session
+struct Tui {
+    terminal: DefaultTerminal,
+    game: Game,
+    strategy: &'static StrategyEntry,
+    palette: Palette,
+    scoreboard: Scoreboard,
+    // Row and column, counted from the top left
+    cursor: (usize, usize),
+    // Whatever the last key or move has to say, in place of whose turn it is
+    status: String,
+    show_help: bool,
+}
+
+pub fn tui_main(args: &TuiArgs) -> ExitReason {
+    let mut tui = Tui {
+        terminal: ratatui::init(),
+        game: Game::new(args.new_game_board()),
+        strategy: args.strategy.unwrap_or_else(registry::default_strategy),
+        palette: args.palette.unwrap_or_default(),
+        scoreboard: Scoreboard::new(),
+        cursor: (0, 0),
+        status: String::new(),
+        show_help: true,
+    };
+
+    let exit_reason = loop {
+        tui.draw();
+        run_game(&mut tui);
+
+        let game_board = tui.game.board();
+        if !game_board.is_game_over() {
+            break ExitReason::for_game(game_board);
+        }
+
+        tui.scoreboard.record(game_board);
+        if !args.hotseat {
+            if let Err(error) = stats::record_game(game_board, tui.strategy) {
+                tui.status = format!("{} Statistics not saved: {error}", tui.status);
+            }
+        }
+
+        if !tui.wait_for_next_game() {
+            break ExitReason::for_game(tui.game.board());
+        }
+        tui.game = Game::new(args.new_game_board());
+        tui.status.clear();
+    };
+
+    ratatui::restore();
+    exit_reason
+}
+
+impl Tui {
+    fn draw(&mut self) {
+        let status = self.status_text();
+        let cursor = (self.game.turn() == Turn::Human).then_some(self.cursor);
+
+        // Nothing to be done about a terminal that cannot be drawn on, the next key redraws it
+        _ = self.terminal.draw(|frame| {
+            draw_screen(
+                frame,
+                self.game.board(),
+                cursor,
+                self.palette,
+                &self.scoreboard,
+                &status,
+                self.show_help,
+            );
+        });
+    }
+
+    fn status_text(&self) -> String {
+        if !self.status.is_empty() {
+            return self.status.clone();
+        }
+
+        let game_board = self.game.board();
+        let next_up = game_board.player_for_id(game_board.next_up);
+        match (self.game.turn(), next_up) {
+            (Turn::Over, _) => String::new(),
+            (Turn::Computer, _) => "The computer is thinking...".to_string(),
+            (Turn::Human, player) if game_board.is_human_vs_human() => {
+                format!("{} to play", player.piece())
+            }
+            (Turn::Human, player) => format!("Your turn, you are {}", player.piece()),
+        }
+    }
+
+    // Whether to play another game, with the finished one still on screen
+    fn wait_for_next_game(&mut self) -> bool {
+        loop {
+            self.draw();
+
+            let Some(key) = read_key() else {
+                return false;
+            };
+            match key.code {
+                KeyCode::Char('n') | KeyCode::Enter => return true,
+                KeyCode::Char('?') => self.show_help = !self.show_help,
+                _ if is_quit(key) => return false,
+                _ => (),
+            }
+        }
+    }
+}
+
+impl Frontend for Tui {
+    fn game(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    // One key at a time, so that the board is redrawn as the cursor moves
+    fn prompt_move(&mut self) -> Result<(), GameError> {
+        let key = read_key().ok_or(GameError::Quit)?;
+        self.status.clear();
+
+        let size = self.game.board().size();
+        let (row, col) = &mut self.cursor;
+        match key.code {
+            KeyCode::Up => *row = row.saturating_sub(1),
+            KeyCode::Down => *row = (*row + 1).min(size - 1),
+            KeyCode::Left => *col = col.saturating_sub(1),
+            KeyCode::Right => *col = (*col + 1).min(size - 1),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let chosen = self.game.board().own_move(*row * size + *col + 1);
+                self.game.play(chosen)?;
+            }
+            KeyCode::Char('u') => {
+                let undone = self.game.undo_turn();
+                if undone.is_empty() {
+                    self.status = "There is nothing to undo".to_string();
+                }
+            }
+            KeyCode::Char('r') => {
+                let redone = self.game.redo_turn();
+                if redone.is_empty() {
+                    self.status = "There is nothing to redo".to_string();
+                }
+            }
+            KeyCode::Char('?') => self.show_help = !self.show_help,
+            _ if is_quit(key) => return Err(GameError::Quit),
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn play_computer_move(&mut self) -> Result<(), GameError> {
+        let chosen = self.game.play_computer_turn(self.strategy)?;
+        self.status = format!(
+            "The computer played {} in position {}",
+            chosen.piece, chosen.position
+        );
+        Ok(())
+    }
+
+    fn render_board(&mut self, _board_before_turn: &GameBoard) {
+        self.draw();
+    }
+
+    // The board was drawn before the error, so it is drawn again with it
+    fn report_error(&mut self, error: &GameError) {
+        self.status = error.to_string();
+        self.draw();
+    }
+
+    fn announce_result(&mut self) {
+        if let Some(result) = self.game.result_text() {
+            self.status = format!("{result} Press n for the next game or q to quit.");
+        }
+    }
+}
+
+// The next key pressed, or a key that does nothing when the terminal is resized, so that the
+// screen is redrawn. None once input cannot be read at all, which ends the session.
+fn read_key() -> Option<KeyEvent> {
+    loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => return Some(key),
+            Ok(Event::Resize(..)) => return Some(KeyEvent::from(KeyCode::Null)),
+            Ok(_) => (),
+            Err(_) => return None,
+        }
+    }
+}
+
+// Raw mode leaves Ctrl+C to the program, so it quits like q does
+fn is_quit(key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => true,
+        KeyCode::Char('c') => key.modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    }
+}
+
+fn draw_screen(
+    frame: &mut Frame,
+    game_board: &GameBoard,
+    cursor: Option<(usize, usize)>,
+    palette: Palette,
+    scoreboard: &Scoreboard,
+    status: &str,
+    show_help: bool,
+) {
+    let size = u16::try_from(game_board.size()).unwrap_or(u16::MAX);
+    let [main, status_bar] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    // Three columns and a divider for each cell, a line and a divider for each row, and the border
+    let [board_column, side] =
+        Layout::horizontal([Constraint::Length(size * 4 + 1), Constraint::Min(0)]).areas(main);
+    let [board_area, _] = Layout::vertical([Constraint::Length(size * 2 + 1), Constraint::Min(0)])
+        .areas(board_column);
+
+    let scores = scoreboard.to_string();
+    let score_lines = u16::try_from(scores.lines().count()).unwrap_or(u16::MAX);
+    let [scores_area, help_area] =
+        Layout::vertical([Constraint::Length(score_lines + 2), Constraint::Min(0)]).areas(side);
+
+    frame.render_widget(
+        Paragraph::new(board_text(game_board, cursor, palette))
+            .block(Block::bordered().title(" Board ")),
+        board_area,
+    );
+    frame.render_widget(
+        Paragraph::new(scores).block(Block::bordered().title(" Scoreboard ")),
+        scores_area,
+    );
+
+    if show_help {
+        let help: Vec<Line> = HELP
+            .iter()
+            .map(|&(keys, action)| Line::from(vec![format!("{keys:<13}").bold(), action.into()]))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(help).block(Block::bordered().title(" Help ")),
+            help_area,
+        );
+    }
+
+    frame.render_widget(
+        Paragraph::new(format!(" {status}")).style(Style::new().reversed()),
+        status_bar,
+    );
+}
+
+// The cells between lines in the palette's colors, with the cursor's cell highlighted and a
+// winning line picked out
+fn board_text(
+    game_board: &GameBoard,
+    cursor: Option<(usize, usize)>,
+    palette: Palette,
+) -> Text<'static> {
+    let size = game_board.size();
+    let winning_positions = game_board
+        .winning_line()
+        .map(|line| line.positions)
+        .unwrap_or_default();
+    let mut lines = Vec::new();
+
+    for row in 0..size {
+        if row > 0 {
+            lines.push(Line::from(vec!["───"; size].join("┼")));
+        }
+
+        let mut spans = Vec::new();
+        for col in 0..size {
+            if col > 0 {
+                spans.push(Span::raw("│"));
+            }
+
+            let position = row * size + col + 1;
+            let (mark, mut style) = match game_board.get_cell_at_position(position) {
+                Some(&CellState::Occupied(piece)) => {
+                    let mut style = Style::new().fg(rgb(palette.piece_color(piece)));
+                    if winning_positions.contains(&position) {
+                        style = style.bold().bg(rgb(palette.piece_tint(piece)));
+                    }
+                    (piece.to_string(), style)
+                }
+                Some(CellState::Blocked) => ("#".to_string(), Style::new()),
+                Some(CellState::Empty) | None => (" ".to_string(), Style::new()),
+            };
+            if cursor == Some((row, col)) {
+                style = style.reversed();
+            }
+            spans.push(Span::styled(format!(" {mark} "), style));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    Text::from(lines)
+}
+
+const fn rgb((red, green, blue): (u8, u8, u8)) -> Color {
+    Color::Rgb(red, green, blue)
+}
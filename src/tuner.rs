@@ -0,0 +1,337 @@
+use crate::exit_code::ExitReason;
+use crate::registry::StrategyEntry;
+use std::fs;
+use tic_tac_toe::engine::HeuristicWeights;
+use tic_tac_toe::game_model::{Difficulty, GameBoard, Piece};
+use tic_tac_toe::random_source::{RandomSource, ThreadRandom};
+use tic_tac_toe::strategy::{MoveStrategy, RandomStrategy, WeightedStrategy, WinOrBlockStrategy};
+use tic_tac_toe::table::Table;
+
+#[derive(clap::Args)]
+pub struct TuneArgs {
+    #[arg(
+        long,
+        value_name = "n",
+        default_value_t = 15,
+        help = "How many generations to evolve"
+    )]
+    generations: usize,
+    #[arg(
+        long,
+        value_name = "file",
+        default_value = "tuned-strategy.txt",
+        help = "Where to save the best weights"
+    )]
+    output: String,
+    #[arg(long, help = "Print the final summary as markdown tables")]
+    markdown: bool,
+}
+
+const POPULATION: usize = 16;
+// The best genomes are carried over unchanged into the next generation
+const ELITES: usize = 2;
+const GAMES_PER_OPPONENT: usize = 40;
+const MUTATION_RATE: f64 = 0.2;
+const MUTATION_SIZE: f64 = 2.0;
+const MAX_WEIGHT: f64 = 10.0;
+// Opponents are kept fast so that every generation plays hundreds of games
+const OPPONENTS: [(&str, &dyn MoveStrategy); 2] = [
+    ("random", &RandomStrategy),
+    ("win-or-block", &WinOrBlockStrategy),
+];
+
+// Evolves the weighted heuristic's weights by self-play against the built-in strategies
+pub fn tune_main(args: &TuneArgs) -> ExitReason {
+    let output_path = &args.output;
+
+    let best = evolve(args.generations, &mut ThreadRandom);
+    print_summary(&best, args.markdown);
+
+    match fs::write(output_path, format_weights(&best)) {
+        Ok(()) => {
+            println!("\nSaved the best weights to '{output_path}'");
+            println!("Play against them with --strategy tuned:{output_path}");
+            ExitReason::Completed
+        }
+        Err(error) => {
+            println!("Could not write '{output_path}': {error}");
+            ExitReason::InvalidUsage
+        }
+    }
+}
+
+fn evolve(generations: usize, random: &mut dyn RandomSource) -> HeuristicWeights {
+    let mut population: Vec<[f64; 7]> = (0..POPULATION).map(|_| random_genome(random)).collect();
+
+    for generation in 1..=generations {
+        let mut ranked: Vec<([f64; 7], f64)> = population
+            .iter()
+            .map(|&genome| (genome, fitness(genome)))
+            .collect();
+        ranked.sort_by(|(_, first), (_, second)| second.total_cmp(first));
+
+        println!(
+            "Generation {generation}: best fitness {:.2}",
+            ranked.first().map_or(0.0, |&(_, fitness)| fitness)
+        );
+
+        population = ranked
+            .iter()
+            .take(ELITES)
+            .map(|&(genome, _)| genome)
+            .collect();
+
+        while population.len() < POPULATION {
+            let first_parent = select(&ranked, random);
+            let second_parent = select(&ranked, random);
+            population.push(mutate(
+                crossover(first_parent, second_parent, random),
+                random,
+            ));
+        }
+    }
+
+    // Elites come first, so this is the best genome of the last generation
+    HeuristicWeights::from_values(clamp(population[0]))
+}
+
+fn random_genome(random: &mut dyn RandomSource) -> [f64; 7] {
+    clamp(std::array::from_fn(|_| random.next_fraction() * MAX_WEIGHT))
+}
+
+// Points per game against each built-in opponent, 1 for a win and a half for a draw
+fn fitness(genome: [f64; 7]) -> f64 {
+    let candidate = WeightedStrategy {
+        weights: HeuristicWeights::from_values(genome),
+    };
+
+    let points: f64 = OPPONENTS
+        .iter()
+        .map(|(_, opponent)| {
+            let (wins, draws, _) = record_against(&candidate, *opponent);
+            count_to_f64(wins) + count_to_f64(draws) / 2.0
+        })
+        .sum();
+
+    points / count_to_f64(OPPONENTS.len() * GAMES_PER_OPPONENT)
+}
+
+// Wins, draws and losses for the candidate, alternating who moves first
+fn record_against(
+    candidate: &dyn MoveStrategy,
+    opponent: &dyn MoveStrategy,
+) -> (usize, usize, usize) {
+    let (mut wins, mut draws, mut losses) = (0, 0, 0);
+
+    for game in 0..GAMES_PER_OPPONENT {
+        let (candidate_piece, winner) = if game % 2 == 0 {
+            (Piece::X, play(candidate, opponent))
+        } else {
+            (Piece::O, play(opponent, candidate))
+        };
+
+        match winner {
+            Some(piece) if piece == candidate_piece => wins += 1,
+            Some(_) => losses += 1,
+            None => draws += 1,
+        }
+    }
+
+    (wins, draws, losses)
+}
+
+fn count_to_f64(count: usize) -> f64 {
+    f64::from(u32::try_from(count).unwrap_or(u32::MAX))
+}
+
+fn print_summary(weights: &HeuristicWeights, markdown: bool) {
+    let mut weights_table = Table::new(&["Weight", "Value"]);
+    for (name, value) in HeuristicWeights::NAMES.iter().zip(weights.values()) {
+        weights_table.add_row(vec![name.to_string(), format!("{value:.2}")]);
+    }
+
+    let candidate = WeightedStrategy { weights: *weights };
+    let mut results_table = Table::new(&["Opponent", "Wins", "Draws", "Losses"]);
+    for (name, opponent) in OPPONENTS {
+        let (wins, draws, losses) = record_against(&candidate, opponent);
+        results_table.add_row(vec![
+            name.to_string(),
+            wins.to_string(),
+            draws.to_string(),
+            losses.to_string(),
+        ]);
+    }
+
+    let render = |table: &Table| {
+        if markdown {
+            table.to_markdown()
+        } else {
+            table.to_string()
+        }
+    };
+
+    println!("\nBest weights:\n{}", render(&weights_table));
+    print!(
+        "Results over {GAMES_PER_OPPONENT} games per opponent:\n{}",
+        render(&results_table)
+    );
+}
+
+fn play(x_strategy: &dyn MoveStrategy, o_strategy: &dyn MoveStrategy) -> Option<Piece> {
+    let mut game_board = GameBoard::new_computer_vs_computer();
+
+    while !game_board.is_game_over() {
+        let strategy = match game_board.player_for_id(game_board.next_up).piece() {
+            Piece::X => x_strategy,
+            Piece::O => o_strategy,
+        };
+
+        let Some(position) = strategy.choose_move(&game_board) else {
+            break;
+        };
+        if game_board.play_next_up_at_position(position).is_err() {
+            break;
+        }
+    }
+
+    game_board
+        .determine_winning_player()
+        .map(|player| *player.piece())
+}
+
+// The fitter of two genomes picked at random
+fn select(ranked: &[([f64; 7], f64)], random: &mut dyn RandomSource) -> [f64; 7] {
+    let first = random.next_index(ranked.len());
+    let second = random.next_index(ranked.len());
+
+    // Ranked best first
+    ranked[first.min(second)].0
+}
+
+fn crossover(first: [f64; 7], second: [f64; 7], random: &mut dyn RandomSource) -> [f64; 7] {
+    std::array::from_fn(|gene| {
+        if random.next_index(2) == 0 {
+            first[gene]
+        } else {
+            second[gene]
+        }
+    })
+}
+
+fn mutate(genome: [f64; 7], random: &mut dyn RandomSource) -> [f64; 7] {
+    let mutated = genome.map(|gene| {
+        if random.next_fraction() < MUTATION_RATE {
+            gene + (random.next_fraction() - 0.5) * MUTATION_SIZE
+        } else {
+            gene
+        }
+    });
+
+    clamp(mutated)
+}
+
+// Weights stay between 0 and MAX_WEIGHT, the blunder rate is a probability
+fn clamp(genome: [f64; 7]) -> [f64; 7] {
+    let mut clamped = genome.map(|gene| gene.clamp(0.0, MAX_WEIGHT));
+    clamped[6] = clamped[6].min(1.0);
+    clamped
+}
+
+fn format_weights(weights: &HeuristicWeights) -> String {
+    let mut text =
+        String::from("# Tuned heuristic weights, play them with --strategy tuned:<file>\n");
+
+    for (name, value) in HeuristicWeights::NAMES.iter().zip(weights.values()) {
+        text.push_str(format!("{name}={value}\n").as_str());
+    }
+
+    text
+}
+
+fn parse_weights(text: &str) -> Result<HeuristicWeights, String> {
+    let mut values: [Option<f64>; 7] = [None; 7];
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((name, value)) = line.split_once('=') else {
+            return Err(format!("Expected name=value, got '{line}'"));
+        };
+        let Some(index) = HeuristicWeights::NAMES
+            .iter()
+            .position(|&known| known == name.trim())
+        else {
+            return Err(format!("Unknown weight '{}'", name.trim()));
+        };
+        let Ok(value) = value.trim().parse() else {
+            return Err(format!("'{}' is not a number", value.trim()));
+        };
+
+        values[index] = Some(value);
+    }
+
+    let mut weights = [0.0; 7];
+    for (index, value) in values.into_iter().enumerate() {
+        let Some(value) = value else {
+            return Err(format!(
+                "Missing weight '{}'",
+                HeuristicWeights::NAMES[index]
+            ));
+        };
+        weights[index] = value;
+    }
+
+    Ok(HeuristicWeights::from_values(weights))
+}
+
+// Tuned strategies are picked at runtime, so their entries live for the rest of the program like
+// the registered ones
+pub fn load_entry(path: &str) -> Result<&'static StrategyEntry, String> {
+    let text =
+        fs::read_to_string(path).map_err(|error| format!("Could not read '{path}': {error}"))?;
+    let strategy: &'static WeightedStrategy = Box::leak(Box::new(WeightedStrategy {
+        weights: parse_weights(&text)?,
+    }));
+
+    Ok(Box::leak(Box::new(StrategyEntry {
+        name: "tuned",
+        description: "Weighted heuristic evolved with the tune command",
+        difficulty: Difficulty::Medium,
+        implementation: strategy,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tic_tac_toe::random_source::SeededRandom;
+
+    #[test]
+    fn test_weights_round_trip_through_text() {
+        let weights = HeuristicWeights::from_values([9.5, 8.0, 4.25, 3.0, 2.0, 1.0, 0.1]);
+
+        assert_eq!(parse_weights(&format_weights(&weights)), Ok(weights));
+    }
+
+    #[test]
+    fn test_incomplete_weights_are_rejected() {
+        assert!(parse_weights("win=1\nblock=2").is_err());
+        assert!(parse_weights("speed=1").is_err());
+    }
+
+    #[test]
+    fn test_mutation_keeps_genomes_in_range() {
+        let mut random = SeededRandom::new(3);
+
+        for _ in 0..100 {
+            let genome = mutate(random_genome(&mut random), &mut random);
+
+            assert!(genome
+                .iter()
+                .all(|&gene| (0.0..=MAX_WEIGHT).contains(&gene)));
+            assert!(genome[6] <= 1.0);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::exit_code::ExitReason;
+use crate::registry::{self, StrategyEntry};
+use std::thread;
+use std::time::Duration;
+use tic_tac_toe::game::{Game, GameObserver};
+use tic_tac_toe::game_model::{GameBoard, GameState, MoveRecord, Piece};
+
+#[derive(clap::Args)]
+pub struct WatchArgs {
+    #[arg(
+        long,
+        value_name = "name",
+        value_parser = registry::strategy,
+        default_value = registry::default_strategy().name,
+        help = "Strategy playing X, who moves first"
+    )]
+    x_strategy: &'static StrategyEntry,
+    #[arg(
+        long,
+        value_name = "name",
+        value_parser = registry::strategy,
+        default_value = registry::default_strategy().name,
+        help = "Strategy playing O"
+    )]
+    o_strategy: &'static StrategyEntry,
+}
+
+const MOVE_DELAY: Duration = Duration::from_millis(800);
+
+// Prints each move with the board after it, and the result at the end
+struct Commentary {
+    x_name: &'static str,
+    o_name: &'static str,
+}
+
+impl GameObserver for Commentary {
+    fn on_move_played(&mut self, game_board: &GameBoard, record: &MoveRecord) {
+        let piece = game_board.piece_played(record);
+        let name = match piece {
+            Piece::X => self.x_name,
+            Piece::O => self.o_name,
+        };
+
+        println!(
+            "\n{name} played {piece} in position: {}\n{game_board}",
+            record.position
+        );
+    }
+
+    fn on_game_over(&mut self, _game_board: &GameBoard, state: &GameState) {
+        match state {
+            GameState::Winner(player, line) => println!("\n{} wins on the {line}", player.piece()),
+            GameState::Draw | GameState::InProgress => println!("\nThe game is a draw"),
+        }
+    }
+}
+
+// Plays one game between two computer strategies, pausing after each move so it can be followed
+pub fn watch_main(args: &WatchArgs) -> ExitReason {
+    let (x_strategy, o_strategy) = (args.x_strategy, args.o_strategy);
+
+    let mut game = Game::new(GameBoard::new_computer_vs_computer());
+    println!(
+        "\n{} (X) vs {} (O):\n{}",
+        x_strategy.name,
+        o_strategy.name,
+        game.board()
+    );
+    game.add_observer(Commentary {
+        x_name: x_strategy.name,
+        o_name: o_strategy.name,
+    });
+
+    while !game.board().is_game_over() {
+        thread::sleep(MOVE_DELAY);
+
+        let strategy = match game.board().next_piece() {
+            Piece::X => x_strategy,
+            Piece::O => o_strategy,
+        };
+
+        if let Err(error) = game.play_computer_turn(strategy) {
+            println!("\nError: {error}");
+            return ExitReason::InvalidInput;
+        }
+    }
+
+    ExitReason::for_game(game.board())
+}